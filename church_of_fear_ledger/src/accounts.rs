@@ -0,0 +1,185 @@
+//! Account totals of a `MoralLedger`: running aggregates per actor, the
+//! dispute book whose outcomes they reflect, and the reward schedule that
+//! prices their CHURCH recommendations. The ledger hands every committed
+//! deed to its `AccountBook`; the chain itself does not depend on them.
+
+use crate::checkpoint::AccountAggregate;
+use crate::deed::DeedEvent;
+use crate::index::DeedIndex;
+use crate::migrate::MIGRATION_DEED_TYPE;
+use crate::store::{LedgerStore, StoreError};
+use deed_schema::dispute::is_dispute_step;
+use deed_schema::{CorrectableDeed, DisputeBook, DisputeStep, ReviewPanel, ANCHOR_DEED_TYPE};
+use std::collections::BTreeMap;
+use token_journal::RewardSchedule;
+
+#[derive(Debug)]
+pub(crate) struct AccountBook {
+    totals: BTreeMap<String, AccountAggregate>,
+    disputes: Option<DisputeBook>,
+    schedule: RewardSchedule,
+}
+
+impl Default for AccountBook {
+    fn default() -> Self {
+        Self { totals: BTreeMap::new(), disputes: None, schedule: RewardSchedule::builtin().clone() }
+    }
+}
+
+impl AccountBook {
+    /// Resume from a checkpoint's totals, dispute book and schedule (`None`
+    /// for the builtin one).
+    pub(crate) fn resume(
+        totals: BTreeMap<String, AccountAggregate>,
+        disputes: Option<DisputeBook>,
+        schedule: Option<RewardSchedule>,
+    ) -> Self {
+        Self { totals, disputes, schedule: schedule.unwrap_or_else(|| RewardSchedule::builtin().clone()) }
+    }
+
+    /// No totals, under the same review panel and schedule; where a replay
+    /// from genesis starts.
+    pub(crate) fn fresh(&self) -> Self {
+        Self {
+            totals: BTreeMap::new(),
+            disputes: self.disputes.as_ref().map(|book| DisputeBook::new(book.panel().clone())),
+            schedule: self.schedule.clone(),
+        }
+    }
+
+    pub(crate) fn get(&self, actor_id: &str) -> Option<&AccountAggregate> {
+        self.totals.get(actor_id)
+    }
+
+    pub(crate) fn remove(&mut self, actor_id: &str) -> Option<AccountAggregate> {
+        self.totals.remove(actor_id)
+    }
+
+    pub(crate) fn totals(&self) -> &BTreeMap<String, AccountAggregate> {
+        &self.totals
+    }
+
+    pub(crate) fn disputes(&self) -> Option<&DisputeBook> {
+        self.disputes.as_ref()
+    }
+
+    pub(crate) fn schedule(&self) -> &RewardSchedule {
+        &self.schedule
+    }
+
+    /// The schedule a checkpoint records: none for the builtin one.
+    pub(crate) fn checkpoint_schedule(&self) -> Option<RewardSchedule> {
+        (self.schedule != *RewardSchedule::builtin()).then(|| self.schedule.clone())
+    }
+
+    /// Decide disputes with `panel`. Returns `false` if it already does;
+    /// otherwise the totals must be replayed.
+    pub(crate) fn set_panel(&mut self, panel: ReviewPanel) -> bool {
+        if self.disputes.as_ref().is_some_and(|book| *book.panel() == panel) {
+            return false;
+        }
+        self.disputes = Some(DisputeBook::new(panel));
+        true
+    }
+
+    /// Price recommendations with `schedule`. Returns `false` if it already
+    /// does; otherwise the totals must be replayed.
+    pub(crate) fn set_schedule(&mut self, schedule: RewardSchedule) -> bool {
+        if self.schedule == schedule {
+            return false;
+        }
+        self.schedule = schedule;
+        true
+    }
+
+    /// Show `deed` with the outcome of any dispute over it.
+    pub(crate) fn apply_standing(&self, deed: &mut DeedEvent) {
+        if let Some(book) = &self.disputes {
+            book.standing(&deed.event_id.to_string()).apply(deed);
+        }
+    }
+
+    /// Fold the deed at `seq` in as readers see it: an applied correction or
+    /// a dispute decision swaps the old contribution of its target for the
+    /// new one, and neither corrections, dispute steps nor bookkeeping deeds
+    /// (migration markers, peer anchors) count as deeds themselves.
+    pub(crate) fn fold<S: LedgerStore>(&mut self, index: &DeedIndex, store: &S, seq: u64, event: &DeedEvent) -> Result<(), StoreError> {
+        if event.deed_type == MIGRATION_DEED_TYPE || event.deed_type == ANCHOR_DEED_TYPE {
+            return Ok(());
+        }
+        if is_dispute_step(&event.deed_type) {
+            if self.disputes.is_some() {
+                self.fold_dispute_step(index, store, seq, event)?;
+            }
+            return Ok(());
+        }
+        if event.correction().is_none() {
+            self.totals.entry(event.actor_id.clone()).or_default().apply(event, &self.schedule);
+            return Ok(());
+        }
+        let Some(target) = index.correction_target(seq) else {
+            return Ok(());
+        };
+        if let (Some(mut before), Some(mut after)) = (index.effective(store, target, seq)?, index.effective(store, target, seq + 1)?) {
+            if let Some(book) = &self.disputes {
+                let standing = book.standing(&before.event_id.to_string());
+                standing.apply(&mut before);
+                standing.apply(&mut after);
+            }
+            self.totals.entry(before.actor_id.clone()).or_default().correct(&before, &after, &self.schedule);
+        }
+        Ok(())
+    }
+
+    /// Fold every deed of `store` from `from` up to `to` in.
+    pub(crate) fn fold_range<S: LedgerStore>(&mut self, index: &DeedIndex, store: &S, from: u64, to: u64) -> Result<(), StoreError> {
+        for (seq, event) in (from..to).zip(store.read_range(from, to)?) {
+            self.fold(index, store, seq, &event)?;
+        }
+        Ok(())
+    }
+
+    fn fold_dispute_step<S: LedgerStore>(&mut self, index: &DeedIndex, store: &S, seq: u64, event: &DeedEvent) -> Result<(), StoreError> {
+        let Some(book) = &mut self.disputes else {
+            return Ok(());
+        };
+        let mut failed = None;
+        let step = book.apply(event, |id| {
+            earlier_deed(index, store, id, seq).unwrap_or_else(|e| {
+                failed = Some(e);
+                None
+            })
+        });
+        if let Some(e) = failed {
+            return Err(e);
+        }
+        match step {
+            Ok(Some(DisputeStep::Decided { dispute, deed, accused, previous, verdict, before, after })) => {
+                log::info!("dispute {} over deed {} decided: {:?}", dispute, deed, verdict);
+                let Some(target) = index.position_of(&deed) else {
+                    return Ok(());
+                };
+                let account = self.totals.entry(accused).or_default();
+                account.decide(previous.as_ref(), &verdict);
+                if let Some(current) = index.effective(store, target, seq)? {
+                    let (mut old, mut new) = (current.clone(), current);
+                    before.apply(&mut old);
+                    after.apply(&mut new);
+                    account.correct(&old, &new, &self.schedule);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("dispute step {} at {} ignored: {}", event.event_id, seq, e),
+        }
+        Ok(())
+    }
+}
+
+/// The deed `event_id` as it stood just before position `seq`, if it
+/// precedes it; dispute outcomes are not applied.
+pub(crate) fn earlier_deed<S: LedgerStore>(index: &DeedIndex, store: &S, event_id: &str, seq: u64) -> Result<Option<DeedEvent>, StoreError> {
+    match index.position_of(event_id).filter(|&target| target < seq) {
+        Some(target) => index.effective(store, target, seq),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,42 @@
+//! Rewrite a JSONL moral ledger under the current schema.
+//!
+//!   ledger_migrate <old.jsonl> <new.jsonl>          upgrade and re-chain into a new file
+//!   ledger_migrate <old.jsonl> <new.jsonl> verify   check a migrated file against its source
+
+use church_of_fear_ledger::{migrate_ledger, verify_migration};
+use deed_schema::Upgraders;
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (Some(source), Some(target)) = (args.first().map(PathBuf::from), args.get(1).map(PathBuf::from)) else {
+        eprintln!("usage: ledger_migrate <old.jsonl> <new.jsonl> [verify]");
+        std::process::exit(2);
+    };
+    let upgraders = Upgraders::new();
+
+    match args.get(2).map(String::as_str) {
+        None => {
+            let report = migrate_ledger(&source, &target, &upgraders)?;
+            println!(
+                "migrated {} deeds ({} upgraded) to schema v{}",
+                report.deeds, report.upgraded, report.link.to_version
+            );
+            println!("old head {} (tree size {}, root {})", report.link.source_head, report.link.source_tree.tree_size, report.link.source_tree.root);
+            println!("new head {}", report.head);
+        }
+        Some("verify") => {
+            if !verify_migration(&source, &target, &upgraders)? {
+                println!("verification FAILED");
+                std::process::exit(1);
+            }
+            println!("ok");
+        }
+        Some(command) => {
+            eprintln!("unknown command {}", command);
+            std::process::exit(2);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Maintenance for segmented ledgers (`MoralLedger::open_segmented`).
+//!
+//!   ledger_segments <dir> archive      gzip every closed segment
+//!   ledger_segments <dir> verify [n]   check the manifest, or fully check segment n
+
+use church_of_fear_ledger::store::{SegmentedStore, DEFAULT_DEEDS_PER_SEGMENT};
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (Some(dir), Some(command)) = (args.first(), args.get(1)) else {
+        eprintln!("usage: ledger_segments <dir> archive | verify [segment]");
+        std::process::exit(2);
+    };
+    let mut store = SegmentedStore::open(PathBuf::from(dir), DEFAULT_DEEDS_PER_SEGMENT)?;
+
+    let ok = match (command.as_str(), args.get(2)) {
+        ("archive", None) => {
+            let archived = store.archive_closed()?;
+            println!("archived {} segment(s): {:?}", archived.len(), archived);
+            true
+        }
+        ("verify", None) => store.verify_manifest()?,
+        ("verify", Some(segment)) => store.verify_segment(segment.parse()?)?,
+        _ => {
+            eprintln!("unknown command {}", command);
+            std::process::exit(2);
+        }
+    };
+    if !ok {
+        println!("verification FAILED");
+        std::process::exit(1);
+    }
+    println!("ok");
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! One replicating ledger node: serves its chain and pulls from its peers.
+//!
+//! Run several locally, e.g.
+//!   ledger_sync_node a.jsonl 127.0.0.1:7101 127.0.0.1:7102
+//!   ledger_sync_node b.jsonl 127.0.0.1:7102 127.0.0.1:7101
+//! Fork evidence goes to `<ledger>.forks.jsonl`. Checkpoints signed by the
+//! keys in `<ledger>.notaries.json`, if present, outrank chain length when a
+//! fork is settled.
+
+use church_of_fear_ledger::sync::{pull_from_peer, serve, SyncOutcome};
+use church_of_fear_ledger::{KeyRegistry, MoralLedger};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const PULL_INTERVAL: Duration = Duration::from_secs(5);
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    let mut args = std::env::args().skip(1);
+    let (Some(ledger_path), Some(listen)) = (args.next(), args.next()) else {
+        eprintln!("usage: ledger_sync_node <ledger.jsonl> <listen-addr> [peer-addr...]");
+        std::process::exit(2);
+    };
+    let peers: Vec<String> = args.collect();
+    let ledger_path = PathBuf::from(ledger_path);
+    let mut fork_log = ledger_path.clone().into_os_string();
+    fork_log.push(".forks.jsonl");
+    let fork_log = PathBuf::from(fork_log);
+    let mut notaries_path = ledger_path.clone().into_os_string();
+    notaries_path.push(".notaries.json");
+    let notaries_path = PathBuf::from(notaries_path);
+    let notaries = if notaries_path.exists() { KeyRegistry::load(&notaries_path)? } else { KeyRegistry::new() };
+
+    let ledger = Arc::new(Mutex::new(MoralLedger::open_or_create(ledger_path)?));
+    let listener = TcpListener::bind(&listen)?;
+    let served = Arc::clone(&ledger);
+    std::thread::spawn(move || serve(listener, served));
+
+    loop {
+        for peer in &peers {
+            match pull_from_peer(&ledger, peer, &notaries, &fork_log) {
+                Ok(SyncOutcome::UpToDate) | Ok(SyncOutcome::PeerBehind) => {}
+                Ok(outcome) => log::info!("sync with {}: {:?}", peer, outcome),
+                Err(e) => log::warn!("sync with {} failed: {}", peer, e),
+            }
+        }
+        std::thread::sleep(PULL_INTERVAL);
+    }
+}
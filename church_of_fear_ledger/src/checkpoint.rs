@@ -0,0 +1,194 @@
+//! Signed checkpoints – a node-attested summary of the ledger up to some
+//! position, so startup and account queries only replay the tail.
+//!
+//! Checkpoints are appended to `<ledger>.ckpt.jsonl`, one per line. On open
+//! the newest line whose signature verifies under the node key *and* whose
+//! `chain_head` matches the record at `event_count - 1` is used; anything
+//! else is ignored and the ledger falls back to a full replay.
+
+use crate::deed::DeedEvent;
+use crate::store::{ResumePoint, StoreError};
+use deed_schema::canonical::to_canonical_string;
+use deed_schema::{DeedSignature, DisputeBook, Verdict};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use token_journal::RewardSchedule;
+
+/// Appends between automatic checkpoints.
+pub const CHECKPOINT_EVERY: u64 = 1024;
+
+/// Per-actor totals, updated deed by deed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountAggregate {
+    pub deeds: u64,
+    pub good_deeds: u64,                    // deeds with a CHURCH recommendation
+    pub harm_flags: u64,                    // deeds with life_harm_flag set
+    pub church_recommended: u64,            // advisory CHURCH total
+    pub last_timestamp: i64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub disputes_upheld: u64,               // decided disputes against this actor, not cleared
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+impl AccountAggregate {
+    pub fn apply(&mut self, event: &DeedEvent, schedule: &RewardSchedule) {
+        let recommendation = event.church_recommendation(schedule);
+        self.deeds += 1;
+        if recommendation > 0 {
+            self.good_deeds += 1;
+        }
+        if event.life_harm_flag {
+            self.harm_flags += 1;
+        }
+        self.church_recommended += recommendation;
+        self.last_timestamp = self.last_timestamp.max(event.timestamp);
+    }
+
+    /// Replace the contribution of a deed as it was (`before`) with that of
+    /// its corrected form (`after`). The deed count is unchanged.
+    pub fn correct(&mut self, before: &DeedEvent, after: &DeedEvent, schedule: &RewardSchedule) {
+        let (old, new) = (before.church_recommendation(schedule), after.church_recommendation(schedule));
+        self.good_deeds = self.good_deeds + u64::from(new > 0) - u64::from(old > 0);
+        self.harm_flags = self.harm_flags + u64::from(after.life_harm_flag) - u64::from(before.life_harm_flag);
+        self.church_recommended = self.church_recommended + new - old;
+    }
+
+    /// Count a dispute decision against this actor; an appeal that ends in
+    /// `clear` takes the earlier upheld outcome back.
+    pub fn decide(&mut self, previous: Option<&Verdict>, verdict: &Verdict) {
+        let upheld = |v: Option<&Verdict>| u64::from(v.is_some_and(|v| *v != Verdict::Clear));
+        self.disputes_upheld = self.disputes_upheld + upheld(Some(verdict)) - upheld(previous);
+    }
+}
+
+/// The key a node signs its checkpoints with.
+#[derive(Debug)]
+pub struct NodeKey {
+    pub key_id: String,                     // DID URL, e.g. did:bostrom:node-1#checkpoint
+    pub signing_key: SigningKey,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub event_count: u64,
+    pub chain_head: String,                 // self_hash of record event_count - 1
+    pub merkle_root: String,                // tree head over the same records
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byte_offset: Option<u64>,           // end of that record in a JSONL store
+    pub created_at: i64,
+    pub accounts: BTreeMap<String, AccountAggregate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disputes: Option<DisputeBook>,      // dispute state the accounts were folded with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reward_schedule: Option<RewardSchedule>, // schedule the accounts were folded with, if not the builtin one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<DeedSignature>,   // node signature over every other field
+}
+
+impl Checkpoint {
+    fn signing_bytes(&self) -> Result<Vec<u8>, StoreError> {
+        let mut unsigned = self.clone();
+        unsigned.signature = None;
+        Ok(to_canonical_string(&serde_json::to_value(&unsigned)?).into_bytes())
+    }
+
+    pub fn sign(mut self, node: &NodeKey) -> Result<Self, StoreError> {
+        let signature = node.signing_key.sign(&self.signing_bytes()?);
+        self.signature =
+            Some(DeedSignature { key_id: node.key_id.clone(), signature: hex::encode(signature.to_bytes()) });
+        Ok(self)
+    }
+
+    pub fn verify(&self, key: &VerifyingKey) -> bool {
+        let Some(sig) = &self.signature else {
+            return false;
+        };
+        let Some(bytes) = hex::decode(&sig.signature).ok().and_then(|b| <[u8; 64]>::try_from(b).ok()) else {
+            return false;
+        };
+        match self.signing_bytes() {
+            Ok(msg) => key.verify(&msg, &Signature::from_bytes(&bytes)).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    pub fn resume_point(&self) -> Option<ResumePoint> {
+        Some(ResumePoint {
+            event_count: self.event_count,
+            byte_offset: self.byte_offset?,
+            chain_head: self.chain_head.clone(),
+        })
+    }
+}
+
+/// Where a ledger writes its signed checkpoints, the node key it signs them
+/// with, and how many deeds it has committed since the last one.
+#[derive(Debug)]
+pub(crate) struct Checkpointer {
+    path: PathBuf,
+    node: NodeKey,
+    since: u64,
+}
+
+impl Checkpointer {
+    pub(crate) fn new(path: PathBuf, node: NodeKey) -> Self {
+        Self { path, node, since: 0 }
+    }
+
+    /// Count one committed deed; `true` once a checkpoint is due.
+    pub(crate) fn count(&mut self) -> bool {
+        self.since += 1;
+        self.since >= CHECKPOINT_EVERY
+    }
+
+    /// Sign and persist `checkpoint`, and count again from it.
+    pub(crate) fn write(&mut self, checkpoint: Checkpoint) -> Result<Checkpoint, StoreError> {
+        let checkpoint = checkpoint.sign(&self.node)?;
+        append_checkpoint(&self.path, &checkpoint)?;
+        self.since = 0;
+        Ok(checkpoint)
+    }
+
+    /// Every checkpoint this node signed, newest first.
+    pub(crate) fn signed(&self) -> Result<Vec<Checkpoint>, StoreError> {
+        signed_checkpoints(&self.path, &self.node.signing_key.verifying_key())
+    }
+}
+
+/// Append one checkpoint line and fsync it.
+pub fn append_checkpoint(path: &Path, checkpoint: &Checkpoint) -> Result<(), StoreError> {
+    let mut line = serde_json::to_vec(checkpoint)?;
+    line.push(b'\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&line)?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Newest checkpoint in `path` signed by `key`; unreadable lines (e.g. a
+/// torn last write) and foreign signatures are skipped.
+pub fn latest_signed_checkpoint(path: &Path, key: &VerifyingKey) -> Result<Option<Checkpoint>, StoreError> {
+    Ok(signed_checkpoints(path, key)?.into_iter().next())
+}
+
+/// Every checkpoint in `path` signed by `key`, newest first.
+pub fn signed_checkpoints(path: &Path, key: &VerifyingKey) -> Result<Vec<Checkpoint>, StoreError> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(bytes
+        .split(|b| *b == b'\n')
+        .rev()
+        .filter_map(|line| serde_json::from_slice::<Checkpoint>(line).ok())
+        .filter(|cp| cp.verify(key))
+        .collect())
+}
@@ -0,0 +1,268 @@
+//! Deeds as W3C Verifiable Credentials (VC Data Model 2.0).
+//!
+//! The node DID is the issuer, the actor is the credential subject and the
+//! ledger row is carried as-is, so its `self_hash` can be recomputed. The
+//! evidence is the deed's chain position with a Merkle inclusion proof
+//! against the tree head at export time. The proof is a `DataIntegrityProof`
+//! using the `eddsa-jcs-2022` cryptosuite, which signs JCS bytes – the same
+//! canonical form the ledger already hashes with.
+//!
+//! The deed terms are defined in an inline context rather than a context
+//! URL, so processors do not need to fetch anything to expand them.
+
+use crate::checkpoint::NodeKey;
+use crate::deed::DeedEvent;
+use crate::keys::{KeyError, KeyRegistry};
+use crate::ledger::MoralLedger;
+use crate::store::{LedgerStore, StoreError};
+use deed_schema::canonical::to_canonical_string;
+use deed_schema::{key_controller, verify_inclusion, InclusionProof, TreeHead};
+use ed25519_dalek::{Signature, Signer, Verifier};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+pub const VC_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+/// Namespace of the deed terms in `deed_context`.
+pub const DEED_VOCAB: &str = "urn:church-of-fear:deed:v1#";
+const CRYPTOSUITE: &str = "eddsa-jcs-2022";
+
+/// Inline JSON-LD context for the terms VC 2.0 does not define. Ledger
+/// rows, tree heads and proofs are opaque `@json` literals.
+pub fn deed_context() -> Value {
+    let term = |name: &str| format!("{}{}", DEED_VOCAB, name);
+    serde_json::json!({
+        "@protected": true,
+        "DeedCredential": term("DeedCredential"),
+        "DeedLedgerEvidence": term("DeedLedgerEvidence"),
+        "deed": { "@id": term("deed"), "@type": "@json" },
+        "position": { "@id": term("position"), "@type": "http://www.w3.org/2001/XMLSchema#nonNegativeInteger" },
+        "treeHead": { "@id": term("treeHead"), "@type": "@json" },
+        "inclusionProof": { "@id": term("inclusionProof"), "@type": "@json" },
+    })
+}
+
+#[derive(Error, Debug)]
+pub enum CredentialError {
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("key registry error: {0}")]
+    Keys(#[from] KeyError),
+    #[error("no deed at position {0}")]
+    NoSuchDeed(u64),
+    #[error("issuer key {0} is unknown or revoked")]
+    UnknownIssuerKey(String),
+    #[error("invalid proof: {0}")]
+    BadProof(String),
+    #[error("credential does not match its deed: {0}")]
+    Mismatch(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeedCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<Value>,
+    pub id: String,                         // urn:uuid:<event_id>
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub issuer: String,                     // node DID
+    #[serde(rename = "validFrom")]
+    pub valid_from: String,                 // RFC 3339
+    #[serde(rename = "credentialSubject")]
+    pub credential_subject: DeedSubject,
+    pub evidence: Vec<DeedEvidence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<DataIntegrityProof>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeedSubject {
+    pub id: String,                         // actor DID
+    pub deed: DeedEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeedEvidence {
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub position: u64,
+    pub tree_head: TreeHead,
+    pub inclusion_proof: InclusionProof,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataIntegrityProof {
+    #[serde(rename = "type")]
+    pub proof_type: String,
+    pub cryptosuite: String,
+    pub created: String,
+    pub verification_method: String,        // DID URL of the node key
+    pub proof_purpose: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_value: Option<String>,        // multibase base58btc
+}
+
+impl<S: LedgerStore> MoralLedger<S> {
+    /// Issue the deed at `seq` as a credential signed by `node`.
+    pub fn export_credential(&self, seq: u64, node: &NodeKey) -> Result<DeedCredential, CredentialError> {
+        let deed = self.store().get(seq)?.ok_or(CredentialError::NoSuchDeed(seq))?;
+        let issuer = key_controller(&node.key_id)
+            .ok_or_else(|| CredentialError::BadProof(format!("{} is not a DID URL", node.key_id)))?
+            .to_string();
+        let tree_head = self.tree_head();
+        let inclusion_proof = self.inclusion_proof(seq, tree_head.tree_size)?;
+        let now = self.clock().now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+        let mut credential = DeedCredential {
+            context: vec![Value::from(VC_CONTEXT), deed_context()],
+            id: format!("urn:uuid:{}", deed.event_id),
+            types: vec!["VerifiableCredential".to_string(), "DeedCredential".to_string()],
+            issuer,
+            valid_from: now.clone(),
+            credential_subject: DeedSubject { id: deed.actor_id.clone(), deed },
+            evidence: vec![DeedEvidence {
+                types: vec!["DeedLedgerEvidence".to_string()],
+                position: seq,
+                tree_head,
+                inclusion_proof,
+            }],
+            proof: None,
+        };
+        let mut proof = DataIntegrityProof {
+            proof_type: "DataIntegrityProof".to_string(),
+            cryptosuite: CRYPTOSUITE.to_string(),
+            created: now,
+            verification_method: node.key_id.clone(),
+            proof_purpose: "assertionMethod".to_string(),
+            proof_value: None,
+        };
+        let signature = node.signing_key.sign(&hash_data(&credential, &proof)?);
+        proof.proof_value = Some(format!("z{}", bs58::encode(signature.to_bytes()).into_string()));
+        credential.proof = Some(proof);
+        Ok(credential)
+    }
+}
+
+/// eddsa-jcs-2022: SHA-256(JCS(proof options)) || SHA-256(JCS(document)).
+fn hash_data(unsecured: &DeedCredential, proof: &DataIntegrityProof) -> Result<Vec<u8>, CredentialError> {
+    let mut options = serde_json::to_value(DataIntegrityProof { proof_value: None, ..proof.clone() })?;
+    if let Value::Object(map) = &mut options {
+        map.insert("@context".to_string(), serde_json::to_value(&unsecured.context)?);
+    }
+    let document = serde_json::to_value(DeedCredential { proof: None, ..unsecured.clone() })?;
+    let mut out = Sha256::digest(to_canonical_string(&options).as_bytes()).to_vec();
+    out.extend_from_slice(&Sha256::digest(to_canonical_string(&document).as_bytes()));
+    Ok(out)
+}
+
+/// Parse and fully verify a credential: issuer proof under a key the
+/// registry holds for the issuer DID, subject = actor, the deed's own hash,
+/// exactly one `DeedLedgerEvidence` whose inclusion proof holds, and the
+/// actor signature if the deed carries one.
+pub fn import_credential(json: &str, keys: &KeyRegistry) -> Result<DeedCredential, CredentialError> {
+    let credential: DeedCredential = serde_json::from_str(json)?;
+    let proof = credential.proof.as_ref().ok_or_else(|| CredentialError::BadProof("missing proof".to_string()))?;
+    if proof.cryptosuite != CRYPTOSUITE || proof.proof_type != "DataIntegrityProof" {
+        return Err(CredentialError::BadProof(format!("unsupported cryptosuite {}", proof.cryptosuite)));
+    }
+    if key_controller(&proof.verification_method) != Some(credential.issuer.as_str()) {
+        return Err(CredentialError::BadProof("verification method is not controlled by the issuer".to_string()));
+    }
+    let key = keys
+        .resolve(&proof.verification_method)?
+        .ok_or_else(|| CredentialError::UnknownIssuerKey(proof.verification_method.clone()))?;
+    let signature: [u8; 64] = proof
+        .proof_value
+        .as_deref()
+        .and_then(|v| v.strip_prefix('z'))
+        .and_then(|v| bs58::decode(v).into_vec().ok())
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| CredentialError::BadProof("proofValue is not a base58btc ed25519 signature".to_string()))?;
+    key.verify(&hash_data(&credential, proof)?, &Signature::from_bytes(&signature))
+        .map_err(|e| CredentialError::BadProof(e.to_string()))?;
+
+    let deed = &credential.credential_subject.deed;
+    if credential.credential_subject.id != deed.actor_id {
+        return Err(CredentialError::Mismatch("subject is not the deed's actor".to_string()));
+    }
+    if !deed.verify_self_hash() {
+        return Err(CredentialError::Mismatch("deed content does not match its self_hash".to_string()));
+    }
+    let [evidence] = credential.evidence.as_slice() else {
+        return Err(CredentialError::Mismatch(format!(
+            "expected one DeedLedgerEvidence entry, found {}",
+            credential.evidence.len()
+        )));
+    };
+    if !evidence.types.iter().any(|t| t == "DeedLedgerEvidence") {
+        return Err(CredentialError::Mismatch("evidence is not DeedLedgerEvidence".to_string()));
+    }
+    if !verify_inclusion(&deed.self_hash, &evidence.inclusion_proof, &evidence.tree_head)
+        || evidence.inclusion_proof.leaf_index != evidence.position
+    {
+        return Err(CredentialError::Mismatch(format!("inclusion proof for position {} fails", evidence.position)));
+    }
+    if deed.signature.is_some() {
+        crate::validator::LedgerValidator::validate_signature(deed, keys)
+            .map_err(|e| CredentialError::Mismatch(e.to_string()))?;
+    }
+    Ok(credential)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+    use crate::store::MemoryStore;
+    use ed25519_dalek::SigningKey;
+
+    fn node() -> NodeKey {
+        NodeKey { key_id: "did:bostrom:node-1#vc".into(), signing_key: SigningKey::from_bytes(&[5; 32]) }
+    }
+
+    #[test]
+    fn exported_credential_round_trips_and_detects_tampering() {
+        let mut ledger = MoralLedger::with_store(MemoryStore::new()).unwrap();
+        for i in 0..3 {
+            let deed = DeedEvent::new_math_science_education("did:bostrom:alice".into(), format!("crate-{}", i), &SystemClock, &RandomIds);
+            ledger.append(deed).unwrap();
+        }
+        let mut keys = KeyRegistry::new();
+        keys.register("did:bostrom:node-1#vc", &node().signing_key.verifying_key()).unwrap();
+
+        let credential = ledger.export_credential(1, &node()).unwrap();
+        let json = serde_json::to_string(&credential).unwrap();
+        let imported = import_credential(&json, &keys).unwrap();
+        assert_eq!(imported.credential_subject.deed.event_id, credential.credential_subject.deed.event_id);
+        assert_eq!(imported.issuer, "did:bostrom:node-1");
+
+        let tampered = json.replace("crate-1", "crate-9");
+        assert!(matches!(import_credential(&tampered, &keys), Err(CredentialError::BadProof(_))));
+        assert!(matches!(import_credential(&json, &KeyRegistry::new()), Err(CredentialError::UnknownIssuerKey(_))));
+    }
+
+    #[test]
+    fn credential_without_chain_evidence_is_rejected() {
+        let mut ledger = MoralLedger::with_store(MemoryStore::new()).unwrap();
+        ledger.append(DeedEvent::new_math_science_education("did:bostrom:alice".into(), "crate".into(), &SystemClock, &RandomIds)).unwrap();
+        let mut keys = KeyRegistry::new();
+        keys.register("did:bostrom:node-1#vc", &node().signing_key.verifying_key()).unwrap();
+
+        let mut credential = ledger.export_credential(0, &node()).unwrap();
+        let evidence = credential.evidence.clone();
+        for bad in [vec![], vec![evidence[0].clone(), evidence[0].clone()]] {
+            credential.evidence = bad;
+            let proof = credential.proof.take().unwrap();
+            let signature = node().signing_key.sign(&hash_data(&credential, &proof).unwrap());
+            let proof_value = Some(format!("z{}", bs58::encode(signature.to_bytes()).into_string()));
+            credential.proof = Some(DataIntegrityProof { proof_value, ..proof });
+            let json = serde_json::to_string(&credential).unwrap();
+            assert!(matches!(import_credential(&json, &keys), Err(CredentialError::Mismatch(_))));
+        }
+    }
+}
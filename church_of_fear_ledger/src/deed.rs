@@ -1,13 +1,26 @@
-use serde::{Deserialize, Serialize};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
 use zeroize::Zeroize;
 
+use token_journal::schedule::rules::GOOD_DEED_RECOMMENDATION;
+use token_journal::ceiling::GOOD_DEED_TAGS;
+use token_journal::{CeilingDeed, RewardInput, RewardSchedule};
+use deed_schema::dispute::{Appeal, DisputeFiling, DisputeVote};
+use deed_schema::{
+    Anchor, Clock, CorrectableDeed, CorrectedFields, Correction, DeedFieldsMut, DeedSignature, Disclosures, IdGenerator, SchemaError,
+    Verdict, ANCHOR_DEED_TYPE, CORRECTION_DEED_TYPE, DISPUTE_APPEAL_DEED_TYPE, DISPUTE_DEED_TYPE, DISPUTE_VOTE_DEED_TYPE, SCHEMA_VERSION,
+};
+use ed25519_dalek::SigningKey;
+
 /// Exact DeedEvent schema from the Church-of-FEAR moral ledger specification
 #[derive(Debug, Clone, Serialize, Deserialize, Zeroize)]
 #[zeroize(drop)]
 pub struct DeedEvent {
+    #[serde(default, deserialize_with = "supported_version", skip_serializing_if = "is_unversioned")]
+    pub schema_version: u32,                // 0 on rows written before versioning; never serialized then
+    #[zeroize(skip)]
     pub event_id: Uuid,
     pub timestamp: i64,                     // Unix epoch seconds
     pub prev_hash: String,                  // hex-encoded SHA-256
@@ -16,22 +29,34 @@ pub struct DeedEvent {
     pub target_ids: Vec<String>,
     pub deed_type: String,                  // e.g. "ecological_sustainability"
     pub tags: Vec<String>,
+    #[zeroize(skip)]
     pub context_json: serde_json::Value,    // evidence, URLs, grant proposals
     pub ethics_flags: Vec<String>,          // RoH breaches, ALN violations
     pub life_harm_flag: bool,
+    #[zeroize(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<DeedSignature>,   // actor's ed25519 signature (absent on legacy rows)
+    #[zeroize(skip)]
+    #[serde(default, skip_serializing_if = "Disclosures::is_empty")]
+    pub disclosures: Disclosures,           // concealed context fields – must stay last, never hashed
 }
 
 impl DeedEvent {
+    /// Fresh, unchained deed; `event_id` comes from `ids` and `timestamp`
+    /// from `clock`, so a fixed clock and seeded ids give a reproducible chain.
     pub fn new(
         actor_id: String,
         target_ids: Vec<String>,
         deed_type: String,
         tags: Vec<String>,
         context_json: serde_json::Value,
+        clock: &dyn Clock,
+        ids: &dyn IdGenerator,
     ) -> Self {
-        let event_id = Uuid::new_v4();
-        let timestamp = Utc::now().timestamp();
+        let event_id = ids.next_id();
+        let timestamp = clock.unix_seconds();
         Self {
+            schema_version: SCHEMA_VERSION,
             event_id,
             timestamp,
             prev_hash: "".to_string(),
@@ -43,22 +68,36 @@ impl DeedEvent {
             context_json,
             ethics_flags: vec![],
             life_harm_flag: false,
+            signature: None,
+            disclosures: Disclosures::new(),
         }
     }
 
     /// Convenience constructors – these are the deeds that earn CHURCH recommendations
-    pub fn new_ecological_sustainability(actor_id: String, evidence_url: String) -> Self {
-        let mut ctx = serde_json::json!({ "evidence_url": evidence_url });
+    pub fn new_ecological_sustainability(
+        actor_id: String,
+        evidence_url: String,
+        clock: &dyn Clock,
+        ids: &dyn IdGenerator,
+    ) -> Self {
+        let ctx = serde_json::json!({ "evidence_url": evidence_url });
         Self::new(
             actor_id,
             vec![],
             "ecological_sustainability".to_string(),
             vec!["reforestation".to_string(), "carbon_negative".to_string()],
             ctx,
+            clock,
+            ids,
         )
     }
 
-    pub fn new_math_science_education(actor_id: String, crate_name: String) -> Self {
+    pub fn new_math_science_education(
+        actor_id: String,
+        crate_name: String,
+        clock: &dyn Clock,
+        ids: &dyn IdGenerator,
+    ) -> Self {
         let ctx = serde_json::json!({ "crate": crate_name, "license": "MIT/Apache-2.0" });
         Self::new(
             actor_id,
@@ -66,9 +105,71 @@ impl DeedEvent {
             "math_science_education".to_string(),
             vec!["open_source".to_string(), "rust".to_string()],
             ctx,
+            clock,
+            ids,
         )
     }
 
+    /// Correction of the earlier deed `corrects`; must be appended by the
+    /// same actor. The original stays in the chain untouched.
+    pub fn new_correction(
+        actor_id: String,
+        corrects: Uuid,
+        set: CorrectedFields,
+        reason: String,
+        clock: &dyn Clock,
+        ids: &dyn IdGenerator,
+    ) -> Self {
+        let correction = Correction { corrects: corrects.to_string(), set, reason };
+        Self::new(actor_id, vec![], CORRECTION_DEED_TYPE.to_string(), vec![], correction.to_context(), clock, ids)
+    }
+
+    /// Dispute of another actor's deed, e.g. on the grounds of "misleading
+    /// impact declarations"; `evidence` holds URLs or content hashes.
+    pub fn new_dispute(
+        challenger: String,
+        disputes: Uuid,
+        grounds: String,
+        evidence: Vec<String>,
+        clock: &dyn Clock,
+        ids: &dyn IdGenerator,
+    ) -> Self {
+        let filing = DisputeFiling { disputes: disputes.to_string(), grounds, evidence };
+        Self::new(challenger, vec![], DISPUTE_DEED_TYPE.to_string(), vec![], to_context(&filing), clock, ids)
+    }
+
+    /// A reviewer's vote in `round` (0, or 1 after an appeal) of `dispute`.
+    pub fn new_dispute_vote(
+        reviewer: String,
+        dispute: Uuid,
+        round: u32,
+        verdict: Verdict,
+        reason: String,
+        clock: &dyn Clock,
+        ids: &dyn IdGenerator,
+    ) -> Self {
+        let vote = DisputeVote { dispute: dispute.to_string(), round, verdict, reason };
+        Self::new(reviewer, vec![], DISPUTE_VOTE_DEED_TYPE.to_string(), vec![], to_context(&vote), clock, ids)
+    }
+
+    /// Appeal of a decided dispute by one of its parties.
+    pub fn new_dispute_appeal(
+        party: String,
+        dispute: Uuid,
+        grounds: String,
+        evidence: Vec<String>,
+        clock: &dyn Clock,
+        ids: &dyn IdGenerator,
+    ) -> Self {
+        let appeal = Appeal { appeals: dispute.to_string(), grounds, evidence };
+        Self::new(party, vec![], DISPUTE_APPEAL_DEED_TYPE.to_string(), vec![], to_context(&appeal), clock, ids)
+    }
+
+    /// Record of a peer ledger's head, as its `MoralLedger::anchor` gives it.
+    pub fn new_anchor(actor_id: String, anchor: Anchor, clock: &dyn Clock, ids: &dyn IdGenerator) -> Self {
+        Self::new(actor_id, vec![], ANCHOR_DEED_TYPE.to_string(), vec![], anchor.to_context(), clock, ids)
+    }
+
     /// Finalize hash chain – called by ledger after prev_hash is known
     pub fn finalize_hash_chain(mut self, prev_hash: String) -> Self {
         self.prev_hash = prev_hash;
@@ -76,24 +177,178 @@ impl DeedEvent {
         self
     }
 
+    /// CanonicalV1 hash of the row (see `deed_schema::hash`). Unversioned
+    /// rows keep the legacy struct-order hash so they still verify. Neither
+    /// covers `disclosures` – concealed fields are covered through their
+    /// `_sd` digests in `context_json`.
     pub fn compute_self_hash(&self) -> String {
+        if self.schema_version != deed_schema::UNVERSIONED {
+            return deed_schema::DeedEvent::from(self).compute_self_hash().expect("serialization infallible for owned data");
+        }
         let mut hasher = Sha256::new();
-        let serialized = serde_json::to_string(self).expect("serialization infallible for owned data");
+        let serialized = if self.disclosures.is_empty() {
+            serde_json::to_string(self)
+        } else {
+            let mut committed = self.clone();
+            committed.disclosures.clear();
+            serde_json::to_string(&committed)
+        }
+        .expect("serialization infallible for owned data");
         hasher.update(serialized.as_bytes());
         hex::encode(hasher.finalize())
     }
 
-    /// CHURCH recommendation – advisory only, never automatic mint
-    pub fn church_recommendation(&self) -> u64 {
-        if self.life_harm_flag {
-            return 0;
-        }
-        if !self.ethics_flags.is_empty() {
-            return 0;
+    /// Recompute the hash of a stored (already sealed) event – `self_hash` is
+    /// blanked first, exactly as it was when `finalize_hash_chain` ran – and
+    /// check every remaining disclosure against its digest.
+    pub fn verify_self_hash(&self) -> bool {
+        let mut unsealed = self.clone();
+        unsealed.self_hash = String::new();
+        unsealed.compute_self_hash() == self.self_hash
+            && deed_schema::disclosure::check_disclosures(&self.context_json, &self.disclosures).is_ok()
+    }
+
+    /// Replace `fields` of `context_json` with salted commitments so they can
+    /// be redacted later. Must happen before signing and chaining.
+    pub fn conceal(mut self, fields: &[&str]) -> Result<Self, deed_schema::SchemaError> {
+        deed_schema::conceal_fields(&mut self.context_json, fields, &mut self.disclosures)?;
+        Ok(self)
+    }
+
+    /// `context_json` with concealed values put back; redacted fields are absent.
+    pub fn disclosed_context(&self) -> Result<serde_json::Value, deed_schema::SchemaError> {
+        deed_schema::reveal(&self.context_json, &self.disclosures)
+    }
+
+    /// Drop the value of a concealed field for good. `self_hash` and the
+    /// signature are unaffected; returns `false` if there was nothing to drop.
+    pub fn redact(&mut self, field: &str) -> bool {
+        self.disclosures.remove(field).is_some()
+    }
+
+    /// Concealed fields whose value has been redacted.
+    pub fn redacted_fields(&self) -> Vec<String> {
+        deed_schema::redacted_fields(&self.context_json, &self.disclosures)
+    }
+
+    /// Sign the deed as `key_id` (a DID URL under `actor_id`) over its
+    /// canonical bytes. Must happen before the ledger chains it.
+    pub fn sign(mut self, key_id: &str, key: &SigningKey) -> Result<Self, deed_schema::SchemaError> {
+        let mut canonical = deed_schema::DeedEvent::from(&self);
+        canonical.sign(key_id, key)?;
+        self.signature = canonical.signature.clone();
+        Ok(self)
+    }
+
+    /// CHURCH recommendation – advisory only, never automatic mint. The
+    /// amount is `schedule`'s `good_deed_recommendation`.
+    pub fn church_recommendation(&self, schedule: &RewardSchedule) -> u64 {
+        let clean = !self.life_harm_flag && self.ethics_flags.is_empty();
+        let input = RewardInput::new(&self.deed_type, &self.tags, clean);
+        schedule.evaluate(&input).amount_of(GOOD_DEED_RECOMMENDATION)
+    }
+}
+
+fn is_unversioned(version: &u32) -> bool {
+    *version == 0
+}
+
+/// Rows from a newer schema fail to parse instead of losing fields.
+fn supported_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+    if version > SCHEMA_VERSION {
+        return Err(D::Error::custom(SchemaError::UnsupportedVersion(version)));
+    }
+    Ok(version)
+}
+
+fn to_context<T: Serialize>(step: &T) -> serde_json::Value {
+    serde_json::to_value(step).expect("dispute steps serialize")
+}
+
+/// Lossless conversion into the canonical schema, keeping the row's own
+/// `schema_version`. Takes a reference because `#[zeroize(drop)]` forbids
+/// moving fields out of the legacy struct.
+impl From<&DeedEvent> for deed_schema::DeedEvent {
+    fn from(e: &DeedEvent) -> Self {
+        deed_schema::DeedEvent {
+            schema_version: e.schema_version,
+            event_id: e.event_id,
+            timestamp: e.timestamp,
+            timestamp_nanos: 0,
+            prev_hash: e.prev_hash.clone(),
+            self_hash: e.self_hash.clone(),
+            actor_id: e.actor_id.clone(),
+            target_ids: e.target_ids.clone(),
+            deed_type: e.deed_type.clone(),
+            tags: e.tags.clone(),
+            context_json: e.context_json.clone(),
+            ethics_flags: e.ethics_flags.clone(),
+            life_harm_flag: e.life_harm_flag,
+            extensions: serde_json::Map::new(),
+            signature: e.signature.clone(),
+            disclosures: e.disclosures.clone(),
         }
-        match self.deed_type.as_str() {
-            "ecological_sustainability" | "homelessness_relief" | "math_science_education" => CHURCH_RECOMMEND_PER_GOOD_DEED,
-            _ => 0,
+    }
+}
+
+impl CorrectableDeed for DeedEvent {
+    fn id(&self) -> String {
+        self.event_id.to_string()
+    }
+
+    fn actor(&self) -> &str {
+        &self.actor_id
+    }
+
+    fn kind(&self) -> &str {
+        &self.deed_type
+    }
+
+    fn context(&self) -> &serde_json::Value {
+        &self.context_json
+    }
+
+    fn ethics_flags(&self) -> &[String] {
+        &self.ethics_flags
+    }
+
+    fn fields_mut(&mut self) -> DeedFieldsMut<'_> {
+        DeedFieldsMut {
+            deed_type: &mut self.deed_type,
+            target_ids: Some(&mut self.target_ids),
+            tags: Some(&mut self.tags),
+            context_json: &mut self.context_json,
+            ethics_flags: &mut self.ethics_flags,
+            life_harm_flag: &mut self.life_harm_flag,
         }
     }
 }
+
+impl CeilingDeed for DeedEvent {
+    fn event_id(&self) -> String {
+        self.event_id.to_string()
+    }
+
+    fn deed_type(&self) -> &str {
+        &self.deed_type
+    }
+
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    fn ethics_flags(&self) -> &[String] {
+        &self.ethics_flags
+    }
+
+    fn life_harm_flag(&self) -> bool {
+        self.life_harm_flag
+    }
+
+    /// Clean and typed or tagged with one of `GOOD_DEED_TAGS`; this ledger's
+    /// constructors name the good deed in `deed_type`.
+    fn is_good_deed(&self) -> bool {
+        self.is_clean() && GOOD_DEED_TAGS.iter().any(|mark| self.is_marked(mark))
+    }
+}
@@ -0,0 +1,283 @@
+//! Content-addressed evidence store.
+//!
+//! Blobs are stored under their CID, one file per blob, and are checked
+//! against it on every read. Accepted references:
+//!
+//! - CIDv1 in base32 (`bafk…`) with the `raw` codec and a sha2-256 or
+//!   blake3 multihash, bare or as `ipfs://<cid>`;
+//! - a bare 64-hex SHA-256 digest, taken as the raw sha2-256 CIDv1.
+//!
+//! CIDv0 (`Qm…`) and `dag-pb` CIDs hash the IPFS block, not the file bytes,
+//! so they cannot be checked here and are rejected as unsupported.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const CODEC_RAW: u64 = 0x55;
+const MH_SHA2_256: u64 = 0x12;
+const MH_BLAKE3: u64 = 0x1e;
+const BASE32: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// `context_json` fields that name evidence.
+pub const EVIDENCE_FIELDS: [&str; 3] = ["evidence_url", "proof_hash", "evidence"];
+
+#[derive(Error, Debug)]
+pub enum EvidenceError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("unsupported content id {0}")]
+    UnsupportedCid(String),
+    #[error("evidence {0} is missing")]
+    Missing(String),
+    #[error("evidence {0} does not match its hash")]
+    Mismatch(String),
+}
+
+/// A CIDv1 with the raw codec.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cid {
+    pub hash_code: u64,                     // multihash function code
+    pub digest: Vec<u8>,
+}
+
+impl Cid {
+    /// CID of `bytes` under sha2-256.
+    pub fn for_bytes(bytes: &[u8]) -> Self {
+        Self { hash_code: MH_SHA2_256, digest: Sha256::digest(bytes).to_vec() }
+    }
+
+    pub fn parse(reference: &str) -> Result<Self, EvidenceError> {
+        let unsupported = || EvidenceError::UnsupportedCid(reference.to_string());
+        let id = reference.strip_prefix("ipfs://").unwrap_or(reference);
+        if id.len() == 64 {
+            if let Ok(digest) = hex::decode(id) {
+                return Ok(Self { hash_code: MH_SHA2_256, digest });
+            }
+        }
+        let bytes = id.strip_prefix('b').and_then(base32_decode).ok_or_else(unsupported)?;
+        let mut rest = bytes.as_slice();
+        let mut next = || read_varint(&mut rest).ok_or_else(unsupported);
+        let (version, codec, hash_code, len) = (next()?, next()?, next()?, next()?);
+        if version != 1 || codec != CODEC_RAW || !matches!(hash_code, MH_SHA2_256 | MH_BLAKE3) {
+            return Err(unsupported());
+        }
+        if len != 32 || rest.len() != 32 {
+            return Err(unsupported());
+        }
+        Ok(Self { hash_code, digest: rest.to_vec() })
+    }
+
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        let digest = match self.hash_code {
+            MH_SHA2_256 => Sha256::digest(bytes).to_vec(),
+            MH_BLAKE3 => blake3::hash(bytes).as_bytes().to_vec(),
+            _ => return false,
+        };
+        digest == self.digest
+    }
+}
+
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = Vec::with_capacity(4 + self.digest.len());
+        for n in [1, CODEC_RAW, self.hash_code, self.digest.len() as u64] {
+            write_varint(&mut bytes, n);
+        }
+        bytes.extend_from_slice(&self.digest);
+        write!(f, "b{}", base32_encode(&bytes))
+    }
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..63).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+/// RFC 4648 base32, lowercase, unpadded (multibase `b`).
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 8 / 5 + 1);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.bytes() {
+        let value = BASE32.iter().position(|&b| b == c)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// Content-addressed references in `context`: every string under an
+/// `EVIDENCE_FIELDS` key that parses as a `Cid`, and every `ipfs://` string
+/// anywhere. Plain URLs are not content-addressed and are skipped.
+pub fn evidence_refs(context: &Value) -> Vec<String> {
+    fn walk(value: &Value, named: bool, out: &mut Vec<String>) {
+        match value {
+            Value::String(s) if s.starts_with("ipfs://") || (named && Cid::parse(s).is_ok()) => out.push(s.clone()),
+            Value::Array(items) => items.iter().for_each(|v| walk(v, named, out)),
+            Value::Object(map) => map.iter().for_each(|(k, v)| walk(v, EVIDENCE_FIELDS.contains(&k.as_str()), out)),
+            _ => {}
+        }
+    }
+    let mut refs = Vec::new();
+    walk(context, false, &mut refs);
+    refs
+}
+
+/// A directory of blobs named by CID.
+#[derive(Debug, Clone)]
+pub struct EvidenceStore {
+    dir: PathBuf,
+}
+
+impl EvidenceStore {
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, EvidenceError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, cid: &Cid) -> PathBuf {
+        self.dir.join(cid.to_string())
+    }
+
+    /// Store `bytes` under their sha2-256 CID.
+    pub fn put(&self, bytes: &[u8]) -> Result<Cid, EvidenceError> {
+        let cid = Cid::for_bytes(bytes);
+        self.write(&cid, bytes)?;
+        Ok(cid)
+    }
+
+    /// Store `bytes` fetched for `reference`, which they must match.
+    pub fn insert(&self, reference: &str, bytes: &[u8]) -> Result<Cid, EvidenceError> {
+        let cid = Cid::parse(reference)?;
+        if !cid.matches(bytes) {
+            return Err(EvidenceError::Mismatch(reference.to_string()));
+        }
+        self.write(&cid, bytes)?;
+        Ok(cid)
+    }
+
+    fn write(&self, cid: &Cid, bytes: &[u8]) -> Result<(), EvidenceError> {
+        let path = self.path(cid);
+        if path.exists() {
+            return Ok(());
+        }
+        let tmp = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// The blob for `reference`, checked against its hash.
+    pub fn get(&self, reference: &str) -> Result<Vec<u8>, EvidenceError> {
+        let cid = Cid::parse(reference)?;
+        let bytes = match fs::read(self.path(&cid)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(EvidenceError::Missing(reference.to_string())),
+            Err(e) => return Err(e.into()),
+        };
+        if !cid.matches(&bytes) {
+            return Err(EvidenceError::Mismatch(reference.to_string()));
+        }
+        Ok(bytes)
+    }
+
+    /// Whether the blob for `reference` is present and intact.
+    pub fn verify(&self, reference: &str) -> Result<bool, EvidenceError> {
+        match self.get(reference) {
+            Ok(_) => Ok(true),
+            Err(EvidenceError::Missing(_) | EvidenceError::Mismatch(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Every content-addressed reference in `context` must be present and
+    /// intact.
+    pub fn check_context(&self, context: &Value) -> Result<(), EvidenceError> {
+        for reference in evidence_refs(context) {
+            self.get(&reference)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blobs_round_trip_under_their_cid_and_tampering_is_caught() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EvidenceStore::open(dir.path()).unwrap();
+        let cid = store.put(b"hello world").unwrap();
+        assert_eq!(cid.to_string(), "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e");
+        assert_eq!(Cid::parse(&cid.to_string()).unwrap(), cid);
+        let hex_ref = hex::encode(&cid.digest);
+        assert_eq!(store.get(&format!("ipfs://{}", cid)).unwrap(), b"hello world");
+        assert_eq!(store.get(&hex_ref).unwrap(), b"hello world");
+
+        let blake = Cid { hash_code: MH_BLAKE3, digest: blake3::hash(b"receipt").as_bytes().to_vec() }.to_string();
+        assert!(store.insert(&blake, b"forged").is_err());
+        store.insert(&blake, b"receipt").unwrap();
+        assert!(store.verify(&blake).unwrap());
+
+        fs::write(dir.path().join(cid.to_string()), b"hello w0rld").unwrap();
+        assert!(matches!(store.get(&cid.to_string()), Err(EvidenceError::Mismatch(_))));
+        assert!(!store.verify(&"00".repeat(32)).unwrap());
+        assert!(matches!(Cid::parse("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"), Err(EvidenceError::UnsupportedCid(_))));
+
+        let context = serde_json::json!({
+            "evidence_url": "https://example.org/photo.jpg",
+            "proof_hash": hex_ref,
+            "bundle": { "uri": format!("ipfs://{}", blake) },
+        });
+        assert_eq!(evidence_refs(&context).len(), 2);
+    }
+}
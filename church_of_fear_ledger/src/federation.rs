@@ -0,0 +1,128 @@
+//! Cross-ledger references between federated congregations.
+//!
+//! A deed on one ledger names a deed on another through a `LedgerRef` in
+//! its `target_ids`. Each ledger records what it has seen of its peers as
+//! `ledger_anchor` deeds (`DeedEvent::new_anchor` with a peer's
+//! `MoralLedger::anchor`). `verify_reference` checks a reference with a
+//! local copy of the peer ledger: the deed must be in the peer's chain up
+//! to the referenced head, and that chain must be a prefix of one of the
+//! peer heads anchored here.
+
+use crate::ledger::MoralLedger;
+use crate::store::{LedgerStore, StoreError};
+use deed_schema::{Anchor, LedgerRef};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum ReferenceError {
+    #[error("store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("peer ledger is {found:?}, reference names {expected}")]
+    WrongLedger { expected: String, found: Option<String> },
+    #[error("deed {0} is not on the referenced ledger")]
+    UnknownDeed(Uuid),
+    #[error("head {0} does not follow the referenced deed")]
+    UnknownHead(String),
+    #[error("no anchor of {ledger_id} covers position {seq}")]
+    NotAnchored { ledger_id: String, seq: u64 },
+    #[error("anchor at {anchor_seq} does not match the referenced ledger")]
+    AnchorMismatch { anchor_seq: u64 },
+}
+
+/// Check `reference`, made on `local`, against `peer`, the ledger it names.
+/// Returns the local position of the anchor that vouches for it.
+pub fn verify_reference<S: LedgerStore, P: LedgerStore>(
+    local: &MoralLedger<S>,
+    reference: &LedgerRef,
+    peer: &MoralLedger<P>,
+) -> Result<u64, ReferenceError> {
+    if peer.ledger_id() != Some(reference.ledger_id.as_str()) {
+        return Err(ReferenceError::WrongLedger {
+            expected: reference.ledger_id.clone(),
+            found: peer.ledger_id().map(str::to_string),
+        });
+    }
+    let deed_seq =
+        peer.position_of(&reference.event_id).ok_or(ReferenceError::UnknownDeed(reference.event_id))?;
+    let head_seq = (deed_seq..)
+        .zip(peer.store().read_from(deed_seq)?)
+        .find(|(_, deed)| deed.self_hash == reference.head)
+        .map(|(seq, _)| seq)
+        .ok_or_else(|| ReferenceError::UnknownHead(reference.head.clone()))?;
+
+    let mut mismatch = None;
+    for (anchor_seq, anchor) in local.anchors(&reference.ledger_id)?.into_iter().rev() {
+        let size = anchor.tree.tree_size;
+        if size <= head_seq || size > peer.len() {
+            continue;
+        }
+        if matches_peer(&anchor, peer)? {
+            return Ok(anchor_seq);
+        }
+        mismatch.get_or_insert(anchor_seq);
+    }
+    match mismatch {
+        Some(anchor_seq) => Err(ReferenceError::AnchorMismatch { anchor_seq }),
+        None => Err(ReferenceError::NotAnchored { ledger_id: reference.ledger_id.clone(), seq: head_seq }),
+    }
+}
+
+/// Whether `peer`'s first `anchor.tree.tree_size` deeds are the ones anchored.
+fn matches_peer<P: LedgerStore>(anchor: &Anchor, peer: &MoralLedger<P>) -> Result<bool, StoreError> {
+    let size = anchor.tree.tree_size;
+    let head = peer.store().get(size - 1)?.map(|deed| deed.self_hash.clone());
+    Ok(head.as_deref() == Some(anchor.head.as_str()) && peer.tree_head_at(size)? == anchor.tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+    use crate::deed::DeedEvent;
+    use crate::store::MemoryStore;
+    use crate::validator::ValidationError;
+
+    fn ledger(id: &str) -> MoralLedger<MemoryStore> {
+        MoralLedger::with_store(MemoryStore::default()).unwrap().with_ledger_id(id.to_string())
+    }
+
+    fn deed(actor: &str) -> DeedEvent {
+        DeedEvent::new_ecological_sustainability(actor.to_string(), "https://example.org".to_string(), &SystemClock, &RandomIds)
+    }
+
+    #[test]
+    fn references_check_out_against_anchored_peer_heads() {
+        let mut east = ledger("phoenix-east");
+        let mut west = ledger("phoenix-west");
+        let planted = east.append(deed("alice")).unwrap();
+        let reference = east.reference(planted).unwrap();
+
+        let mut thanks = deed("bob");
+        thanks.target_ids.push(reference.to_string());
+        west.append(thanks).unwrap();
+        assert!(matches!(
+            verify_reference(&west, &reference, &east),
+            Err(ReferenceError::NotAnchored { seq: 0, .. })
+        ));
+
+        east.append(deed("carol")).unwrap();
+        west.append(DeedEvent::new_anchor("bob".into(), east.anchor().unwrap(), &SystemClock, &RandomIds)).unwrap();
+        assert_eq!(verify_reference(&west, &reference, &east).unwrap(), 1);
+        assert!(west.account("bob").is_some_and(|a| a.deeds == 1));
+        assert!(matches!(verify_reference(&west, &reference, &west), Err(ReferenceError::WrongLedger { .. })));
+
+        // A copy of east whose history differs from the anchored one.
+        let mut forged = ledger("phoenix-east");
+        forged.append_sealed(&east.store().get(0).unwrap().unwrap()).unwrap();
+        forged.append(deed("mallory")).unwrap();
+        assert!(matches!(
+            verify_reference(&west, &reference, &forged),
+            Err(ReferenceError::AnchorMismatch { anchor_seq: 1 })
+        ));
+
+        let mut broken = deed("bob");
+        broken.target_ids.push("ledger://phoenix-east/nope@00".into());
+        assert!(matches!(west.append(broken), Err(ValidationError::InvalidReference(_))));
+    }
+}
@@ -0,0 +1,289 @@
+//! Live deed feed for in-process consumers.
+//!
+//! `MoralLedger::subscribe` hands out a channel that receives every deed
+//! committed after the call, plus a `Rewound` notice when fork resolution
+//! drops deeds. Channels are bounded: a subscriber that falls
+//! `FEED_BUFFER` events behind is cut off rather than stalling appends.
+//!
+//! `Follower` adds a durable `Cursor` on top, so a consumer can stop,
+//! restart and pick up after the last deed it acknowledged. Deeds are
+//! delivered in chain order without gaps; a deed counts as processed once
+//! it is `ack`ed. A consumer that must never see a deed twice keeps the
+//! cursor in the same transaction as its own state instead of in a file.
+
+use crate::deed::DeedEvent;
+use crate::ledger::MoralLedger;
+use crate::store::{LedgerStore, StoreError};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Events a subscriber may have queued before it is cut off.
+pub const FEED_BUFFER: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub enum FeedEvent {
+    /// `deed` was committed at chain position `seq`.
+    Appended { seq: u64, deed: Arc<DeedEvent> },
+    /// Every deed from position `len` on was dropped; the winning branch
+    /// follows as `Appended` events.
+    Rewound { len: u64 },
+}
+
+#[derive(Error, Debug)]
+pub enum FeedError {
+    #[error("store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("no deed with hash {0}")]
+    UnknownHash(String),
+    /// The chain no longer holds the deed at `seq - 1` the cursor points
+    /// past, e.g. after a fork was resolved against it.
+    #[error("chain diverged from the cursor at position {seq}")]
+    Diverged { seq: u64 },
+    #[error("follower is not attached to a ledger")]
+    Detached,
+    #[error("expected deed {expected}, got {got}; re-attach to catch up")]
+    Gap { expected: u64, got: u64 },
+}
+
+/// The ledger's side of the feed.
+#[derive(Debug, Default)]
+pub(crate) struct Broadcast {
+    subscribers: Vec<SyncSender<FeedEvent>>,
+}
+
+impl Broadcast {
+    pub(crate) fn subscribe(&mut self) -> Receiver<FeedEvent> {
+        let (tx, rx) = mpsc::sync_channel(FEED_BUFFER);
+        self.subscribers.push(tx);
+        rx
+    }
+
+    /// Deliver `event` to every live subscriber, dropping the ones that
+    /// hung up or fell too far behind.
+    pub(crate) fn send(&mut self, event: impl FnOnce() -> FeedEvent) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        let event = event();
+        self.subscribers.retain(|tx| match tx.try_send(event.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                log::warn!("deed feed subscriber fell {} events behind; disconnecting it", FEED_BUFFER);
+                false
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+}
+
+/// Where a consumer stands: the next position to process and the
+/// `self_hash` of the deed before it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub next: u64,
+    pub prev_hash: String,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self { next: 0, prev_hash: "0".repeat(64) } // genesis
+    }
+}
+
+impl Cursor {
+    /// The cursor saved at `path`, or genesis if there is none yet.
+    pub fn load(path: &Path) -> Result<Self, FeedError> {
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Atomically replace the cursor at `path` (write temp, fsync, rename).
+    pub fn save(&self, path: &Path) -> Result<(), FeedError> {
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// The cursor just past the deed whose `self_hash` is `hash`.
+    pub fn after_hash<S: LedgerStore>(store: &S, hash: &str) -> Result<Self, FeedError> {
+        let genesis = Self::default();
+        if hash == genesis.prev_hash {
+            return Ok(genesis);
+        }
+        for seq in (0..store.len()).rev() {
+            if store.get(seq)?.is_some_and(|e| e.self_hash == hash) {
+                return Ok(Self { next: seq + 1, prev_hash: hash.to_string() });
+            }
+        }
+        Err(FeedError::UnknownHash(hash.to_string()))
+    }
+
+    /// Whether `store` still holds the chain this cursor points into.
+    fn check<S: LedgerStore>(&self, store: &S) -> Result<(), FeedError> {
+        let head = match self.next {
+            0 => Some(Self::default().prev_hash),
+            n => store.get(n - 1)?.map(|e| e.self_hash.clone()),
+        };
+        if head.as_deref() != Some(self.prev_hash.as_str()) {
+            return Err(FeedError::Diverged { seq: self.next });
+        }
+        Ok(())
+    }
+}
+
+/// Exactly-once reader of the ledger's deeds, resumable from a `Cursor`.
+#[derive(Debug)]
+pub struct Follower {
+    cursor: Cursor,
+    path: Option<PathBuf>,
+    delivered: u64,                         // next position to hand out
+    live: Option<Receiver<FeedEvent>>,
+}
+
+impl Follower {
+    pub fn new(cursor: Cursor) -> Self {
+        let delivered = cursor.next;
+        Self { cursor, path: None, delivered, live: None }
+    }
+
+    /// A follower whose cursor lives at `path` and is saved on every `ack`.
+    pub fn open(path: PathBuf) -> Result<Self, FeedError> {
+        let mut follower = Self::new(Cursor::load(&path)?);
+        follower.path = Some(path);
+        Ok(follower)
+    }
+
+    pub fn cursor(&self) -> &Cursor {
+        &self.cursor
+    }
+
+    /// Subscribe to `ledger` and return the deeds after the cursor that it
+    /// already holds. Taking the ledger mutably means nothing can be
+    /// appended between the backlog and the subscription.
+    pub fn attach<S: LedgerStore>(&mut self, ledger: &mut MoralLedger<S>) -> Result<Vec<(u64, DeedEvent)>, FeedError> {
+        self.cursor.check(ledger.store())?;
+        let backlog: Vec<(u64, DeedEvent)> =
+            (self.cursor.next..).zip(ledger.store().read_from(self.cursor.next)?).collect();
+        self.delivered = self.cursor.next + backlog.len() as u64;
+        self.live = Some(ledger.subscribe());
+        Ok(backlog)
+    }
+
+    /// The next live deed, waiting at most `timeout`; `None` on timeout.
+    /// A follower cut off by the ledger fails with `Detached` and should
+    /// `attach` again.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<(u64, DeedEvent)>, FeedError> {
+        loop {
+            let event = match self.live.as_ref().ok_or(FeedError::Detached)?.recv_timeout(timeout) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.live = None;
+                    return Err(FeedError::Detached);
+                }
+            };
+            match event {
+                FeedEvent::Appended { seq, .. } if seq < self.delivered => continue,
+                FeedEvent::Appended { seq, deed } if seq == self.delivered => {
+                    self.delivered += 1;
+                    return Ok(Some((seq, Arc::unwrap_or_clone(deed))));
+                }
+                FeedEvent::Appended { seq, .. } => {
+                    self.live = None;
+                    return Err(FeedError::Gap { expected: self.delivered, got: seq });
+                }
+                FeedEvent::Rewound { len } if len < self.cursor.next => {
+                    self.live = None;
+                    return Err(FeedError::Diverged { seq: len });
+                }
+                FeedEvent::Rewound { len } => self.delivered = self.delivered.min(len),
+            }
+        }
+    }
+
+    /// Mark the deed at `seq` processed. Deeds must be acknowledged in
+    /// order; the cursor file, if any, is saved before this returns.
+    pub fn ack(&mut self, seq: u64, deed: &DeedEvent) -> Result<(), FeedError> {
+        if seq != self.cursor.next {
+            return Err(FeedError::Gap { expected: self.cursor.next, got: seq });
+        }
+        self.cursor = Cursor { next: seq + 1, prev_hash: deed.self_hash.clone() };
+        if let Some(path) = &self.path {
+            self.cursor.save(path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+    use crate::store::MemoryStore;
+
+    fn deed(actor: &str) -> DeedEvent {
+        DeedEvent::new_ecological_sustainability(actor.to_string(), "https://example.org".to_string(), &SystemClock, &RandomIds)
+    }
+
+    #[test]
+    fn follower_resumes_after_its_last_ack() {
+        let dir = tempfile::tempdir().unwrap();
+        let cursor_path = dir.path().join("planner.cursor.json");
+        let mut ledger = MoralLedger::with_store(MemoryStore::default()).unwrap();
+        ledger.append(deed("alice")).unwrap();
+
+        let mut follower = Follower::open(cursor_path.clone()).unwrap();
+        let backlog = follower.attach(&mut ledger).unwrap();
+        assert_eq!(backlog.len(), 1);
+        follower.ack(backlog[0].0, &backlog[0].1).unwrap();
+
+        ledger.append(deed("bob")).unwrap();
+        ledger.append(deed("carol")).unwrap();
+        let (seq, bob) = follower.recv_timeout(Duration::ZERO).unwrap().unwrap();
+        assert_eq!((seq, bob.actor_id.as_str()), (1, "bob"));
+        follower.ack(seq, &bob).unwrap();
+        assert!(follower.ack(seq, &bob).is_err());
+        drop(follower);
+
+        // Carol was delivered but never acknowledged, so she comes again.
+        let mut follower = Follower::open(cursor_path).unwrap();
+        let backlog = follower.attach(&mut ledger).unwrap();
+        assert_eq!(backlog.iter().map(|(s, _)| *s).collect::<Vec<_>>(), vec![2]);
+        assert!(follower.recv_timeout(Duration::ZERO).unwrap().is_none());
+
+        let from_hash = Cursor::after_hash(ledger.store(), &bob.self_hash).unwrap();
+        assert_eq!(from_hash, *follower.cursor());
+        assert!(matches!(Cursor::after_hash(ledger.store(), "ff"), Err(FeedError::UnknownHash(_))));
+
+        ledger.rewind_to(1).unwrap();
+        assert!(matches!(follower.recv_timeout(Duration::ZERO), Err(FeedError::Diverged { seq: 1 })));
+        assert!(matches!(follower.attach(&mut ledger), Err(FeedError::Diverged { seq: 2 })));
+    }
+
+    #[test]
+    fn slow_subscribers_are_cut_off() {
+        let mut ledger = MoralLedger::with_store(MemoryStore::default()).unwrap();
+        let feed = ledger.subscribe();
+        for _ in 0..=FEED_BUFFER {
+            ledger.append(deed("alice")).unwrap();
+        }
+        assert_eq!(feed.try_iter().count(), FEED_BUFFER);
+        assert!(matches!(feed.try_recv(), Err(mpsc::TryRecvError::Disconnected)));
+    }
+}
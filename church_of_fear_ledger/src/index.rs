@@ -0,0 +1,416 @@
+//! Secondary indexes over the moral ledger plus the paginated query API.
+//!
+//! The index maps actor, target, deed type, tag and timestamp to chain
+//! positions (`seq`, 0-based). `MoralLedger` updates it on every append and
+//! snapshots it next to the ledger file, so lookups never re-read the chain.
+//! The Merkle tree over the chain's `self_hash`es is kept in the same
+//! snapshot for the same reason.
+//!
+//! Corrections are folded in as they are indexed: a corrected deed is
+//! indexed (and returned by queries) under its effective fields, and the
+//! index remembers which correction applies to which deed.
+
+use crate::deed::DeedEvent;
+use crate::store::{LedgerStore, StoreError};
+use deed_schema::correction::check_correction;
+use deed_schema::{CorrectableDeed, MerkleTree};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the snapshot layout changes; older snapshots are rebuilt.
+const INDEX_VERSION: u32 = 1;
+
+/// Appends between index snapshots; a stale snapshot is caught up on open.
+const INDEX_FLUSH_EVERY: u64 = 256;
+
+/// Where a ledger snapshots its index, and how many appends the snapshot
+/// is behind.
+#[derive(Debug)]
+pub(crate) struct IndexFile {
+    path: PathBuf,
+    unflushed: u64,
+}
+
+impl IndexFile {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path, unflushed: 0 }
+    }
+
+    /// Count one append; `true` once a snapshot is due.
+    pub(crate) fn count(&mut self) -> bool {
+        self.unflushed += 1;
+        self.unflushed >= INDEX_FLUSH_EVERY
+    }
+
+    pub(crate) fn is_behind(&self) -> bool {
+        self.unflushed > 0
+    }
+
+    pub(crate) fn save(&mut self, index: &DeedIndex) -> Result<(), StoreError> {
+        index.save(&self.path)?;
+        self.unflushed = 0;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeedIndex {
+    #[serde(default)]
+    version: u32,
+    /// Number of chain positions covered by this index.
+    pub covered: u64,
+    /// `self_hash` at position `covered - 1`, used to detect a stale snapshot.
+    pub head: Option<String>,
+    by_actor: HashMap<String, Vec<u64>>,
+    by_target: HashMap<String, Vec<u64>>,
+    by_deed_type: HashMap<String, Vec<u64>>,
+    by_tag: HashMap<String, Vec<u64>>,
+    by_time: BTreeMap<i64, Vec<u64>>,
+    /// One leaf per covered position; snapshots that predate it are rebuilt.
+    #[serde(default)]
+    pub merkle: MerkleTree,
+    #[serde(default)]
+    by_event_id: HashMap<String, u64>,
+    /// Applied corrections: correction position -> corrected position.
+    #[serde(default)]
+    corrections: BTreeMap<u64, u64>,
+}
+
+impl Default for DeedIndex {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            covered: 0,
+            head: None,
+            by_actor: HashMap::new(),
+            by_target: HashMap::new(),
+            by_deed_type: HashMap::new(),
+            by_tag: HashMap::new(),
+            by_time: BTreeMap::new(),
+            merkle: MerkleTree::default(),
+            by_event_id: HashMap::new(),
+            corrections: BTreeMap::new(),
+        }
+    }
+}
+
+impl DeedIndex {
+    /// Index the event stored at position `seq` (must be `self.covered`),
+    /// then apply it if it is a valid correction. Returns the corrected
+    /// position in that case.
+    pub fn index_event<S: LedgerStore>(&mut self, store: &S, seq: u64, event: &DeedEvent) -> Result<Option<u64>, StoreError> {
+        self.insert(seq, event);
+        let Some(correction) = event.correction() else {
+            return Ok(None);
+        };
+        let target = match correction {
+            Ok(c) => self.by_event_id.get(&c.corrects).copied().map(|t| (t, c)),
+            Err(e) => {
+                log::warn!("correction {} at {} is malformed: {}", event.event_id, seq, e);
+                None
+            }
+        };
+        let Some((target, correction)) = target else {
+            log::warn!("correction {} at {} names no earlier deed", event.event_id, seq);
+            return Ok(None);
+        };
+        let before = self.effective(store, target, seq)?;
+        if let Err(e) = check_correction(event, &correction, before.as_ref()) {
+            log::warn!("correction {} at {} ignored: {}", event.event_id, seq, e);
+            return Ok(None);
+        }
+        let before = before.expect("checked above");
+        let mut after = before.clone();
+        after.supersede(&correction.set);
+        self.corrections.insert(seq, target);
+        self.reindex(target, &before, &after);
+        Ok(Some(target))
+    }
+
+    /// The deed at `seq` with every correction before position `upto` applied.
+    pub fn effective<S: LedgerStore>(&self, store: &S, seq: u64, upto: u64) -> Result<Option<DeedEvent>, StoreError> {
+        let Some(mut deed) = store.get(seq)? else {
+            return Ok(None);
+        };
+        for (&at, _) in self.corrections.range(..upto).filter(|(_, &target)| target == seq) {
+            if let Some(Ok(correction)) = store.get(at)?.and_then(|c| c.correction()) {
+                deed.supersede(&correction.set);
+            }
+        }
+        Ok(Some(deed))
+    }
+
+    /// Position of the deed the correction at `seq` was applied to.
+    pub fn correction_target(&self, seq: u64) -> Option<u64> {
+        self.corrections.get(&seq).copied()
+    }
+
+    /// Number of deeds before the first one stamped after `timestamp`. Every
+    /// deed stamped at or before it, since appends keep timestamps in order.
+    pub fn height_at(&self, timestamp: i64) -> u64 {
+        self.by_time.range(timestamp.saturating_add(1)..).flat_map(|(_, seqs)| seqs).min().copied().unwrap_or(self.covered)
+    }
+
+    /// Newest timestamp among the first `len` deeds.
+    pub fn latest_timestamp_before(&self, len: u64) -> Option<i64> {
+        self.by_time.iter().rev().find(|(_, seqs)| seqs.iter().any(|&seq| seq < len)).map(|(&timestamp, _)| timestamp)
+    }
+
+    /// Position of the deed with `event_id`.
+    pub fn position_of(&self, event_id: &str) -> Option<u64> {
+        self.by_event_id.get(event_id).copied()
+    }
+
+    /// Move `seq` from the keys of `before` to those of `after`.
+    fn reindex(&mut self, seq: u64, before: &DeedEvent, after: &DeedEvent) {
+        fn remove(map: &mut HashMap<String, Vec<u64>>, key: &str, seq: u64) {
+            if let Some(hits) = map.get_mut(key) {
+                if let Ok(i) = hits.binary_search(&seq) {
+                    hits.remove(i);
+                }
+            }
+        }
+        fn add(map: &mut HashMap<String, Vec<u64>>, key: &str, seq: u64) {
+            let hits = map.entry(key.to_string()).or_default();
+            if let Err(i) = hits.binary_search(&seq) {
+                hits.insert(i, seq);
+            }
+        }
+        remove(&mut self.by_deed_type, &before.deed_type, seq);
+        add(&mut self.by_deed_type, &after.deed_type, seq);
+        for target in &before.target_ids {
+            remove(&mut self.by_target, target, seq);
+        }
+        for target in &after.target_ids {
+            add(&mut self.by_target, target, seq);
+        }
+        for tag in &before.tags {
+            remove(&mut self.by_tag, tag, seq);
+        }
+        for tag in &after.tags {
+            add(&mut self.by_tag, tag, seq);
+        }
+    }
+
+    fn insert(&mut self, seq: u64, event: &DeedEvent) {
+        debug_assert_eq!(seq, self.covered, "index positions must be appended in order");
+        self.by_actor.entry(event.actor_id.clone()).or_default().push(seq);
+        for target in &event.target_ids {
+            self.by_target.entry(target.clone()).or_default().push(seq);
+        }
+        self.by_deed_type.entry(event.deed_type.clone()).or_default().push(seq);
+        for tag in &event.tags {
+            self.by_tag.entry(tag.clone()).or_default().push(seq);
+        }
+        self.by_time.entry(event.timestamp).or_default().push(seq);
+        self.by_event_id.entry(event.event_id.to_string()).or_insert(seq);
+        self.merkle.push_self_hash(&event.self_hash);
+        self.covered = seq + 1;
+        self.head = Some(event.self_hash.clone());
+    }
+
+    /// Load a snapshot and index whatever the store gained since it was taken.
+    /// A snapshot that does not match the store's chain is rebuilt from scratch.
+    pub fn load_or_rebuild<S: LedgerStore>(path: &Path, store: &S) -> Result<Self, StoreError> {
+        let snapshot: Option<DeedIndex> = match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).ok(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let mut index = match snapshot {
+            Some(idx) if idx.matches(store)? => idx,
+            _ => DeedIndex::default(),
+        };
+        index.catch_up(store)?;
+        Ok(index)
+    }
+
+    /// Index every store position beyond `covered`.
+    pub fn catch_up<S: LedgerStore>(&mut self, store: &S) -> Result<(), StoreError> {
+        for seq in self.covered..store.len() {
+            if let Some(event) = store.get(seq)? {
+                self.index_event(store, seq, &event)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn matches<S: LedgerStore>(&self, store: &S) -> Result<bool, StoreError> {
+        if self.version != INDEX_VERSION || self.merkle.len() != self.covered {
+            return Ok(false);
+        }
+        if self.covered == 0 {
+            return Ok(true);
+        }
+        if self.covered > store.len() {
+            return Ok(false);
+        }
+        let stored = store.get(self.covered - 1)?.map(|e| e.self_hash.clone());
+        Ok(stored == self.head)
+    }
+
+    /// Atomically replace the snapshot at `path` (write temp, fsync, rename).
+    pub fn save(&self, path: &Path) -> Result<(), StoreError> {
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Matching positions in ascending chain order.
+    pub fn select(&self, query: &DeedQuery) -> Vec<u64> {
+        let mut candidates: Option<Vec<u64>> = None;
+        let mut narrow = |hits: Option<&Vec<u64>>| {
+            let hits = hits.cloned().unwrap_or_default();
+            candidates = Some(match candidates.take() {
+                None => hits,
+                Some(current) => intersect(&current, &hits),
+            });
+        };
+        if let Some(actor) = &query.actor_id {
+            narrow(self.by_actor.get(actor));
+        }
+        if let Some(target) = &query.target_id {
+            narrow(self.by_target.get(target));
+        }
+        if let Some(deed_type) = &query.deed_type {
+            narrow(self.by_deed_type.get(deed_type));
+        }
+        if let Some(tag) = &query.tag {
+            narrow(self.by_tag.get(tag));
+        }
+        if query.from.is_some() || query.to.is_some() {
+            let from = query.from.unwrap_or(i64::MIN);
+            let to = query.to.unwrap_or(i64::MAX);
+            let mut in_range: Vec<u64> = self.by_time.range(from..=to).flat_map(|(_, s)| s.iter().copied()).collect();
+            in_range.sort_unstable();
+            narrow(Some(&in_range));
+        }
+        candidates.unwrap_or_else(|| (0..self.covered).collect())
+    }
+}
+
+/// Both inputs are sorted ascending.
+fn intersect(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                out.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Filter for `MoralLedger::query`. All set fields must match; `from`/`to`
+/// are inclusive Unix seconds. Results come in chain order, `limit` per
+/// page, resuming after `after_seq` (the previous page's `next_cursor`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeedQuery {
+    pub actor_id: Option<String>,
+    pub target_id: Option<String>,
+    pub deed_type: Option<String>,
+    pub tag: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub after_seq: Option<u64>,
+    pub limit: usize,
+}
+
+impl DeedQuery {
+    pub fn for_actor(actor_id: &str) -> Self {
+        Self { actor_id: Some(actor_id.to_string()), limit: DEFAULT_PAGE_SIZE, ..Self::default() }
+    }
+}
+
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Deeds come back in their effective (corrected) form.
+#[derive(Debug, Clone)]
+pub struct DeedPage {
+    pub items: Vec<(u64, DeedEvent)>,
+    /// Pass back as `after_seq` to fetch the next page; `None` on the last page.
+    pub next_cursor: Option<u64>,
+    /// Total matches across all pages.
+    pub total: usize,
+}
+
+pub fn run_query<S: LedgerStore>(index: &DeedIndex, store: &S, query: &DeedQuery) -> Result<DeedPage, StoreError> {
+    let hits = index.select(query);
+    let total = hits.len();
+    let limit = if query.limit == 0 { DEFAULT_PAGE_SIZE } else { query.limit };
+    let start = match query.after_seq {
+        Some(after) => hits.partition_point(|seq| *seq <= after),
+        None => 0,
+    };
+    let page: Vec<u64> = hits[start..].iter().take(limit).copied().collect();
+    let next_cursor = if start + page.len() < total { page.last().copied() } else { None };
+
+    let mut items = Vec::with_capacity(page.len());
+    for seq in page {
+        if let Some(event) = index.effective(store, seq, u64::MAX)? {
+            items.push((seq, event));
+        }
+    }
+    Ok(DeedPage { items, next_cursor, total })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+    use crate::ledger::MoralLedger;
+    use crate::store::MemoryStore;
+
+    #[test]
+    fn filters_intersect_and_paginate() {
+        let mut ledger = MoralLedger::with_store(MemoryStore::new()).unwrap();
+        for i in 0..5 {
+            let actor = if i % 2 == 0 { "alice" } else { "bob" };
+            ledger.append(DeedEvent::new_math_science_education(actor.into(), format!("crate-{}", i), &SystemClock, &RandomIds)).unwrap();
+        }
+        ledger.append(DeedEvent::new_ecological_sustainability("alice".into(), "ipfs://r".into(), &SystemClock, &RandomIds)).unwrap();
+
+        let mut query = DeedQuery::for_actor("alice");
+        query.deed_type = Some("math_science_education".into());
+        query.limit = 2;
+        let first = ledger.query(&query).unwrap();
+        assert_eq!(first.total, 3);
+        assert_eq!(first.items.iter().map(|(s, _)| *s).collect::<Vec<_>>(), vec![0, 2]);
+
+        query.after_seq = first.next_cursor;
+        let second = ledger.query(&query).unwrap();
+        assert_eq!(second.items.iter().map(|(s, _)| *s).collect::<Vec<_>>(), vec![4]);
+        assert!(second.next_cursor.is_none());
+
+        let mut by_tag = DeedQuery { tag: Some("reforestation".into()), ..DeedQuery::default() };
+        by_tag.from = Some(0);
+        assert_eq!(ledger.query(&by_tag).unwrap().total, 1);
+    }
+
+    #[test]
+    fn stale_snapshot_catches_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        {
+            let mut ledger = MoralLedger::open_or_create(path.clone()).unwrap();
+            ledger.append(DeedEvent::new_math_science_education("alice".into(), "a".into(), &SystemClock, &RandomIds)).unwrap();
+            ledger.flush_index().unwrap();
+            ledger.append(DeedEvent::new_math_science_education("alice".into(), "b".into(), &SystemClock, &RandomIds)).unwrap();
+            std::mem::forget(ledger); // crash: no flush on drop
+        }
+        let ledger = MoralLedger::open_or_create(path).unwrap();
+        assert_eq!(ledger.query(&DeedQuery::for_actor("alice")).unwrap().total, 2);
+    }
+}
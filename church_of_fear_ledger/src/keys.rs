@@ -0,0 +1,102 @@
+//! Actor key registry – which ed25519 keys may sign deeds for which DID.
+//!
+//! Keys are registered under their DID URL (`did:method:id#key-1`); the
+//! controlling DID is the part before `#` and must equal the deed's
+//! `actor_id`. Revoked keys stay in the registry so a rotated key cannot be
+//! re-registered by someone else under the same id.
+
+use deed_schema::key_controller;
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum KeyError {
+    #[error("not a DID URL: {0}")]
+    NotADidUrl(String),
+    #[error("key {0} is already registered")]
+    AlreadyRegistered(String),
+    #[error("unknown key {0}")]
+    UnknownKey(String),
+    #[error("invalid public key for {0}")]
+    InvalidKey(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RegisteredKey {
+    public_key: String,     // hex-encoded ed25519 public key
+    revoked: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyRegistry {
+    keys: BTreeMap<String, RegisteredKey>,
+}
+
+impl KeyRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind `key` to the DID named in `key_id`.
+    pub fn register(&mut self, key_id: &str, key: &VerifyingKey) -> Result<(), KeyError> {
+        if key_controller(key_id).is_none() {
+            return Err(KeyError::NotADidUrl(key_id.to_string()));
+        }
+        if self.keys.contains_key(key_id) {
+            return Err(KeyError::AlreadyRegistered(key_id.to_string()));
+        }
+        self.keys.insert(key_id.to_string(), RegisteredKey { public_key: hex::encode(key.as_bytes()), revoked: false });
+        Ok(())
+    }
+
+    /// Stop accepting new deeds signed with `key_id`.
+    pub fn revoke(&mut self, key_id: &str) -> Result<(), KeyError> {
+        let key = self.keys.get_mut(key_id).ok_or_else(|| KeyError::UnknownKey(key_id.to_string()))?;
+        key.revoked = true;
+        Ok(())
+    }
+
+    /// Active key for `key_id`, `None` if unknown or revoked.
+    pub fn resolve(&self, key_id: &str) -> Result<Option<VerifyingKey>, KeyError> {
+        let Some(key) = self.keys.get(key_id).filter(|k| !k.revoked) else {
+            return Ok(None);
+        };
+        let bytes: [u8; 32] = hex::decode(&key.public_key)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| KeyError::InvalidKey(key_id.to_string()))?;
+        VerifyingKey::from_bytes(&bytes).map(Some).map_err(|_| KeyError::InvalidKey(key_id.to_string()))
+    }
+
+    /// Active key ids controlled by `did`.
+    pub fn keys_for(&self, did: &str) -> Vec<&str> {
+        self.keys
+            .iter()
+            .filter(|(id, k)| !k.revoked && key_controller(id) == Some(did))
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, KeyError> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Atomically replace the registry file at `path`.
+    pub fn save(&self, path: &Path) -> Result<(), KeyError> {
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&serde_json::to_vec_pretty(self)?)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
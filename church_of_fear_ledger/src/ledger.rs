@@ -1,51 +1,1089 @@
+use crate::accounts::{earlier_deed, AccountBook};
+use crate::checkpoint::{latest_signed_checkpoint, AccountAggregate, Checkpoint, Checkpointer, NodeKey};
 use crate::deed::DeedEvent;
-use crate::validator::{LedgerValidator, ValidationError};
-use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use crate::evidence::EvidenceStore;
+use crate::feed::{Broadcast, FeedEvent};
+use crate::keys::KeyRegistry;
+use crate::migrate::MIGRATION_DEED_TYPE;
+use crate::index::{run_query, DeedIndex, DeedPage, DeedQuery, IndexFile};
+use crate::store::{JsonlStore, LedgerStore, SegmentedStore, StoreError};
+use crate::validator::{DeedChecks, LedgerValidator, ValidationError};
+use deed_schema::dispute::is_dispute_step;
+use deed_schema::{
+    Anchor, Clock, ConsistencyProof, CorrectableDeed, DisputeBook, IdGenerator, InclusionProof, LedgerRef, RandomIds,
+    ReviewPanel, SystemClock, TreeHead, ANCHOR_DEED_TYPE,
+};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use token_journal::{CeilingPolicy, DebtCeiling, RewardSchedule};
+use uuid::Uuid;
 
 /// Append-only, hash-chained moral ledger (exactly .evolve.jsonl + .donutloop.aln pattern)
+///
+/// Generic over its `LedgerStore`; the default is the durable JSONL file.
+/// With a `KeyRegistry` attached, every new deed must carry a valid
+/// signature from one of its actor's keys. With a `NodeKey` it also writes
+/// signed checkpoints and resumes from the newest one on open.
+///
+/// The chain is the store, its head and its index. Everything else is a
+/// component that `commit` hands each new deed to: the index snapshot file,
+/// the append checks, the account totals, the feed and the checkpoints.
 #[derive(Debug)]
-pub struct MoralLedger {
-    path: PathBuf,
+pub struct MoralLedger<S: LedgerStore = JsonlStore> {
+    store: S,
+    ledger_id: Option<String>,
     last_hash: String,
+    index: DeedIndex,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGenerator>,
+    index_file: Option<IndexFile>,
+    checks: DeedChecks,
+    accounts: AccountBook,
+    feed: Broadcast,
+    checkpoints: Option<Checkpointer>,
 }
 
-impl MoralLedger {
-    pub fn open_or_create(path: PathBuf) -> Result<Self, std::io::Error> {
-        let mut file = OpenOptions::new().read(true).append(true).create(true).open(&path)?;
-        let mut last_hash = "0".repeat(64); // genesis
+impl MoralLedger<JsonlStore> {
+    /// Opens the JSONL ledger and its index snapshot (`<path>.idx.json`).
+    pub fn open_or_create(path: PathBuf) -> Result<Self, StoreError> {
+        let index_path = sibling(&path, ".idx.json");
+        Self::with_store_and_index_file(JsonlStore::open(path)?, index_path)
+    }
+
+    /// Like `open_or_create`, plus signed checkpoints in `<path>.ckpt.jsonl`.
+    /// Records covered by the newest valid checkpoint are not re-parsed and
+    /// account totals are replayed from it.
+    pub fn open_with_node_key(path: PathBuf, node: NodeKey) -> Result<Self, StoreError> {
+        let (index_path, checkpoint_path) = (sibling(&path, ".idx.json"), sibling(&path, ".ckpt.jsonl"));
+        let checkpoint = latest_signed_checkpoint(&checkpoint_path, &node.signing_key.verifying_key())?;
+        let resume = checkpoint.as_ref().and_then(Checkpoint::resume_point);
+        let store = JsonlStore::open_from(path, resume.as_ref())?;
+        let index = DeedIndex::load_or_rebuild(&index_path, &store)?;
+        let mut ledger = Self::from_parts(store, index, Some(index_path), checkpoint)?;
+        ledger.checkpoints = Some(Checkpointer::new(checkpoint_path, node));
+        Ok(ledger)
+    }
+}
+
+impl MoralLedger<SegmentedStore> {
+    /// Opens a segmented ledger directory (see `store::segmented`) and its
+    /// index snapshot (`<dir>/index.json`).
+    pub fn open_segmented(dir: PathBuf, deeds_per_segment: u64) -> Result<Self, StoreError> {
+        let index_path = dir.join("index.json");
+        Self::with_store_and_index_file(SegmentedStore::open(dir, deeds_per_segment)?, index_path)
+    }
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    name.into()
+}
+
+impl<S: LedgerStore> MoralLedger<S> {
+    /// Wrap any store; the chain resumes from its newest event. The index is
+    /// built in memory only.
+    pub fn with_store(store: S) -> Result<Self, StoreError> {
+        let mut index = DeedIndex::default();
+        index.catch_up(&store)?;
+        Self::from_parts(store, index, None, None)
+    }
+
+    /// Wrap any store and persist its index at `index_path`, reusing an
+    /// existing snapshot when it still matches the chain.
+    pub fn with_store_and_index_file(store: S, index_path: PathBuf) -> Result<Self, StoreError> {
+        let index = DeedIndex::load_or_rebuild(&index_path, &store)?;
+        Self::from_parts(store, index, Some(index_path), None)
+    }
+
+    /// Wrap any store with an index file and signed checkpoints at
+    /// `checkpoint_path`.
+    pub fn with_store_and_checkpoints(
+        store: S,
+        index_path: PathBuf,
+        checkpoint_path: PathBuf,
+        node: NodeKey,
+    ) -> Result<Self, StoreError> {
+        let checkpoint = latest_signed_checkpoint(&checkpoint_path, &node.signing_key.verifying_key())?;
+        let index = DeedIndex::load_or_rebuild(&index_path, &store)?;
+        let mut ledger = Self::from_parts(store, index, Some(index_path), checkpoint)?;
+        ledger.checkpoints = Some(Checkpointer::new(checkpoint_path, node));
+        Ok(ledger)
+    }
+
+    fn from_parts(
+        store: S,
+        index: DeedIndex,
+        index_path: Option<PathBuf>,
+        checkpoint: Option<Checkpoint>,
+    ) -> Result<Self, StoreError> {
+        let last_hash = store.last_hash().unwrap_or_else(|| "0".repeat(64)); // genesis
 
-        if path.exists() {
-            let reader = BufReader::new(File::open(&path)?);
-            for line in reader.lines() {
-                let line = line?;
-                if line.trim().is_empty() { continue; }
-                let event: DeedEvent = serde_json::from_str(&line).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-                last_hash = event.self_hash.clone();
+        // A checkpoint only counts if the store still holds the chain it signed.
+        let checkpoint = match checkpoint {
+            Some(cp) if cp.event_count == 0 => Some(cp),
+            Some(cp) if cp.event_count <= store.len() => {
+                let head = store.get(cp.event_count - 1)?.map(|e| e.self_hash.clone());
+                if head.as_deref() == Some(cp.chain_head.as_str()) {
+                    Some(cp)
+                } else {
+                    log::warn!("checkpoint at {} does not match the chain; replaying from genesis", cp.event_count);
+                    None
+                }
             }
+            _ => None,
+        };
+        let (mut accounts, replay_from) = match checkpoint {
+            Some(cp) => (AccountBook::resume(cp.accounts, cp.disputes, cp.reward_schedule), cp.event_count),
+            None => (AccountBook::default(), 0),
+        };
+        accounts.fold_range(&index, &store, replay_from, store.len())?;
+
+        Ok(Self {
+            store,
+            ledger_id: None,
+            last_hash,
+            index,
+            clock: Arc::new(SystemClock),
+            ids: Arc::new(RandomIds),
+            index_file: index_path.map(IndexFile::new),
+            checks: DeedChecks::default(),
+            accounts,
+            feed: Broadcast::default(),
+            checkpoints: None,
+        })
+    }
+
+    /// Name this ledger for cross-ledger references (`[A-Za-z0-9._-]+`,
+    /// e.g. the congregation's name).
+    pub fn with_ledger_id(mut self, ledger_id: String) -> Self {
+        self.ledger_id = Some(ledger_id);
+        self
+    }
+
+    pub fn ledger_id(&self) -> Option<&str> {
+        self.ledger_id.as_deref()
+    }
+
+    /// Require actor signatures on every append from now on.
+    pub fn with_key_registry(mut self, keys: KeyRegistry) -> Self {
+        self.checks.keys = Some(keys);
+        self
+    }
+
+    pub fn key_registry_mut(&mut self) -> Option<&mut KeyRegistry> {
+        self.checks.keys.as_mut()
+    }
+
+    /// Time source for checkpoints, credentials and fork evidence.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Id source for the deeds this ledger's callers construct, e.g. the
+    /// `church` helpers, alongside `clock`.
+    pub fn with_ids(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    pub fn ids(&self) -> &dyn IdGenerator {
+        self.ids.as_ref()
+    }
+
+    /// Require the evidence new deeds reference by content id to be in
+    /// `evidence` and intact. Deeds from peers are not checked.
+    pub fn with_evidence_store(mut self, evidence: EvidenceStore) -> Self {
+        self.checks.evidence = Some(evidence);
+        self
+    }
+
+    pub fn evidence_store(&self) -> Option<&EvidenceStore> {
+        self.checks.evidence.as_ref()
+    }
+
+    /// Enable disputes decided by `panel`. Needs a key registry, so every
+    /// dispute step is signed by its actor. Dispute outcomes depend on the
+    /// panel: a ledger resumed from a checkpoint written under the same
+    /// panel keeps its dispute state, otherwise account totals are replayed
+    /// from genesis under it.
+    pub fn with_review_panel(mut self, panel: ReviewPanel) -> Result<Self, ValidationError> {
+        if self.checks.keys.is_none() {
+            return Err(ValidationError::InvalidDispute("a review panel needs a key registry".to_string()));
         }
+        if self.accounts.set_panel(panel) {
+            self.replay_accounts()?;
+        }
+        Ok(self)
+    }
 
-        Ok(Self { path, last_hash })
+    pub fn disputes(&self) -> Option<&DisputeBook> {
+        self.accounts.disputes()
+    }
+
+    /// Take CHURCH recommendations from `schedule`, e.g. one loaded with
+    /// `RewardSchedule::load` from the deployment's policy file, instead of
+    /// the builtin one. Like the review panel, a ledger resumed from a
+    /// checkpoint written under the same schedule keeps its account totals;
+    /// otherwise they are replayed from genesis under it.
+    pub fn with_reward_schedule(mut self, schedule: RewardSchedule) -> Result<Self, StoreError> {
+        if self.accounts.set_schedule(schedule) {
+            self.replay_accounts()?;
+        }
+        Ok(self)
+    }
+
+    pub fn reward_schedule(&self) -> &RewardSchedule {
+        self.accounts.schedule()
+    }
+
+    /// Every deed committed from now on, in chain order. See `feed` for
+    /// back-pressure and for resuming with a `Follower`.
+    pub fn subscribe(&mut self) -> Receiver<FeedEvent> {
+        self.feed.subscribe()
+    }
+
+    fn replay_accounts(&mut self) -> Result<(), StoreError> {
+        self.accounts = self.accounts.fresh();
+        self.accounts.fold_range(&self.index, &self.store, 0, self.store.len())
     }
 
     /// Append a new deed – performs full validation + hash chaining
-    pub fn append(&mut self, mut event: DeedEvent) -> Result<Uuid, ValidationError> {
+    pub fn append(&mut self, event: DeedEvent) -> Result<Uuid, ValidationError> {
         LedgerValidator::validate_new_event(&event, &self.last_hash)?;
-        event = event.finalize_hash_chain(self.last_hash.clone());
-
-        let serialized = serde_json::to_string(&event).map_err(ValidationError::Serialization)?;
-        let mut file = OpenOptions::new().append(true).open(&self.path)
-            .map_err(ValidationError::Io)?;
-        writeln!(file, "{}", serialized).map_err(ValidationError::Io)?;
-        self.last_hash = event.self_hash.clone();
+        LedgerValidator::validate_timestamp(&event, self.index.latest_timestamp_before(self.store.len()))?;
+        self.check_correction(&event)?;
+        self.check_dispute_step(&event)?;
+        self.checks.check_new(&event)?;
+        LedgerValidator::validate_references(&event)?;
+        let event = event.finalize_hash_chain(self.last_hash.clone());
+        self.commit(&event)?;
 
         // CHURCH recommendation (advisory logging only)
-        let recommendation = event.church_recommendation();
+        let recommendation = event.church_recommendation(self.accounts.schedule());
         if recommendation > 0 {
             log::info!("CHURCH recommendation +{} for deed {} by {}", recommendation, event.event_id, event.actor_id);
         }
 
         Ok(event.event_id)
     }
+
+    /// Append a deed sealed by another node (replication). It goes through
+    /// the same checks as `append`, must already link to our head and must
+    /// carry its own valid `self_hash`; nothing is re-hashed.
+    pub fn append_sealed(&mut self, event: &DeedEvent) -> Result<(), ValidationError> {
+        self.check_sealed(event, &self.last_hash, self.index.latest_timestamp_before(self.store.len()))?;
+        self.commit(event)
+    }
+
+    /// Run the `append_sealed` checks over a whole branch that would follow
+    /// `prev_hash`, without changing the ledger.
+    pub fn verify_branch(&self, prev_hash: &str, deeds: &[DeedEvent]) -> Result<(), ValidationError> {
+        let mut prev = prev_hash;
+        let mut previous_timestamp = self.index.latest_timestamp_before(self.len_ending_in(prev_hash)?);
+        for deed in deeds {
+            self.check_sealed(deed, prev, previous_timestamp)?;
+            prev = &deed.self_hash;
+            previous_timestamp = Some(deed.timestamp);
+        }
+        Ok(())
+    }
+
+    /// Length of the prefix of our chain whose last deed is `hash`; 0 if no
+    /// deed has it (genesis).
+    fn len_ending_in(&self, hash: &str) -> Result<u64, StoreError> {
+        if hash == self.last_hash {
+            return Ok(self.store.len());
+        }
+        for len in (1..self.store.len()).rev() {
+            if self.store.get(len - 1)?.is_some_and(|e| e.self_hash == hash) {
+                return Ok(len);
+            }
+        }
+        Ok(0)
+    }
+
+    fn check_sealed(&self, event: &DeedEvent, prev_hash: &str, previous_timestamp: Option<i64>) -> Result<(), ValidationError> {
+        LedgerValidator::validate_new_event(event, prev_hash)?;
+        LedgerValidator::validate_timestamp(event, previous_timestamp)?;
+        if event.prev_hash != prev_hash {
+            return Err(ValidationError::HashMismatch { expected: prev_hash.to_string(), actual: event.prev_hash.clone() });
+        }
+        if !event.verify_self_hash() {
+            let mut unsealed = event.clone();
+            unsealed.self_hash = String::new();
+            return Err(ValidationError::HashMismatch {
+                expected: unsealed.compute_self_hash(),
+                actual: event.self_hash.clone(),
+            });
+        }
+        if self.checks.keys.is_none() && self.accounts.disputes().is_some() && is_dispute_step(&event.deed_type) {
+            return Err(ValidationError::InvalidDispute("dispute steps need a key registry".to_string()));
+        }
+        self.checks.check_signature(event)
+    }
+
+    /// A correction must name an earlier deed of its own actor that is not
+    /// itself a correction. Sealed deeds are not checked: an invalid
+    /// correction from a peer is kept in the chain but has no effect.
+    fn check_correction(&self, event: &DeedEvent) -> Result<(), ValidationError> {
+        let Some(correction) = event.correction() else {
+            return Ok(());
+        };
+        let correction = correction.map_err(|e| ValidationError::InvalidCorrection(e.to_string()))?;
+        let target = match self.index.position_of(&correction.corrects) {
+            Some(seq) => self.index.effective(&self.store, seq, u64::MAX)?,
+            None => None,
+        };
+        deed_schema::correction::check_correction(event, &correction, target.as_ref())
+            .map_err(|e| ValidationError::InvalidCorrection(e.to_string()))
+    }
+
+    /// Dispute steps need a review panel and a key registry (`append`
+    /// checks the signature) and must follow the panel's rules.
+    fn check_dispute_step(&self, event: &DeedEvent) -> Result<(), ValidationError> {
+        if !is_dispute_step(&event.deed_type) {
+            return Ok(());
+        }
+        let Some(book) = self.accounts.disputes() else {
+            return Err(ValidationError::InvalidDispute("no review panel is configured".to_string()));
+        };
+        if self.checks.keys.is_none() {
+            return Err(ValidationError::InvalidDispute("dispute steps need a key registry".to_string()));
+        }
+        let mut failed = None;
+        let checked = book.check(event, |id| {
+            earlier_deed(&self.index, &self.store, id, self.store.len()).unwrap_or_else(|e| {
+                failed = Some(e);
+                None
+            })
+        });
+        if let Some(e) = failed {
+            return Err(e.into());
+        }
+        checked.map_err(|e| ValidationError::InvalidDispute(e.to_string()))
+    }
+
+    fn commit(&mut self, event: &DeedEvent) -> Result<(), ValidationError> {
+        self.store.append(event)?;
+        self.last_hash = event.self_hash.clone();
+        let seq = self.store.len() - 1;
+        self.index.index_event(&self.store, seq, event)?;
+        self.accounts.fold(&self.index, &self.store, seq, event)?;
+        self.feed.send(|| FeedEvent::Appended { seq, deed: Arc::new(event.clone()) });
+        if self.index_file.as_mut().is_some_and(IndexFile::count) {
+            self.flush_index()?;
+        }
+        if self.checkpoints.as_mut().is_some_and(Checkpointer::count) {
+            self.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    /// Drop every deed from position `len` on and return them, oldest first.
+    /// Only fork resolution uses this, and it records the returned branch
+    /// as fork evidence before adopting the winning one.
+    pub(crate) fn rewind_to(&mut self, len: u64) -> Result<Vec<DeedEvent>, StoreError> {
+        let removed = self.store.read_from(len)?;
+        self.store.truncate(len)?;
+        self.last_hash = self.store.last_hash().unwrap_or_else(|| "0".repeat(64));
+        self.index = DeedIndex::default();
+        self.index.catch_up(&self.store)?;
+        self.replay_accounts()?;
+        self.feed.send(|| FeedEvent::Rewound { len });
+        self.flush_index()?;
+        self.checkpoint()?;
+        Ok(removed)
+    }
+
+    /// Redact the concealed `field` of the deed at `seq` in place. The chain
+    /// is unchanged: only the disclosure is dropped, the committed digest
+    /// stays. Returns `false` when the field was not concealed or is already
+    /// redacted.
+    pub fn redact(&mut self, seq: u64, field: &str) -> Result<bool, StoreError> {
+        let Some(mut event) = self.store.get(seq)? else {
+            return Ok(false);
+        };
+        if !event.redact(field) {
+            return Ok(false);
+        }
+        debug_assert!(event.verify_self_hash());
+        self.store.rewrite(seq, &event)?;
+        log::info!("redacted {} of deed {} at position {}", field, event.event_id, seq);
+        // JSONL offsets after `seq` moved.
+        self.checkpoint()?;
+        Ok(true)
+    }
+
+    /// Number of deeds in the chain.
+    pub fn len(&self) -> u64 {
+        self.store.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.store.is_empty()
+    }
+
+    pub fn last_hash(&self) -> &str {
+        &self.last_hash
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// The deed at `seq` with every correction of it applied; the stored
+    /// original is `store().get(seq)`.
+    pub fn effective_deed(&self, seq: u64) -> Result<Option<DeedEvent>, StoreError> {
+        let mut deed = self.index.effective(&self.store, seq, u64::MAX)?;
+        if let Some(deed) = &mut deed {
+            self.accounts.apply_standing(deed);
+        }
+        Ok(deed)
+    }
+
+    /// Indexed lookup by actor / target / deed type / tag / time range.
+    pub fn query(&self, query: &DeedQuery) -> Result<DeedPage, StoreError> {
+        let mut page = run_query(&self.index, &self.store, query)?;
+        for (_, deed) in &mut page.items {
+            self.accounts.apply_standing(deed);
+        }
+        Ok(page)
+    }
+
+    /// Current Merkle tree head; publish or sign it so deeds can later be
+    /// proven against it.
+    pub fn tree_head(&self) -> TreeHead {
+        self.index.merkle.head()
+    }
+
+    /// Merkle tree head of the first `tree_size` deeds.
+    pub fn tree_head_at(&self, tree_size: u64) -> Result<TreeHead, StoreError> {
+        Ok(self.index.merkle.head_at(tree_size)?)
+    }
+
+    /// Chain position of the deed `event_id`.
+    pub fn position_of(&self, event_id: &Uuid) -> Option<u64> {
+        self.index.position_of(&event_id.to_string())
+    }
+
+    /// A reference other ledgers can put in `target_ids` to name the deed
+    /// `event_id`, pinned to the current head. Needs a ledger id.
+    pub fn reference(&self, event_id: Uuid) -> Option<LedgerRef> {
+        self.position_of(&event_id)?;
+        Some(LedgerRef { ledger_id: self.ledger_id.clone()?, event_id, head: self.last_hash.clone() })
+    }
+
+    /// This ledger's current head, for peers to record with
+    /// `DeedEvent::new_anchor`. Needs a ledger id and at least one deed.
+    pub fn anchor(&self) -> Option<Anchor> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(Anchor { ledger_id: self.ledger_id.clone()?, head: self.last_hash.clone(), tree: self.tree_head() })
+    }
+
+    /// Anchors of the peer `ledger_id` recorded here, oldest first, with
+    /// their positions.
+    pub fn anchors(&self, ledger_id: &str) -> Result<Vec<(u64, Anchor)>, StoreError> {
+        let query = DeedQuery { deed_type: Some(ANCHOR_DEED_TYPE.to_string()), limit: usize::MAX, ..DeedQuery::default() };
+        Ok(self
+            .query(&query)?
+            .items
+            .into_iter()
+            .filter_map(|(seq, deed)| Some((seq, Anchor::from_deed(&deed.deed_type, &deed.context_json)?.ok()?)))
+            .filter(|(_, anchor)| anchor.ledger_id == ledger_id)
+            .collect())
+    }
+
+    /// Proof that the deed at position `seq` is in the tree of the first
+    /// `tree_size` deeds (an earlier published head, or `len()` for now).
+    pub fn inclusion_proof(&self, seq: u64, tree_size: u64) -> Result<InclusionProof, StoreError> {
+        Ok(self.index.merkle.inclusion_proof(seq, tree_size)?)
+    }
+
+    /// Proof that the current tree extends the head of `old_size` deeds.
+    pub fn consistency_proof(&self, old_size: u64) -> Result<ConsistencyProof, StoreError> {
+        Ok(self.index.merkle.consistency_proof(old_size, self.index.merkle.len())?)
+    }
+
+    /// Running totals for `actor_id`, as of the newest deed.
+    pub fn account(&self, actor_id: &str) -> Option<&AccountAggregate> {
+        self.accounts.get(actor_id)
+    }
+
+    /// The account of `actor_id` as it stood after the first `height` deeds,
+    /// with only the corrections and dispute steps recorded before that
+    /// point. Replays from the newest checkpoint at or below `height`, or
+    /// from genesis without one.
+    pub fn account_at(&self, actor_id: &str, height: u64) -> Result<Option<AccountAggregate>, StoreError> {
+        let height = height.min(self.store.len());
+        let (mut accounts, from) = match self.checkpoint_at_or_below(height)? {
+            Some(cp) => (AccountBook::resume(cp.accounts, cp.disputes, cp.reward_schedule), cp.event_count),
+            None => (self.accounts.fresh(), 0),
+        };
+        accounts.fold_range(&self.index, &self.store, from, height)?;
+        Ok(accounts.remove(actor_id))
+    }
+
+    /// `actor_id`'s debt ceiling under `policy`, with every step. Deeds count
+    /// as readers see them, with corrections and dispute outcomes applied;
+    /// corrections, dispute steps and bookkeeping deeds are not deeds of
+    /// their own. A good deed grows the ceiling only if it is verified: its
+    /// stored original is signed by one of its actor's keys in the attached
+    /// `KeyRegistry`, or it names evidence that the attached `EvidenceStore`
+    /// holds.
+    pub fn debt_ceiling(&self, actor_id: &str, policy: &CeilingPolicy) -> Result<DebtCeiling, StoreError> {
+        let mut ceiling = DebtCeiling::new(policy);
+        let query = DeedQuery { limit: usize::MAX, ..DeedQuery::for_actor(actor_id) };
+        for seq in self.index.select(&query) {
+            let Some(original) = self.store.get(seq)? else {
+                continue;
+            };
+            let bookkeeping = original.deed_type == MIGRATION_DEED_TYPE || original.deed_type == ANCHOR_DEED_TYPE;
+            if bookkeeping || is_dispute_step(&original.deed_type) || original.correction().is_some() {
+                continue;
+            }
+            if let Some(deed) = self.effective_deed(seq)? {
+                ceiling.apply(policy, seq, &deed, self.checks.is_verified(&original, &deed));
+            }
+        }
+        Ok(ceiling)
+    }
+
+    /// Newest own checkpoint of at most `height` deeds that still matches the
+    /// chain and was written under the current review panel and reward schedule.
+    fn checkpoint_at_or_below(&self, height: u64) -> Result<Option<Checkpoint>, StoreError> {
+        let panel = self.accounts.disputes().map(DisputeBook::panel);
+        let schedule = self.accounts.checkpoint_schedule();
+        self.own_checkpoint(|cp| {
+            cp.event_count <= height
+                && cp.disputes.as_ref().map(DisputeBook::panel) == panel
+                && cp.reward_schedule == schedule
+        })
+    }
+
+    /// Newest own signed checkpoint that still matches the chain; what this
+    /// node vouches for when it settles a fork with a peer.
+    pub fn latest_checkpoint(&self) -> Result<Option<Checkpoint>, StoreError> {
+        self.own_checkpoint(|_| true)
+    }
+
+    fn own_checkpoint(&self, accept: impl Fn(&Checkpoint) -> bool) -> Result<Option<Checkpoint>, StoreError> {
+        let Some(checkpoints) = &self.checkpoints else {
+            return Ok(None);
+        };
+        for cp in checkpoints.signed()? {
+            if !accept(&cp) {
+                continue;
+            }
+            let on_chain = match cp.event_count {
+                0 => true,
+                n => self.store.get(n - 1)?.is_some_and(|e| e.self_hash == cp.chain_head),
+            };
+            if on_chain {
+                return Ok(Some(cp));
+            }
+        }
+        Ok(None)
+    }
+
+    /// `account_at` the height of the chain at `timestamp`.
+    pub fn account_as_of(&self, actor_id: &str, timestamp: i64) -> Result<Option<AccountAggregate>, StoreError> {
+        self.account_at(actor_id, self.index.height_at(timestamp))
+    }
+
+    /// Sign and persist a checkpoint of the current state. `None` when the
+    /// ledger has no node key.
+    pub fn checkpoint(&mut self) -> Result<Option<Checkpoint>, StoreError> {
+        let Some(checkpoints) = &mut self.checkpoints else {
+            return Ok(None);
+        };
+        let checkpoint = Checkpoint {
+            event_count: self.store.len(),
+            chain_head: self.last_hash.clone(),
+            merkle_root: self.index.merkle.head().root,
+            byte_offset: self.store.resume_offset(),
+            created_at: self.clock.unix_seconds(),
+            accounts: self.accounts.totals().clone(),
+            disputes: self.accounts.disputes().cloned(),
+            reward_schedule: self.accounts.checkpoint_schedule(),
+            signature: None,
+        };
+        Ok(Some(checkpoints.write(checkpoint)?))
+    }
+
+    /// Write the index snapshot now (no-op without an index file).
+    pub fn flush_index(&mut self) -> Result<(), StoreError> {
+        if let Some(file) = &mut self.index_file {
+            file.save(&self.index)?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: LedgerStore> Drop for MoralLedger<S> {
+    fn drop(&mut self) {
+        if self.index_file.as_ref().is_some_and(IndexFile::is_behind) {
+            if let Err(e) = self.flush_index() {
+                log::warn!("could not persist deed index: {}", e);
+            }
+        }
+    }
+}
+
+/// One redacted field, as `redactions` reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redaction {
+    pub seq: u64,
+    pub event_id: Uuid,
+    pub field: String,
+}
+
+/// Every redacted field in the chain. Redactions do not make
+/// `validate_ledger` fail; this is how they are shown.
+pub fn redactions<S: LedgerStore>(store: &S) -> Result<Vec<Redaction>, StoreError> {
+    let mut found = Vec::new();
+    for (seq, event) in store.read_all()?.iter().enumerate() {
+        for field in event.redacted_fields() {
+            found.push(Redaction { seq: seq as u64, event_id: event.event_id, field });
+        }
+    }
+    Ok(found)
+}
+
+/// Validate the entire chain held by any store (real-world audit function).
+pub fn validate_ledger<S: LedgerStore>(store: &S) -> Result<bool, StoreError> {
+    Ok(chain_is_valid(&store.read_all()?))
+}
+
+/// `validate_ledger` for a JSONL file that is not open as a store. The file
+/// is only read; a torn tail is not a record.
+pub fn validate_ledger_file<P: AsRef<Path>>(path: P) -> Result<bool, StoreError> {
+    let text = std::fs::read_to_string(path)?;
+    let complete = text.rfind('\n').map_or(0, |i| i + 1);
+    let mut events = Vec::new();
+    for (idx, line) in text[..complete].lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(serde_json::from_str(line).map_err(|e| StoreError::Corrupt { line: idx + 1, reason: e.to_string() })?);
+    }
+    Ok(chain_is_valid(&events))
+}
+
+fn chain_is_valid(events: &[DeedEvent]) -> bool {
+    let mut prev_hash = "0".repeat(64);
+    for event in events {
+        if event.prev_hash != prev_hash || !event.verify_self_hash() {
+            return false;
+        }
+        prev_hash = event.self_hash.clone();
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+    use crate::store::MemoryStore;
+
+    fn exercise<S: LedgerStore>(store: S) {
+        let mut ledger = MoralLedger::with_store(store).unwrap();
+        ledger.append(DeedEvent::new_ecological_sustainability("a".into(), "ipfs://receipt".into(), &SystemClock, &RandomIds)).unwrap();
+        ledger.append(DeedEvent::new_math_science_education("a".into(), "crate".into(), &SystemClock, &RandomIds)).unwrap();
+        assert_eq!(ledger.store().len(), 2);
+        let range = |from, to| ledger.store().read_range(from, to).unwrap().iter().map(|e| e.deed_type.clone()).collect::<Vec<_>>();
+        assert_eq!(range(0, 1), ["ecological_sustainability"]);
+        assert_eq!(range(1, 5), ["math_science_education"]);
+        assert_eq!((range(0, 2).len(), range(2, 1).len()), (2, 0));
+        assert!(validate_ledger(ledger.store()).unwrap());
+    }
+
+    #[test]
+    fn same_behaviour_on_every_store() {
+        exercise(MemoryStore::new());
+        let dir = tempfile::tempdir().unwrap();
+        exercise(JsonlStore::open(dir.path().join("ledger.jsonl")).unwrap());
+        assert!(validate_ledger_file(dir.path().join("ledger.jsonl")).unwrap());
+        exercise(SegmentedStore::open(dir.path().join("segments"), 1).unwrap());
+        #[cfg(feature = "sqlite")]
+        exercise(crate::store::SqliteStore::open_in_memory().unwrap());
+    }
+
+    #[test]
+    fn deeds_are_provable_against_published_heads() {
+        let mut ledger = MoralLedger::with_store(MemoryStore::new()).unwrap();
+        for i in 0..3 {
+            ledger.append(DeedEvent::new_math_science_education("a".into(), format!("crate-{}", i), &SystemClock, &RandomIds)).unwrap();
+        }
+        let old_head = ledger.tree_head();
+        for i in 3..7 {
+            ledger.append(DeedEvent::new_math_science_education("a".into(), format!("crate-{}", i), &SystemClock, &RandomIds)).unwrap();
+        }
+        let head = ledger.tree_head();
+        let deed = ledger.store().get(1).unwrap().unwrap();
+        let proof = ledger.inclusion_proof(1, head.tree_size).unwrap();
+        assert!(deed_schema::verify_inclusion(&deed.self_hash, &proof, &head));
+        let old_proof = ledger.inclusion_proof(1, old_head.tree_size).unwrap();
+        assert!(deed_schema::verify_inclusion(&deed.self_hash, &old_proof, &old_head));
+        assert!(deed_schema::verify_consistency(&old_head, &head, &ledger.consistency_proof(3).unwrap()));
+    }
+
+    #[test]
+    fn registry_rejects_unsigned_and_foreign_keys() {
+        use ed25519_dalek::SigningKey;
+        let alice = SigningKey::from_bytes(&[1; 32]);
+        let mallory = SigningKey::from_bytes(&[2; 32]);
+        let mut keys = KeyRegistry::new();
+        keys.register("did:bostrom:alice#key-1", &alice.verifying_key()).unwrap();
+        keys.register("did:bostrom:mallory#key-1", &mallory.verifying_key()).unwrap();
+        let mut ledger = MoralLedger::with_store(MemoryStore::new()).unwrap().with_key_registry(keys);
+        let deed = || DeedEvent::new_math_science_education("did:bostrom:alice".into(), "crate".into(), &SystemClock, &RandomIds);
+
+        assert!(matches!(ledger.append(deed()), Err(ValidationError::Unsigned(_))));
+        let forged = deed().sign("did:bostrom:alice#key-1", &mallory).unwrap();
+        assert!(matches!(ledger.append(forged), Err(ValidationError::BadSignature(_))));
+        let mut borrowed = deed().sign("did:bostrom:alice#key-1", &alice).unwrap();
+        borrowed.signature.as_mut().unwrap().key_id = "did:bostrom:mallory#key-1".into();
+        assert!(matches!(ledger.append(borrowed), Err(ValidationError::UnauthorizedKey { .. })));
+
+        ledger.append(deed().sign("did:bostrom:alice#key-1", &alice).unwrap()).unwrap();
+        ledger.key_registry_mut().unwrap().revoke("did:bostrom:alice#key-1").unwrap();
+        let late = deed().sign("did:bostrom:alice#key-1", &alice).unwrap();
+        assert!(matches!(ledger.append(late), Err(ValidationError::UnauthorizedKey { .. })));
+        assert!(validate_ledger(ledger.store()).unwrap());
+    }
+
+    #[test]
+    fn evidence_store_requires_referenced_blobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let evidence = EvidenceStore::open(dir.path()).unwrap();
+        let receipt = evidence.put(b"NPO receipt #42").unwrap();
+        let mut ledger = MoralLedger::with_store(MemoryStore::new()).unwrap().with_evidence_store(evidence);
+
+        let cited = |uri: String| DeedEvent::new_ecological_sustainability("alice".into(), uri, &SystemClock, &RandomIds);
+        ledger.append(cited(format!("ipfs://{}", receipt))).unwrap();
+        ledger.append(cited("https://example.org/photo.jpg".into())).unwrap();
+        let missing = crate::evidence::Cid::for_bytes(b"never stored");
+        assert!(matches!(
+            ledger.append(cited(format!("ipfs://{}", missing))),
+            Err(ValidationError::Evidence(crate::evidence::EvidenceError::Missing(_)))
+        ));
+        assert_eq!(ledger.len(), 2);
+    }
+
+    #[test]
+    fn grants_are_held_to_the_debt_ceiling_of_verified_deeds() {
+        let dir = tempfile::tempdir().unwrap();
+        let evidence = EvidenceStore::open(dir.path()).unwrap();
+        let receipt = evidence.put(b"NPO receipt #42").unwrap();
+        let mut ledger = MoralLedger::with_store(MemoryStore::new()).unwrap().with_evidence_store(evidence);
+        let cited = |uri: String| DeedEvent::new_ecological_sustainability("alice".into(), uri, &SystemClock, &RandomIds);
+        ledger.append(cited(format!("ipfs://{}", receipt))).unwrap();
+        ledger.append(cited("https://example.org/photo.jpg".into())).unwrap();
+
+        // Only the deed with stored evidence grows the ceiling.
+        let policy = CeilingPolicy::default();
+        let ceiling = ledger.debt_ceiling("alice", &policy).unwrap();
+        assert_eq!(ceiling.steps.iter().map(|s| s.position).collect::<Vec<_>>(), [0]);
+        assert!((ceiling.ceiling - 1.1).abs() < 1e-9);
+
+        let mut distributor = crate::SponsorDistributor::new();
+        let mut grant = |amount| {
+            crate::church::propose_homelessness_grant(&ledger, &mut distributor, "alice", "npo".into(), amount, receipt.to_string())
+        };
+        assert!(grant(100.0).is_ok());
+        assert!(matches!(
+            grant(120.0),
+            Err(ValidationError::Ceiling(token_journal::CeilingError::OverAllowance { requested, .. })) if requested == 120.0
+        ));
+    }
+
+    #[test]
+    fn reopen_resumes_from_signed_checkpoint() {
+        use ed25519_dalek::SigningKey;
+        let node = || NodeKey { key_id: "did:bostrom:node#ckpt".into(), signing_key: SigningKey::from_bytes(&[9; 32]) };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        {
+            let mut ledger = MoralLedger::open_with_node_key(path.clone(), node()).unwrap();
+            ledger.append(DeedEvent::new_math_science_education("alice".into(), "a".into(), &SystemClock, &RandomIds)).unwrap();
+            ledger.append(DeedEvent::new_ecological_sustainability("bob".into(), "ipfs://r".into(), &SystemClock, &RandomIds)).unwrap();
+            let cp = ledger.checkpoint().unwrap().unwrap();
+            assert_eq!(cp.event_count, 2);
+            ledger.append(DeedEvent::new_math_science_education("alice".into(), "b".into(), &SystemClock, &RandomIds)).unwrap();
+        }
+        let ledger = MoralLedger::open_with_node_key(path.clone(), node()).unwrap();
+        assert_eq!(ledger.account("alice").unwrap().good_deeds, 2);
+        assert_eq!(ledger.account("bob").unwrap().church_recommended, 1);
+        assert_eq!(ledger.store().len(), 3);
+        let deeds_at = |height| ledger.account_at("alice", height).unwrap().map(|a| a.deeds);
+        assert_eq!((deeds_at(0), deeds_at(1), deeds_at(2), deeds_at(3)), (None, Some(1), Some(1), Some(2)));
+
+        // A checkpoint signed by another node is ignored, not trusted.
+        let stranger = NodeKey { key_id: "did:bostrom:other#ckpt".into(), signing_key: SigningKey::from_bytes(&[3; 32]) };
+        let ledger = MoralLedger::open_with_node_key(path, stranger).unwrap();
+        assert_eq!(ledger.account("alice").unwrap().deeds, 2);
+    }
+
+    #[test]
+    fn accounts_follow_the_configured_reward_schedule() {
+        use ed25519_dalek::SigningKey;
+        let mut schedule = RewardSchedule::builtin().clone();
+        let rule = schedule.rules.iter_mut().find(|rule| rule.name == "good_deed_recommendation").unwrap();
+        rule.amount.base = 3.0;
+        let node = || NodeKey { key_id: "did:bostrom:node#ckpt".into(), signing_key: SigningKey::from_bytes(&[9; 32]) };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        {
+            let mut ledger = MoralLedger::open_with_node_key(path.clone(), node()).unwrap();
+            ledger.append(DeedEvent::new_ecological_sustainability("bob".into(), "ipfs://r".into(), &SystemClock, &RandomIds)).unwrap();
+            #[allow(deprecated)]
+            let per_good_deed = crate::CHURCH_RECOMMEND_PER_GOOD_DEED;
+            assert_eq!(ledger.account("bob").unwrap().church_recommended, per_good_deed);
+            let mut ledger = ledger.with_reward_schedule(schedule.clone()).unwrap();
+            assert_eq!(ledger.account("bob").unwrap().church_recommended, 3);
+            ledger.append(DeedEvent::new_ecological_sustainability("bob".into(), "ipfs://s".into(), &SystemClock, &RandomIds)).unwrap();
+            assert_eq!(ledger.checkpoint().unwrap().unwrap().reward_schedule.as_ref(), Some(&schedule));
+        }
+        // The checkpoint records the schedule its totals were folded with.
+        let ledger = MoralLedger::open_with_node_key(path, node()).unwrap();
+        assert_eq!(ledger.reward_schedule(), &schedule);
+        assert_eq!(ledger.account("bob").unwrap().church_recommended, 6);
+        let ledger = ledger.with_reward_schedule(RewardSchedule::builtin().clone()).unwrap();
+        assert_eq!(ledger.account("bob").unwrap().church_recommended, 2);
+    }
+
+    #[test]
+    fn redaction_keeps_the_chain_and_signature_valid() {
+        use ed25519_dalek::SigningKey;
+        let alice = SigningKey::from_bytes(&[1; 32]);
+        let mut keys = KeyRegistry::new();
+        keys.register("did:bostrom:alice#key-1", &alice.verifying_key()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        let mut ledger = MoralLedger::open_or_create(path.clone()).unwrap().with_key_registry(keys.clone());
+        let context = serde_json::json!({ "site": "San Tan Valley", "meals_served": 120, "program": "shelter" });
+        let kind = "homelessness_relief".to_string();
+        let deed = DeedEvent::new("did:bostrom:alice".into(), vec![], kind, vec![], context, &SystemClock, &RandomIds)
+            .conceal(&["site", "meals_served"])
+            .unwrap()
+            .sign("did:bostrom:alice#key-1", &alice)
+            .unwrap();
+        ledger.append(deed).unwrap();
+        let crate_deed = DeedEvent::new_math_science_education("did:bostrom:alice".into(), "crate".into(), &SystemClock, &RandomIds);
+        ledger.append(crate_deed.sign("did:bostrom:alice#key-1", &alice).unwrap()).unwrap();
+        let head = ledger.last_hash().to_string();
+
+        assert!(ledger.redact(0, "site").unwrap());
+        assert!(!ledger.redact(0, "site").unwrap());
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("San Tan Valley"));
+        let stored = ledger.store().get(0).unwrap().unwrap();
+        assert_eq!(stored.disclosed_context().unwrap(), serde_json::json!({ "meals_served": 120, "program": "shelter" }));
+        LedgerValidator::validate_signature(&stored, &keys).unwrap();
+        assert_eq!(ledger.store().get(1).unwrap().unwrap().self_hash, head);
+        assert!(validate_ledger(ledger.store()).unwrap());
+        assert_eq!(redactions(ledger.store()).unwrap(), vec![Redaction { seq: 0, event_id: stored.event_id, field: "site".into() }]);
+
+        let report = deed_schema::audit_ledger_file(&path, Some(&"0".repeat(64))).unwrap();
+        assert!(report.is_intact());
+        assert_eq!(report.records[0].redacted, vec!["site".to_string()]);
+        drop(ledger);
+        assert_eq!(MoralLedger::open_or_create(path).unwrap().last_hash(), head);
+    }
+
+    #[test]
+    fn corrections_supersede_without_rewriting_history() {
+        use deed_schema::CorrectedFields;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        let mut ledger = MoralLedger::open_or_create(path.clone()).unwrap();
+        let original = DeedEvent::new("alice".into(), vec![], "tree_planting".into(), vec![], serde_json::json!({}), &SystemClock, &RandomIds);
+        let id = ledger.append(original).unwrap();
+        assert_eq!(ledger.account("alice").unwrap().church_recommended, 0);
+
+        let retype = CorrectedFields { deed_type: Some("ecological_sustainability".into()), ..Default::default() };
+        let foreign = DeedEvent::new_correction("mallory".into(), id, retype.clone(), "".into(), &SystemClock, &RandomIds);
+        assert!(matches!(ledger.append(foreign), Err(ValidationError::InvalidCorrection(_))));
+        ledger.append(DeedEvent::new_correction("alice".into(), id, retype, "wrong deed_type".into(), &SystemClock, &RandomIds)).unwrap();
+        assert_eq!(ledger.account_at("alice", 1).unwrap().unwrap().church_recommended, 0);
+        assert_eq!(ledger.account_at("alice", 2).unwrap().unwrap().church_recommended, 1);
+        assert!(ledger.account_as_of("alice", 0).unwrap().is_none());
+
+        let check = |ledger: &MoralLedger| {
+            let account = ledger.account("alice").unwrap();
+            assert_eq!((account.deeds, account.good_deeds, account.church_recommended), (1, 1, 1));
+            assert_eq!(ledger.store().get(0).unwrap().unwrap().deed_type, "tree_planting");
+            assert_eq!(ledger.effective_deed(0).unwrap().unwrap().deed_type, "ecological_sustainability");
+            let query = DeedQuery { deed_type: Some("ecological_sustainability".into()), limit: 10, ..Default::default() };
+            let page = ledger.query(&query).unwrap();
+            assert_eq!(page.items.len(), 1);
+            assert_eq!(page.items[0].1.event_id, id);
+        };
+        check(&ledger);
+        drop(ledger);
+        std::fs::remove_file(sibling(&path, ".idx.json")).unwrap();
+        check(&MoralLedger::open_or_create(path).unwrap());
+    }
+
+    #[test]
+    fn dispute_outcomes_are_recorded_and_reflected_in_accounts() {
+        use deed_schema::Verdict;
+        use ed25519_dalek::SigningKey;
+        let names = ["alice", "bob", "host", "regulator"];
+        let did = |name: &str| format!("did:bostrom:{}", name);
+        let key = |did: &str| {
+            let n = names.iter().position(|name| did.ends_with(name)).unwrap() as u8;
+            SigningKey::from_bytes(&[n + 1; 32])
+        };
+        let mut keys = KeyRegistry::new();
+        for name in names {
+            keys.register(&format!("{}#key-1", did(name)), &key(name).verifying_key()).unwrap();
+        }
+        let signed = |deed: DeedEvent| {
+            let key_id = format!("{}#key-1", deed.actor_id);
+            let key = key(&deed.actor_id);
+            deed.sign(&key_id, &key).unwrap()
+        };
+        let panel = || {
+            ReviewPanel::new(2)
+                .unwrap()
+                .with_reviewer(&did("host"), "Host")
+                .unwrap()
+                .with_reviewer(&did("regulator"), "Regulator")
+                .unwrap()
+        };
+        let node = || NodeKey { key_id: "did:bostrom:node-1#checkpoint".into(), signing_key: SigningKey::from_bytes(&[9; 32]) };
+        let unsigned = MoralLedger::with_store(MemoryStore::new()).unwrap().with_review_panel(panel());
+        assert!(matches!(unsigned, Err(ValidationError::InvalidDispute(_))));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        let mut ledger = MoralLedger::open_with_node_key(path.clone(), node()).unwrap().with_key_registry(keys.clone());
+        let (clock, ids) = (&SystemClock, &RandomIds);
+        let planted = ledger.append(signed(DeedEvent::new_ecological_sustainability(did("alice"), "ipfs://trees".into(), clock, ids))).unwrap();
+        let evidence = vec!["ipfs://satellite".into()];
+        let dispute = signed(DeedEvent::new_dispute(did("bob"), planted, "simulation of deeds".into(), evidence, clock, ids));
+        assert!(matches!(ledger.append(dispute.clone()), Err(ValidationError::InvalidDispute(_))));
+
+        let mut ledger = ledger.with_review_panel(panel()).unwrap();
+        let own = signed(DeedEvent::new_dispute(did("alice"), planted, "".into(), vec![], clock, ids));
+        assert!(matches!(ledger.append(own), Err(ValidationError::InvalidDispute(_))));
+        let dispute = ledger.append(dispute).unwrap();
+        let freeze =
+            |reviewer: &str, round| DeedEvent::new_dispute_vote(did(reviewer), dispute, round, Verdict::FreezeRewards, "".into(), clock, ids);
+        ledger.append(signed(freeze("host", 0))).unwrap();
+        assert!(matches!(ledger.append(signed(freeze("host", 0))), Err(ValidationError::InvalidDispute(_))));
+        assert!(matches!(ledger.append(freeze("regulator", 0)), Err(ValidationError::Unsigned(_))));
+        assert_eq!(ledger.account(&did("alice")).unwrap().church_recommended, 1);
+        ledger.append(signed(freeze("regulator", 0))).unwrap();
+
+        let check = |ledger: &MoralLedger, frozen: bool| {
+            let alice = ledger.account(&did("alice")).unwrap();
+            assert_eq!((alice.deeds, alice.church_recommended, alice.disputes_upheld), (1, u64::from(!frozen), u64::from(frozen)));
+            assert!(ledger.account(&did("bob")).is_none());
+            let effective = ledger.effective_deed(0).unwrap().unwrap();
+            assert_eq!(effective.ethics_flags.contains(&deed_schema::REWARDS_FROZEN_FLAG.to_string()), frozen);
+            assert!(ledger.store().get(0).unwrap().unwrap().ethics_flags.is_empty());
+        };
+        check(&ledger, true);
+        ledger.checkpoint().unwrap();
+        drop(ledger);
+
+        // The checkpoint carries the dispute state, so reopening replays nothing.
+        let ledger = MoralLedger::open_with_node_key(path, node()).unwrap();
+        assert_eq!(ledger.disputes().unwrap().panel(), &panel());
+        check(&ledger, true);
+        let mut ledger = ledger.with_key_registry(keys).with_review_panel(panel()).unwrap();
+        check(&ledger, true);
+
+        ledger.append(signed(DeedEvent::new_dispute_appeal(did("alice"), dispute, "drone survey".into(), vec![], clock, ids))).unwrap();
+        for reviewer in ["host", "regulator"] {
+            ledger.append(signed(DeedEvent::new_dispute_vote(did(reviewer), dispute, 1, Verdict::Clear, "".into(), clock, ids))).unwrap();
+        }
+        check(&ledger, false);
+        assert_eq!(ledger.disputes().unwrap().case(&dispute.to_string()).unwrap().outcome, Some(Verdict::Clear));
+    }
+
+    #[test]
+    fn fixed_clock_and_seeded_ids_give_a_reproducible_chain() {
+        use deed_schema::{FixedClock, SeededIds};
+        let build = || {
+            let clock = Arc::new(FixedClock::new(1_767_225_600));
+            let mut ledger = MoralLedger::with_store(MemoryStore::new())
+                .unwrap()
+                .with_clock(clock.clone())
+                .with_ids(Arc::new(SeededIds::new(42)));
+            for name in ["a", "b", "c"] {
+                crate::church::log_open_source_contribution(&mut ledger, "alice".into(), name.into()).unwrap();
+                clock.advance(3600);
+            }
+            ledger
+        };
+        let (one, two) = (build(), build());
+        assert_eq!(one.last_hash(), two.last_hash());
+        assert_eq!(one.last_hash(), "49d49bf8139fc196feda6fa5805ebf7283203e80e14740880e518302a601f861");
+        assert_eq!(one.store().get(2).unwrap().unwrap().timestamp, 1_767_225_600 + 7200);
+    }
+
+    #[test]
+    fn new_rows_hash_as_canonical_v1_and_legacy_rows_still_verify() {
+        use deed_schema::{detect_scheme, HashScheme};
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        let mut ledger = MoralLedger::open_or_create(path.clone()).unwrap();
+        ledger.append(DeedEvent::new_ecological_sustainability("a".into(), "ipfs://receipt".into(), &SystemClock, &RandomIds)).unwrap();
+        let line = std::fs::read_to_string(&path).unwrap();
+        let check = detect_scheme(&line).unwrap().unwrap();
+        assert_eq!(check.scheme, HashScheme::CanonicalV1);
+        assert_eq!(check.stored.as_deref(), Some(ledger.last_hash()));
+
+        let vectors: serde_json::Value =
+            serde_json::from_str(include_str!("../../crates/deed-schema/tests/vectors/deed_hashes.json")).unwrap();
+        let legacy = vectors["records"].as_array().unwrap().iter().find(|r| r["source"] == "church_of_fear_ledger").unwrap();
+        let row: DeedEvent = serde_json::from_str(legacy["line"].as_str().unwrap()).unwrap();
+        assert_eq!(row.schema_version, deed_schema::UNVERSIONED);
+        assert!(row.verify_self_hash());
+    }
+
+    #[test]
+    fn timestamps_that_go_backwards_are_refused() {
+        use deed_schema::FixedClock;
+        let deed = |at| DeedEvent::new_ecological_sustainability("alice".into(), "ipfs://r".into(), &FixedClock::new(at), &RandomIds);
+        let mut ledger = MoralLedger::with_store(MemoryStore::new()).unwrap();
+        ledger.append(deed(2000)).unwrap();
+        assert!(matches!(
+            ledger.append(deed(1000)),
+            Err(ValidationError::TimestampBeforePrevious { previous: 2000, timestamp: 1000 })
+        ));
+        let earlier = deed(1000).finalize_hash_chain(ledger.last_hash().to_string());
+        assert!(matches!(ledger.append_sealed(&earlier), Err(ValidationError::TimestampBeforePrevious { .. })));
+        assert!(matches!(ledger.verify_branch(ledger.last_hash(), &[earlier]), Err(ValidationError::TimestampBeforePrevious { .. })));
+        // A branch forking before the later deed is judged against what precedes it.
+        let fork = deed(1000).finalize_hash_chain("0".repeat(64));
+        ledger.verify_branch(&"0".repeat(64), &[fork]).unwrap();
+
+        ledger.append(deed(2000)).unwrap();
+        assert!(ledger.account_as_of("alice", 1999).unwrap().is_none());
+        assert_eq!(ledger.account_as_of("alice", 2000).unwrap().unwrap().deeds, 2);
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let mut ledger = MoralLedger::with_store(MemoryStore::new()).unwrap();
+        ledger.append(DeedEvent::new_math_science_education("a".into(), "crate".into(), &SystemClock, &RandomIds)).unwrap();
+        let mut events = ledger.store().read_all().unwrap();
+        events[0].actor_id = "mallory".into();
+        let mut forged = MemoryStore::new();
+        forged.append(&events[0]).unwrap();
+        assert!(!validate_ledger(&forged).unwrap());
+    }
 }
@@ -11,37 +11,71 @@
 //! Use this ledger to sponsor real NPO projects (homelessness relief, reforestation,
 //! open-source Rust science libraries) by attaching grant proposals as context_json.
 
+mod accounts;
+pub mod checkpoint;
+pub mod credential;
 pub mod deed;
+pub mod evidence;
+pub mod feed;
+pub mod federation;
+pub mod index;
+pub mod keys;
 pub mod ledger;
+pub mod migrate;
 pub mod validator;
 pub mod sponsor;
+pub mod store;
+pub mod sync;
 
+pub use checkpoint::{AccountAggregate, Checkpoint, NodeKey};
+pub use credential::{import_credential, CredentialError, DeedCredential};
 pub use deed::DeedEvent;
-pub use ledger::MoralLedger;
+pub use evidence::{Cid, EvidenceError, EvidenceStore};
+pub use feed::{Cursor, FeedError, FeedEvent, Follower};
+pub use federation::{verify_reference, ReferenceError};
+pub use index::{DeedIndex, DeedPage, DeedQuery};
+pub use keys::{KeyError, KeyRegistry};
+pub use ledger::{redactions, validate_ledger, validate_ledger_file, MoralLedger, Redaction};
+pub use migrate::{migrate_ledger, verify_migration, MigrationError, MigrationLink, MigrationReport};
 pub use validator::{ValidationError, LedgerValidator};
 pub use sponsor::{EcoGrantProposal, SponsorDistributor};
+pub use store::{JsonlStore, ResumePoint, LedgerStore, MemoryStore, SegmentedStore, StoreError};
+#[cfg(feature = "sqlite")]
+pub use store::SqliteStore;
 
-/// Global constant – CHURCH token recommendation per verified good deed (advisory only)
+/// Global constant – CHURCH token recommendation per verified good deed (advisory only).
+/// The amount now comes from the reward schedule; this is the builtin one's.
+#[deprecated(note = "use the reward schedule's `good_deed_recommendation` rule, see `MoralLedger::reward_schedule`")]
 pub const CHURCH_RECOMMEND_PER_GOOD_DEED: u64 = 1;
 
-/// Short-abbreviation functions for CHURCH earning (real-world usable)
+/// Short-abbreviation functions for CHURCH earning (real-world usable).
+/// Deeds are stamped with the ledger's clock and ids.
 pub mod church {
     use super::*;
     
     /// NANO-1: Log a verified ecological cleanup deed → potential +1 CHURCH
-    pub fn log_ecological_cleanup(ledger: &mut MoralLedger, actor_id: String, evidence_url: String) -> Result<uuid::Uuid, ValidationError> {
-        let event = DeedEvent::new_ecological_sustainability(actor_id, evidence_url);
+    pub fn log_ecological_cleanup<S: LedgerStore>(ledger: &mut MoralLedger<S>, actor_id: String, evidence_url: String) -> Result<uuid::Uuid, ValidationError> {
+        let event = DeedEvent::new_ecological_sustainability(actor_id, evidence_url, ledger.clock(), ledger.ids());
         ledger.append(event)
     }
     
     /// TECH-1: Contribute open-source Rust science crate → potential +2 CHURCH
-    pub fn log_open_source_contribution(ledger: &mut MoralLedger, actor_id: String, crate_name: String) -> Result<uuid::Uuid, ValidationError> {
-        let event = DeedEvent::new_math_science_education(actor_id, crate_name);
+    pub fn log_open_source_contribution<S: LedgerStore>(ledger: &mut MoralLedger<S>, actor_id: String, crate_name: String) -> Result<uuid::Uuid, ValidationError> {
+        let event = DeedEvent::new_math_science_education(actor_id, crate_name, ledger.clock(), ledger.ids());
         ledger.append(event)
     }
     
     /// PWR-1: Sponsor homelessness-relief NPO with proof → potential +5 CHURCH
-    pub fn propose_homelessness_grant(distributor: &mut SponsorDistributor, recipient: String, amount_usd_equiv: f64, proof_hash: String) -> EcoGrantProposal {
-        distributor.propose_grant(recipient, amount_usd_equiv, proof_hash)
+    /// Held to `actor_id`'s current debt ceiling on `ledger`.
+    pub fn propose_homelessness_grant<S: LedgerStore>(
+        ledger: &MoralLedger<S>,
+        distributor: &mut SponsorDistributor,
+        actor_id: &str,
+        recipient: String,
+        amount_usd_equiv: f64,
+        proof_hash: String,
+    ) -> Result<EcoGrantProposal, ValidationError> {
+        let ceiling = ledger.debt_ceiling(actor_id, distributor.ceiling_policy())?;
+        Ok(distributor.propose_grant(&ceiling, recipient, amount_usd_equiv, proof_hash)?)
     }
 }
@@ -1,3 +1,5 @@
+use church_of_fear_ledger::{church, MoralLedger};
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
     let mut ledger = MoralLedger::open_or_create("moral_ledger.jsonl".into())?;
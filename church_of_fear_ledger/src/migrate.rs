@@ -0,0 +1,264 @@
+//! Rewrite a JSONL ledger under the current schema.
+//!
+//! `migrate_ledger` checks the old file's chain with the legacy-aware
+//! verifier, upgrades every row with `Upgraders` and re-chains the rows
+//! into a new file. The new chain opens with a `ledger_migration` deed
+//! whose context is a `MigrationLink`: the old head hash and the Merkle
+//! tree head over the old chain, i.e. what `MoralLedger::tree_head`
+//! published before the migration.
+//!
+//! Migration is deterministic: the marker takes its id from the old head
+//! and its timestamp from the old last deed. `verify_migration` therefore
+//! checks a migrated file by redoing the migration and comparing heads.
+
+use crate::deed::DeedEvent;
+use crate::ledger::validate_ledger;
+use crate::store::{JsonlStore, LedgerStore, StoreError};
+use deed_schema::{
+    detect_scheme, record_version, verify_chain_lines, FixedClock, MerkleTree, RandomIds, SchemaError, TreeHead, Upgraders,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use uuid::Uuid;
+
+pub const MIGRATION_DEED_TYPE: &str = "ledger_migration";
+pub const MIGRATION_ACTOR: &str = "ledger";
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("source chain does not verify at line {0}")]
+    BrokenChain(usize),
+    #[error("line {line}: {source}")]
+    Row { line: usize, source: SchemaError },
+    #[error("line {line}: field {field} does not fit the current row shape")]
+    Lossy { line: usize, field: String },
+    #[error("{} already holds a ledger", .0.display())]
+    TargetExists(PathBuf),
+}
+
+/// Context of the `ledger_migration` deed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationLink {
+    pub source_head: String,                // self_hash of the old last deed (genesis if empty)
+    pub source_tree: TreeHead,              // Merkle head over the old chain
+    pub from_version: u32,                  // oldest row version in the old chain
+    pub to_version: u32,
+}
+
+impl MigrationLink {
+    /// The link a migration marker carries; `None` for any other deed.
+    pub fn from_deed(deed: &DeedEvent) -> Option<Self> {
+        if deed.deed_type != MIGRATION_DEED_TYPE {
+            return None;
+        }
+        serde_json::from_value(deed.context_json.clone()).ok()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationReport {
+    pub link: MigrationLink,
+    pub deeds: u64,                         // rows carried over, without the marker
+    pub upgraded: u64,                      // rows that were below `to_version`
+    pub head: String,                       // self_hash of the new last deed
+}
+
+/// Migrate the JSONL ledger at `source` into a new file at `target`,
+/// written atomically. `source` is left untouched.
+pub fn migrate_ledger(source: &Path, target: &Path, upgraders: &Upgraders) -> Result<MigrationReport, MigrationError> {
+    if std::fs::metadata(target).is_ok_and(|m| m.len() > 0) {
+        return Err(MigrationError::TargetExists(target.to_path_buf()));
+    }
+    let (report, chain) = migrated_chain(&std::fs::read_to_string(source)?, upgraders)?;
+    let tmp = target.with_extension("tmp");
+    let mut out = BufWriter::new(File::create(&tmp)?);
+    for deed in &chain {
+        serde_json::to_writer(&mut out, deed)?;
+        out.write_all(b"\n")?;
+    }
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    std::fs::rename(&tmp, target)?;
+    log::info!(
+        "migrated {} deeds ({} upgraded to v{}) from {} to {}",
+        report.deeds,
+        report.upgraded,
+        report.link.to_version,
+        source.display(),
+        target.display()
+    );
+    Ok(report)
+}
+
+/// Whether `target` is exactly what migrating `source` with `upgraders`
+/// produces, and its own chain verifies.
+pub fn verify_migration(source: &Path, target: &Path, upgraders: &Upgraders) -> Result<bool, MigrationError> {
+    let (expected, chain) = migrated_chain(&std::fs::read_to_string(source)?, upgraders)?;
+    let store = JsonlStore::open(target.to_path_buf())?;
+    let marker = store.get(0)?;
+    Ok(validate_ledger(&store)?
+        && store.len() == chain.len() as u64
+        && store.last_hash().as_deref() == Some(expected.head.as_str())
+        && marker.as_ref().and_then(MigrationLink::from_deed) == Some(expected.link))
+}
+
+fn migrated_chain(source: &str, upgraders: &Upgraders) -> Result<(MigrationReport, Vec<DeedEvent>), MigrationError> {
+    let genesis = "0".repeat(64);
+    verify_chain_lines(source.lines(), &genesis).map_err(|idx| MigrationError::BrokenChain(idx + 1))?;
+    let to_version = upgraders.latest();
+
+    let mut tree = MerkleTree::new();
+    let mut source_head = genesis.clone();
+    let mut from_version = to_version;
+    let mut upgraded = 0;
+    let mut rows = Vec::new();
+    let mut last_timestamp = 0;
+    for (idx, line) in source.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let line_no = idx + 1;
+        let row_error = |source| MigrationError::Row { line: line_no, source };
+        let check = detect_scheme(line).map_err(row_error)?.ok_or(MigrationError::BrokenChain(line_no))?;
+        tree.push_self_hash(&check.computed);
+        source_head = check.computed;
+
+        let record: Value = serde_json::from_str(line)?;
+        let version = record_version(&record);
+        from_version = from_version.min(version);
+        if version < to_version {
+            upgraded += 1;
+        }
+        let record = upgraders.upgrade(record, to_version).map_err(row_error)?;
+        let deed: DeedEvent = serde_json::from_value(record.clone()).map_err(|e| row_error(e.into()))?;
+        check_lossless(&record, &deed, line_no)?;
+        last_timestamp = deed.timestamp;
+        rows.push(deed);
+    }
+
+    let link = MigrationLink { source_head: source_head.clone(), source_tree: tree.head(), from_version, to_version };
+    let mut marker = DeedEvent::new(
+        MIGRATION_ACTOR.to_string(),
+        vec![],
+        MIGRATION_DEED_TYPE.to_string(),
+        vec![],
+        serde_json::to_value(&link)?,
+        &FixedClock::new(last_timestamp),
+        &RandomIds,
+    );
+    marker.event_id = marker_id(&source_head);
+
+    let mut chain = Vec::with_capacity(rows.len() + 1);
+    let mut prev = genesis;
+    for mut deed in std::iter::once(marker).chain(rows) {
+        deed.self_hash = String::new();
+        let deed = deed.finalize_hash_chain(prev);
+        prev = deed.self_hash.clone();
+        chain.push(deed);
+    }
+    let report = MigrationReport { link, deeds: chain.len() as u64 - 1, upgraded, head: prev };
+    Ok((report, chain))
+}
+
+/// Every field of the upgraded row must survive in the ledger's row shape.
+fn check_lossless(record: &Value, deed: &DeedEvent, line: usize) -> Result<(), MigrationError> {
+    let kept = serde_json::to_value(deed)?;
+    let empty = |v: &Value| match v {
+        Value::Null => true,
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.is_empty(),
+        _ => false,
+    };
+    for (field, value) in record.as_object().into_iter().flatten() {
+        if kept.get(field) != Some(value) && !empty(value) {
+            return Err(MigrationError::Lossy { line, field: field.clone() });
+        }
+    }
+    Ok(())
+}
+
+/// The id the marker of a migration from `source_head` gets.
+pub fn marker_id(source_head: &str) -> Uuid {
+    let bytes = hex::decode(source_head).unwrap_or_default();
+    uuid::Builder::from_random_bytes(bytes.get(..16).and_then(|b| b.try_into().ok()).unwrap_or_default()).into_uuid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+    use crate::ledger::MoralLedger;
+    use deed_schema::SCHEMA_VERSION;
+
+    #[test]
+    fn migration_upgrades_rows_and_links_back_to_the_old_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let (old, new) = (dir.path().join("old.jsonl"), dir.path().join("new.jsonl"));
+
+        // Two rows written before versioning: one by this crate, one in the
+        // sorted-key shape with an RFC 3339 timestamp.
+        let mut first = DeedEvent::new_ecological_sustainability("alice".into(), "https://example.org".into(), &SystemClock, &RandomIds);
+        first.schema_version = 0;
+        let first = first.finalize_hash_chain("0".repeat(64));
+        let mut second = serde_json::json!({
+            "event_id": Uuid::new_v4(),
+            "timestamp": "2025-01-02T00:00:00Z",
+            "prev_hash": first.self_hash,
+            "self_hash": "",
+            "actor_id": "alice",
+            "deed_type": "ecological_sustainability",
+            "context_json": {},
+        });
+        let second_hash = deed_schema::hash::recompute(&second.to_string(), deed_schema::HashScheme::SortedKeysV0).unwrap();
+        second["self_hash"] = second_hash.clone().into();
+        let lines = format!("{}\n{}\n", serde_json::to_string(&first).unwrap(), second);
+        std::fs::write(&old, &lines).unwrap();
+
+        let upgraders = Upgraders::new();
+        let report = migrate_ledger(&old, &new, &upgraders).unwrap();
+        assert_eq!((report.deeds, report.upgraded), (2, 2));
+        assert_eq!(report.link.source_head, second_hash);
+        assert_eq!((report.link.from_version, report.link.to_version), (0, SCHEMA_VERSION));
+        let mut old_tree = MerkleTree::new();
+        old_tree.push_self_hash(&first.self_hash);
+        old_tree.push_self_hash(&second_hash);
+        assert_eq!(report.link.source_tree, old_tree.head());
+        assert!(verify_migration(&old, &new, &upgraders).unwrap());
+        assert!(matches!(migrate_ledger(&old, &new, &upgraders), Err(MigrationError::TargetExists(_))));
+
+        let ledger = MoralLedger::open_or_create(new.clone()).unwrap();
+        let marker = ledger.store().get(0).unwrap().unwrap();
+        assert_eq!(marker.event_id, marker_id(&second_hash));
+        assert_eq!(MigrationLink::from_deed(&marker), Some(report.link.clone()));
+        let migrated = ledger.store().get(2).unwrap().unwrap();
+        assert_eq!((migrated.schema_version, migrated.timestamp), (SCHEMA_VERSION, 1_735_776_000));
+        assert_eq!(migrated.event_id.to_string(), second["event_id"].as_str().unwrap());
+        assert_eq!(ledger.account("alice").unwrap().deeds, 2);
+        assert!(ledger.account(MIGRATION_ACTOR).is_none());
+
+        std::fs::write(&old, lines.replacen("alice", "mallory", 1)).unwrap();
+        assert!(verify_migration(&old, &new, &upgraders).is_err());
+        let other = dir.path().join("other.jsonl");
+        assert!(matches!(migrate_ledger(&old, &other, &upgraders), Err(MigrationError::BrokenChain(1))));
+    }
+
+    #[test]
+    fn rows_from_a_newer_schema_do_not_parse() {
+        let row = serde_json::to_value(DeedEvent::new_ecological_sustainability("a".into(), "u".into(), &SystemClock, &RandomIds)).unwrap();
+        let mut newer = row.clone();
+        newer["schema_version"] = (SCHEMA_VERSION + 1).into();
+        assert!(serde_json::from_value::<DeedEvent>(newer).is_err());
+        let mut legacy = row;
+        legacy.as_object_mut().unwrap().remove("schema_version");
+        let legacy: DeedEvent = serde_json::from_value(legacy).unwrap();
+        assert_eq!(legacy.schema_version, 0);
+        assert_eq!(deed_schema::DeedEvent::from(&legacy).schema_version, deed_schema::UNVERSIONED);
+        assert!(!serde_json::to_string(&legacy).unwrap().contains("schema_version"));
+    }
+}
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use token_journal::ceiling::gates::GRANT;
+use token_journal::{CeilingError, CeilingPolicy, DebtCeiling};
 
 /// Eco-grant proposal – attach to context_json of a deed to sponsor real NPO
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,17 +14,45 @@ pub struct EcoGrantProposal {
 pub struct SponsorDistributor {
     /// In real deployment this would be a multisig + on-chain treasury
     pub available_pwr: u64,
+    /// Sets the `grant` allowance of a requesting account's debt ceiling
+    ceiling_policy: CeilingPolicy,
+}
+
+impl Default for SponsorDistributor {
+    fn default() -> Self { Self::new() }
 }
 
 impl SponsorDistributor {
-    pub fn new() -> Self { Self { available_pwr: 1_000_000 } }
+    pub fn new() -> Self { Self { available_pwr: 1_000_000, ceiling_policy: CeilingPolicy::default() } }
+
+    /// Hold grants to `policy`'s `grant` gate. Fails with
+    /// `CeilingError::InvalidPolicy` if `policy` is not usable.
+    pub fn with_ceiling_policy(mut self, policy: CeilingPolicy) -> Result<Self, CeilingError> {
+        policy.check()?;
+        self.ceiling_policy = policy;
+        Ok(self)
+    }
+
+    pub fn ceiling_policy(&self) -> &CeilingPolicy {
+        &self.ceiling_policy
+    }
 
-    pub fn propose_grant(&mut self, recipient: String, amount_usd_equiv: f64, proof_hash: String) -> EcoGrantProposal {
-        EcoGrantProposal {
+    /// Propose a grant for an account whose current debt ceiling is
+    /// `ceiling` (see `MoralLedger::debt_ceiling`). Refused with
+    /// `CeilingError::OverAllowance` if the amount is over its `grant` allowance.
+    pub fn propose_grant(
+        &mut self,
+        ceiling: &DebtCeiling,
+        recipient: String,
+        amount_usd_equiv: f64,
+        proof_hash: String,
+    ) -> Result<EcoGrantProposal, CeilingError> {
+        ceiling.check_request(&self.ceiling_policy, GRANT, amount_usd_equiv)?;
+        Ok(EcoGrantProposal {
             recipient,
             amount_usd_equiv,
             proof_hash,
             purpose: "ecological_sustainability".to_string(),
-        }
+        })
     }
 }
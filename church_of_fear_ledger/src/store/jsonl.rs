@@ -0,0 +1,333 @@
+use super::{LedgerStore, StoreError};
+use crate::deed::DeedEvent;
+use deed_schema::{append_synced, complete_len, recover_torn_tail, sync_parent_dir, torn_path};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Durable `.jsonl` store – one sealed DeedEvent per line.
+///
+/// Every append is a single `write_all` of `json + '\n'` followed by
+/// `sync_data`, so a record is either fully on disk or is the torn tail of
+/// the file; an append that fails is cut off before it returns. On open,
+/// bytes after the last newline are an append that never returned `Ok`:
+/// they are added to `<file>.torn` and the file is truncated back to the
+/// last complete record. See `deed_schema::durable`.
+///
+/// `open_from` skips parsing the records covered by a trusted `ResumePoint`
+/// (usually a signed checkpoint); only their line offsets are recovered.
+#[derive(Debug)]
+pub struct JsonlStore {
+    path: PathBuf,
+    file: File,
+    last_hash: Option<String>,
+    offsets: Vec<u64>,          // byte offset of every record, for random access
+    end: u64,
+    recovered_tail: Option<Vec<u8>>,
+}
+
+/// A known-good prefix of the file: `event_count` records ending at
+/// `byte_offset`, the last of which has `self_hash == chain_head`.
+#[derive(Debug, Clone)]
+pub struct ResumePoint {
+    pub event_count: u64,
+    pub byte_offset: u64,
+    pub chain_head: String,
+}
+
+impl JsonlStore {
+    pub fn open(path: PathBuf) -> Result<Self, StoreError> {
+        Self::open_from(path, None)
+    }
+
+    /// Open, parsing only the records after `resume`. Falls back to a full
+    /// parse when the file does not match the resume point.
+    pub fn open_from(path: PathBuf, resume: Option<&ResumePoint>) -> Result<Self, StoreError> {
+        let created = !path.exists();
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(&path)?;
+        if created {
+            sync_parent_dir(&path)?;
+        }
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let complete = complete_len(&bytes);
+        let recovered_tail = recover_torn_tail(&path, &file, &bytes)?.map(<[u8]>::to_vec);
+        if let Some(tail) = &recovered_tail {
+            log::warn!(
+                "recovered torn tail of {} ({} bytes moved to {})",
+                path.display(),
+                tail.len(),
+                torn_path(&path).display()
+            );
+        }
+
+        let mut last_hash = None;
+        let mut offsets = Vec::new();
+        let mut resumed_at = 0;
+        if let Some(resume) = resume {
+            match resume_prefix(&bytes[..complete], resume)? {
+                Some(prefix) => {
+                    offsets = prefix;
+                    last_hash = Some(resume.chain_head.clone());
+                    resumed_at = resume.byte_offset as usize;
+                }
+                None => log::warn!("{} does not match its resume point; parsing every record", path.display()),
+            }
+        }
+        let first_line = bytes[..resumed_at].iter().filter(|b| **b == b'\n').count();
+        let mut offset = resumed_at as u64;
+        for (idx, line) in bytes[resumed_at..complete].split(|b| *b == b'\n').enumerate() {
+            let line_no = first_line + idx + 1;
+            let start = offset;
+            offset += line.len() as u64 + 1;
+            let line = std::str::from_utf8(line)
+                .map_err(|e| StoreError::Corrupt { line: line_no, reason: e.to_string() })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = parse_line(line, line_no)?;
+            last_hash = Some(event.self_hash.clone());
+            offsets.push(start);
+        }
+
+        Ok(Self { path, file, last_hash, offsets, end: complete as u64, recovered_tail })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Bytes dropped from an unfinished append when the file was opened.
+    pub fn recovered_tail(&self) -> Option<&[u8]> {
+        self.recovered_tail.as_deref()
+    }
+}
+
+impl LedgerStore for JsonlStore {
+    fn append(&mut self, event: &DeedEvent) -> Result<(), StoreError> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        append_synced(&mut self.file, &line)?;
+        self.offsets.push(self.end);
+        self.end += line.len() as u64;
+        self.last_hash = Some(event.self_hash.clone());
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: u64) -> Result<(), StoreError> {
+        let Some(&cut) = self.offsets.get(len as usize) else {
+            return Ok(());
+        };
+        self.file.set_len(cut)?;
+        self.file.sync_all()?;
+        self.offsets.truncate(len as usize);
+        self.end = cut;
+        self.last_hash = match len {
+            0 => None,
+            n => self.get(n - 1)?.map(|e| e.self_hash.clone()),
+        };
+        Ok(())
+    }
+
+    /// The file is rewritten to `<file>.rewrite` and renamed over the
+    /// original, so a crash leaves either the old or the new ledger.
+    fn rewrite(&mut self, seq: u64, event: &DeedEvent) -> Result<(), StoreError> {
+        let Some(&start) = self.offsets.get(seq as usize) else {
+            return Ok(());
+        };
+        let bytes = std::fs::read(&self.path)?;
+        let end = self.offsets.get(seq as usize + 1).map_or(self.end, |&o| o) as usize;
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+
+        let mut tmp_name = self.path.as_os_str().to_owned();
+        tmp_name.push(".rewrite");
+        let tmp = PathBuf::from(tmp_name);
+        {
+            let mut out = File::create(&tmp)?;
+            out.write_all(&bytes[..start as usize])?;
+            out.write_all(&line)?;
+            out.write_all(&bytes[end..self.end as usize])?;
+            out.sync_all()?;
+        }
+        std::fs::rename(&tmp, &self.path)?;
+        sync_parent_dir(&self.path)?;
+        self.file = OpenOptions::new().read(true).append(true).open(&self.path)?;
+
+        let delta = line.len() as i64 - (end as i64 - start as i64);
+        for offset in &mut self.offsets[seq as usize + 1..] {
+            *offset = (*offset as i64 + delta) as u64;
+        }
+        self.end = (self.end as i64 + delta) as u64;
+        Ok(())
+    }
+
+    fn read_all(&self) -> Result<Vec<DeedEvent>, StoreError> {
+        let reader = BufReader::new(File::open(&self.path)?);
+        let mut events = Vec::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            events.push(parse_line(&line, idx + 1)?);
+        }
+        Ok(events)
+    }
+
+    fn get(&self, seq: u64) -> Result<Option<DeedEvent>, StoreError> {
+        let Some(&offset) = self.offsets.get(seq as usize) else {
+            return Ok(None);
+        };
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        parse_line(&line, seq as usize + 1).map(Some)
+    }
+
+    fn last_hash(&self) -> Option<String> {
+        self.last_hash.clone()
+    }
+
+    fn len(&self) -> u64 {
+        self.offsets.len() as u64
+    }
+
+    fn read_range(&self, from: u64, to: u64) -> Result<Vec<DeedEvent>, StoreError> {
+        let Some(&offset) = self.offsets.get(from as usize) else {
+            return Ok(vec![]);
+        };
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut events = Vec::new();
+        for (idx, line) in reader.lines().enumerate() {
+            if events.len() as u64 >= to.saturating_sub(from) {
+                break;
+            }
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            events.push(parse_line(&line, from as usize + idx + 1)?);
+        }
+        Ok(events)
+    }
+
+    fn resume_offset(&self) -> Option<u64> {
+        Some(self.end)
+    }
+}
+
+/// Record offsets of the prefix described by `resume`, found by scanning for
+/// newlines; only the last record is parsed, to check the chain head.
+fn resume_prefix(bytes: &[u8], resume: &ResumePoint) -> Result<Option<Vec<u64>>, StoreError> {
+    let end = resume.byte_offset as usize;
+    if end > bytes.len() || (end > 0 && bytes[end - 1] != b'\n') {
+        return Ok(None);
+    }
+    let mut offsets = Vec::with_capacity(resume.event_count as usize);
+    let mut start = 0;
+    for line in bytes[..end].split_inclusive(|b| *b == b'\n') {
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            offsets.push(start as u64);
+        }
+        start += line.len();
+    }
+    if offsets.len() as u64 != resume.event_count {
+        return Ok(None);
+    }
+    let Some(&last) = offsets.last() else {
+        return Ok(Some(offsets));
+    };
+    let line = String::from_utf8_lossy(&bytes[last as usize..end]);
+    let event = parse_line(&line, offsets.len())?;
+    Ok((event.self_hash == resume.chain_head).then_some(offsets))
+}
+
+fn parse_line(line: &str, line_no: usize) -> Result<DeedEvent, StoreError> {
+    serde_json::from_str(line).map_err(|e| StoreError::Corrupt { line: line_no, reason: e.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+
+    fn sealed(prev: &str) -> DeedEvent {
+        DeedEvent::new_math_science_education("actor".into(), "crate".into(), &SystemClock, &RandomIds)
+            .finalize_hash_chain(prev.to_string())
+    }
+
+    #[test]
+    fn torn_tail_is_truncated_and_kept_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        let first = sealed(&"0".repeat(64));
+        {
+            let mut store = JsonlStore::open(path.clone()).unwrap();
+            store.append(&first).unwrap();
+        }
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"event_id":"3d9f1e27-5b6a"#).unwrap();
+
+        let store = JsonlStore::open(path.clone()).unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.last_hash(), Some(first.self_hash.clone()));
+        assert!(store.recovered_tail().is_some());
+        assert!(std::fs::read(&path).unwrap().ends_with(b"\n"));
+        drop(store);
+
+        // A second recovery keeps the first one's bytes.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"event_id":"9a"#).unwrap();
+        JsonlStore::open(path.clone()).unwrap();
+        assert_eq!(std::fs::read(torn_path(&path)).unwrap(), b"{\"event_id\":\"3d9f1e27-5b6a\n{\"event_id\":\"9a\n");
+    }
+
+    #[test]
+    fn resume_point_skips_prefix_and_falls_back_on_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        let first = sealed(&"0".repeat(64));
+        let second = sealed(&first.self_hash);
+        let resume = {
+            let mut store = JsonlStore::open(path.clone()).unwrap();
+            store.append(&first).unwrap();
+            let resume = ResumePoint {
+                event_count: 1,
+                byte_offset: store.resume_offset().unwrap(),
+                chain_head: first.self_hash.clone(),
+            };
+            store.append(&second).unwrap();
+            resume
+        };
+        let store = JsonlStore::open_from(path.clone(), Some(&resume)).unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(0).unwrap().unwrap().event_id, first.event_id);
+        assert_eq!(store.read_from(1).unwrap()[0].event_id, second.event_id);
+
+        let wrong = ResumePoint { chain_head: "f".repeat(64), ..resume };
+        let store = JsonlStore::open_from(path, Some(&wrong)).unwrap();
+        assert_eq!(store.last_hash(), Some(second.self_hash.clone()));
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn reopen_sees_every_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.jsonl");
+        let first = sealed(&"0".repeat(64));
+        let second = sealed(&first.self_hash);
+        {
+            let mut store = JsonlStore::open(path.clone()).unwrap();
+            store.append(&first).unwrap();
+            store.append(&second).unwrap();
+        }
+        let store = JsonlStore::open(path).unwrap();
+        assert_eq!(store.read_all().unwrap().len(), 2);
+        assert_eq!(store.last_hash(), Some(second.self_hash.clone()));
+        assert!(store.recovered_tail().is_none());
+    }
+}
@@ -0,0 +1,49 @@
+use super::{LedgerStore, StoreError};
+use crate::deed::DeedEvent;
+
+/// Volatile store for tests and simulations.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    events: Vec<DeedEvent>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LedgerStore for MemoryStore {
+    fn append(&mut self, event: &DeedEvent) -> Result<(), StoreError> {
+        self.events.push(event.clone());
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: u64) -> Result<(), StoreError> {
+        self.events.truncate(len as usize);
+        Ok(())
+    }
+
+    fn rewrite(&mut self, seq: u64, event: &DeedEvent) -> Result<(), StoreError> {
+        if let Some(slot) = self.events.get_mut(seq as usize) {
+            *slot = event.clone();
+        }
+        Ok(())
+    }
+
+    fn read_all(&self) -> Result<Vec<DeedEvent>, StoreError> {
+        Ok(self.events.clone())
+    }
+
+    fn get(&self, seq: u64) -> Result<Option<DeedEvent>, StoreError> {
+        Ok(self.events.get(seq as usize).cloned())
+    }
+
+    fn last_hash(&self) -> Option<String> {
+        self.events.last().map(|e| e.self_hash.clone())
+    }
+
+    fn len(&self) -> u64 {
+        self.events.len() as u64
+    }
+}
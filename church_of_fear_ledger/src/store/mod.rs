@@ -0,0 +1,87 @@
+//! Storage backends for the moral ledger.
+//!
+//! `MoralLedger` only needs ordered append + full read; everything about
+//! durability and recovery lives behind `LedgerStore`.
+
+pub mod jsonl;
+pub mod memory;
+pub mod segmented;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+pub use jsonl::{JsonlStore, ResumePoint};
+pub use memory::MemoryStore;
+pub use segmented::{SegmentManifest, SegmentedStore, DEFAULT_DEEDS_PER_SEGMENT};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+use crate::deed::DeedEvent;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum StoreError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("corrupt record at line {line}: {reason}")]
+    Corrupt { line: usize, reason: String },
+    #[error("schema error: {0}")]
+    Schema(#[from] deed_schema::SchemaError),
+    #[cfg(feature = "sqlite")]
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+/// Append-only, ordered deed storage.
+pub trait LedgerStore {
+    /// Persist one sealed event. When this returns `Ok` the event must
+    /// survive a crash.
+    fn append(&mut self, event: &DeedEvent) -> Result<(), StoreError>;
+
+    /// Every stored event, in chain order.
+    fn read_all(&self) -> Result<Vec<DeedEvent>, StoreError>;
+
+    /// Random access by 0-based position in the chain.
+    fn get(&self, seq: u64) -> Result<Option<DeedEvent>, StoreError>;
+
+    /// `self_hash` of the newest event, `None` for an empty store.
+    fn last_hash(&self) -> Option<String>;
+
+    /// Drop every event from position `len` on. Only fork resolution calls
+    /// this, after preserving the dropped branch elsewhere.
+    fn truncate(&mut self, len: u64) -> Result<(), StoreError>;
+
+    /// Replace the stored row at `seq` with `event`, which must carry the
+    /// same `self_hash`. Only redaction calls this; the old bytes must not
+    /// remain readable through the store afterwards.
+    fn rewrite(&mut self, seq: u64, event: &DeedEvent) -> Result<(), StoreError>;
+
+    /// Events from position `seq` to the end, in chain order.
+    fn read_from(&self, seq: u64) -> Result<Vec<DeedEvent>, StoreError> {
+        self.read_range(seq, self.len())
+    }
+
+    /// Events at positions `from..to`, in chain order; nothing past `to` is
+    /// read.
+    fn read_range(&self, from: u64, to: u64) -> Result<Vec<DeedEvent>, StoreError> {
+        let mut events = Vec::new();
+        for s in from..to.min(self.len()) {
+            events.extend(self.get(s)?);
+        }
+        Ok(events)
+    }
+
+    /// Backend-specific position a checkpoint can resume from (the JSONL
+    /// byte offset); `None` when the backend opens cheaply anyway.
+    fn resume_offset(&self) -> Option<u64> {
+        None
+    }
+
+    /// Number of stored events.
+    fn len(&self) -> u64;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
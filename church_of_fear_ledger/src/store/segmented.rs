@@ -0,0 +1,611 @@
+//! Segmented JSONL store: the chain split into epoch-sized files.
+//!
+//! `<dir>/segment-NNNNNN.jsonl` holds up to `deeds_per_segment` deeds after
+//! a one-line `SegmentHeader` naming the previous segment's final hash.
+//! `<dir>/manifest.json` lists every segment with its first position, its
+//! boundary hashes and – once closed – the SHA-256 of its file. Only the
+//! open (last) segment is parsed on open.
+//!
+//! Closed segments can be archived to `segment-NNNNNN.jsonl.gz`. The
+//! manifest alone shows how segments link up and lets each file be checked
+//! against its digest without decompressing it; a segment's own chain is
+//! verified by decompressing just that segment.
+
+use super::{LedgerStore, StoreError};
+use crate::deed::DeedEvent;
+use deed_schema::{append_synced, complete_len, recover_torn_tail};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+pub const DEFAULT_DEEDS_PER_SEGMENT: u64 = 65_536;
+const MANIFEST: &str = "manifest.json";
+
+/// First line of every segment file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentHeader {
+    pub segment: u64,
+    pub first_seq: u64,
+    pub prev_hash: String,                  // final hash of the previous segment (genesis for the first)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentState {
+    Open,
+    Closed,
+    Archived,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentEntry {
+    pub segment: u64,
+    pub file: String,                       // relative to the store directory
+    pub state: SegmentState,
+    pub first_seq: u64,
+    pub prev_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deeds: Option<u64>,                 // set once closed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_hash: Option<String>,          // set once closed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,             // digest of `file` as stored, set once closed
+}
+
+impl SegmentEntry {
+    fn header(&self) -> SegmentHeader {
+        SegmentHeader { segment: self.segment, first_seq: self.first_seq, prev_hash: self.prev_hash.clone() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentManifest {
+    pub deeds_per_segment: u64,
+    pub segments: Vec<SegmentEntry>,
+}
+
+#[derive(Debug)]
+pub struct SegmentedStore {
+    dir: PathBuf,
+    manifest: SegmentManifest,
+    file: File,                 // the open segment, append mode
+    offsets: Vec<u64>,          // byte offset of every deed in the open segment
+    end: u64,
+    last_hash: Option<String>,
+    decoded: Mutex<Option<(u64, Vec<DeedEvent>)>>, // last closed segment `get` read
+}
+
+fn segment_file(segment: u64) -> String {
+    format!("segment-{:06}.jsonl", segment)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn corrupt(segment: u64, reason: impl Into<String>) -> StoreError {
+    StoreError::Corrupt { line: 0, reason: format!("segment {}: {}", segment, reason.into()) }
+}
+
+impl SegmentedStore {
+    /// Open the store in `dir`, creating it if needed. `deeds_per_segment`
+    /// only applies to a new store; an existing one keeps its manifest's.
+    pub fn open(dir: PathBuf, deeds_per_segment: u64) -> Result<Self, StoreError> {
+        std::fs::create_dir_all(&dir)?;
+        let manifest_path = dir.join(MANIFEST);
+        let manifest = if manifest_path.exists() {
+            serde_json::from_slice(&std::fs::read(&manifest_path)?)?
+        } else {
+            let manifest = SegmentManifest {
+                deeds_per_segment: deeds_per_segment.max(1),
+                segments: vec![SegmentEntry {
+                    segment: 0,
+                    file: segment_file(0),
+                    state: SegmentState::Open,
+                    first_seq: 0,
+                    prev_hash: "0".repeat(64),
+                    deeds: None,
+                    last_hash: None,
+                    sha256: None,
+                }],
+            };
+            save_manifest(&dir, &manifest)?;
+            manifest
+        };
+        match manifest.segments.last() {
+            Some(entry) if entry.state == SegmentState::Open => {}
+            _ => return Err(corrupt(0, "manifest has no open segment")),
+        }
+        let open = open_segment(&dir, manifest.segments.last().expect("checked above"))?;
+        Ok(Self {
+            dir,
+            manifest,
+            file: open.file,
+            offsets: open.offsets,
+            end: open.end,
+            last_hash: open.last_hash,
+            decoded: Mutex::new(None),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn manifest(&self) -> &SegmentManifest {
+        &self.manifest
+    }
+
+    /// Reopen the last manifest entry for appending.
+    fn open_last_segment(&mut self) -> Result<(), StoreError> {
+        let open = open_segment(&self.dir, self.manifest.segments.last().expect("manifest always has an open segment"))?;
+        (self.file, self.offsets, self.end, self.last_hash) = (open.file, open.offsets, open.end, open.last_hash);
+        Ok(())
+    }
+
+    /// Close the full open segment and start the next one. The manifest is
+    /// saved before the new file is created, so an interrupted rotation is
+    /// finished by the next open.
+    fn rotate(&mut self) -> Result<(), StoreError> {
+        self.file.sync_all()?;
+        let count = self.offsets.len() as u64;
+        let last_hash = self.last_hash.clone().ok_or_else(|| corrupt(0, "cannot close an empty segment"))?;
+        let open = self.manifest.segments.last_mut().expect("manifest always has an open segment");
+        open.state = SegmentState::Closed;
+        open.deeds = Some(count);
+        open.last_hash = Some(last_hash.clone());
+        open.sha256 = Some(file_sha256(&self.dir.join(&open.file))?);
+        let next = SegmentEntry {
+            segment: open.segment + 1,
+            file: segment_file(open.segment + 1),
+            state: SegmentState::Open,
+            first_seq: open.first_seq + count,
+            prev_hash: last_hash,
+            deeds: None,
+            last_hash: None,
+            sha256: None,
+        };
+        log::info!("closed ledger segment {} ({} deeds)", open.segment, count);
+        self.manifest.segments.push(next);
+        save_manifest(&self.dir, &self.manifest)?;
+        self.open_last_segment()
+    }
+
+    /// Compress every closed segment. Returns the segment numbers archived.
+    pub fn archive_closed(&mut self) -> Result<Vec<u64>, StoreError> {
+        let mut archived = Vec::new();
+        for idx in 0..self.manifest.segments.len() {
+            let entry = self.manifest.segments[idx].clone();
+            if entry.state != SegmentState::Closed {
+                continue;
+            }
+            let path = self.dir.join(&entry.file);
+            if Some(file_sha256(&path)?) != entry.sha256 {
+                return Err(corrupt(entry.segment, "file does not match its manifest digest; not archiving"));
+            }
+            let gz_name = format!("{}.gz", entry.file);
+            write_replacing(&self.dir.join(&gz_name), &gzip(&std::fs::read(&path)?)?)?;
+            let slot = &mut self.manifest.segments[idx];
+            slot.state = SegmentState::Archived;
+            slot.file = gz_name;
+            slot.sha256 = Some(file_sha256(&self.dir.join(&slot.file))?);
+            save_manifest(&self.dir, &self.manifest)?;
+            std::fs::remove_file(&path)?;
+            log::info!("archived ledger segment {}", entry.segment);
+            archived.push(entry.segment);
+        }
+        Ok(archived)
+    }
+
+    /// Check the manifest without reading any deed: segments are numbered
+    /// and positioned contiguously, each one starts from the previous
+    /// one's final hash, and every closed file matches its digest.
+    pub fn verify_manifest(&self) -> Result<bool, StoreError> {
+        let mut expected_prev = "0".repeat(64);
+        let mut expected_seq = 0;
+        for (idx, entry) in self.manifest.segments.iter().enumerate() {
+            if entry.segment != idx as u64 || entry.first_seq != expected_seq || entry.prev_hash != expected_prev {
+                return Ok(false);
+            }
+            if entry.state == SegmentState::Open {
+                return Ok(idx + 1 == self.manifest.segments.len());
+            }
+            let (Some(deeds), Some(last_hash)) = (entry.deeds, &entry.last_hash) else {
+                return Ok(false);
+            };
+            if Some(file_sha256(&self.dir.join(&entry.file))?) != entry.sha256 {
+                return Ok(false);
+            }
+            expected_seq += deeds;
+            expected_prev = last_hash.clone();
+        }
+        Ok(false)
+    }
+
+    /// Fully verify one segment on its own: header, every deed's hash and
+    /// link, and (once closed) its deed count and final hash.
+    pub fn verify_segment(&self, segment: u64) -> Result<bool, StoreError> {
+        let Some(entry) = self.manifest.segments.get(segment as usize) else {
+            return Ok(false);
+        };
+        let (header, deeds) = match parse_segment(&self.segment_bytes(entry)?, entry) {
+            Ok(parsed) => parsed,
+            Err(StoreError::Corrupt { .. }) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let mut prev = header.prev_hash.clone();
+        for deed in &deeds {
+            if deed.prev_hash != prev || !deed.verify_self_hash() {
+                return Ok(false);
+            }
+            prev = deed.self_hash.clone();
+        }
+        let closed_ok = match entry.state {
+            SegmentState::Open => true,
+            _ => entry.deeds == Some(deeds.len() as u64) && entry.last_hash.as_ref() == Some(&prev),
+        };
+        Ok(header == entry.header() && closed_ok)
+    }
+
+    fn segment_bytes(&self, entry: &SegmentEntry) -> Result<Vec<u8>, StoreError> {
+        let raw = std::fs::read(self.dir.join(&entry.file))?;
+        if entry.state != SegmentState::Archived {
+            return Ok(raw);
+        }
+        let mut bytes = Vec::new();
+        GzDecoder::new(raw.as_slice()).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn read_segment(&self, entry: &SegmentEntry) -> Result<Vec<DeedEvent>, StoreError> {
+        Ok(parse_segment(&self.segment_bytes(entry)?, entry)?.1)
+    }
+
+    fn decoded(&self) -> MutexGuard<'_, Option<(u64, Vec<DeedEvent>)>> {
+        self.decoded.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Index of the segment holding position `seq`.
+    fn segment_of(&self, seq: u64) -> usize {
+        self.manifest.segments.partition_point(|e| e.first_seq <= seq).saturating_sub(1)
+    }
+
+    fn open_first_seq(&self) -> u64 {
+        self.manifest.segments.last().map_or(0, |e| e.first_seq)
+    }
+}
+
+impl LedgerStore for SegmentedStore {
+    fn append(&mut self, event: &DeedEvent) -> Result<(), StoreError> {
+        if self.offsets.len() as u64 >= self.manifest.deeds_per_segment {
+            self.rotate()?;
+        }
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        append_synced(&mut self.file, &line)?;
+        self.offsets.push(self.end);
+        self.end += line.len() as u64;
+        self.last_hash = Some(event.self_hash.clone());
+        Ok(())
+    }
+
+    /// Truncating into a closed or archived segment reopens it: later
+    /// segment files are removed and an archive is decompressed again.
+    fn truncate(&mut self, len: u64) -> Result<(), StoreError> {
+        if len >= self.len() {
+            return Ok(());
+        }
+        *self.decoded() = None;
+        let keep = self.segment_of(len);
+        let dropped: Vec<SegmentEntry> = self.manifest.segments.drain(keep + 1..).collect();
+        let reopened = self.manifest.segments[keep].clone();
+        if reopened.state != SegmentState::Open {
+            if reopened.state == SegmentState::Archived {
+                let bytes = self.segment_bytes(&reopened)?;
+                write_replacing(&self.dir.join(segment_file(reopened.segment)), &bytes)?;
+            }
+            let slot = &mut self.manifest.segments[keep];
+            slot.file = segment_file(slot.segment);
+            slot.state = SegmentState::Open;
+            slot.deeds = None;
+            slot.last_hash = None;
+            slot.sha256 = None;
+        }
+        save_manifest(&self.dir, &self.manifest)?;
+        for entry in dropped.iter().chain((reopened.state == SegmentState::Archived).then_some(&reopened)) {
+            std::fs::remove_file(self.dir.join(&entry.file))?;
+        }
+
+        self.open_last_segment()?;
+        let cut = self.offsets[(len - reopened.first_seq) as usize];
+        self.file.set_len(cut)?;
+        self.file.sync_all()?;
+        self.open_last_segment()
+    }
+
+    fn rewrite(&mut self, seq: u64, event: &DeedEvent) -> Result<(), StoreError> {
+        if seq >= self.len() {
+            return Ok(());
+        }
+        *self.decoded() = None;
+        let idx = self.segment_of(seq);
+        let entry = self.manifest.segments[idx].clone();
+        let bytes = self.segment_bytes(&entry)?;
+        let target = (seq - entry.first_seq) as usize + 1; // line 0 is the header
+        let mut out = Vec::with_capacity(bytes.len());
+        for (n, line) in bytes.split_inclusive(|b| *b == b'\n').enumerate() {
+            if n == target {
+                out.extend(serde_json::to_vec(event)?);
+                out.push(b'\n');
+            } else {
+                out.extend_from_slice(line);
+            }
+        }
+        let path = self.dir.join(&entry.file);
+        match entry.state {
+            SegmentState::Archived => write_replacing(&path, &gzip(&out)?)?,
+            _ => write_replacing(&path, &out)?,
+        }
+        if entry.state == SegmentState::Open {
+            return self.open_last_segment();
+        }
+        self.manifest.segments[idx].sha256 = Some(file_sha256(&path)?);
+        save_manifest(&self.dir, &self.manifest)
+    }
+
+    fn read_all(&self) -> Result<Vec<DeedEvent>, StoreError> {
+        self.read_from(0)
+    }
+
+    fn read_range(&self, from: u64, to: u64) -> Result<Vec<DeedEvent>, StoreError> {
+        let mut events = Vec::new();
+        for entry in self.manifest.segments[self.segment_of(from)..].iter().take_while(|e| e.first_seq < to) {
+            let skip = from.saturating_sub(entry.first_seq) as usize;
+            let take = ((to - entry.first_seq) as usize).saturating_sub(skip);
+            events.extend(self.read_segment(entry)?.into_iter().skip(skip).take(take));
+        }
+        Ok(events)
+    }
+
+    /// Open-segment deeds are read by seeking to their offset; a closed
+    /// segment is decoded once and kept until another one is read.
+    fn get(&self, seq: u64) -> Result<Option<DeedEvent>, StoreError> {
+        if seq >= self.len() {
+            return Ok(None);
+        }
+        let entry = &self.manifest.segments[self.segment_of(seq)];
+        let idx = (seq - entry.first_seq) as usize;
+        if entry.state == SegmentState::Open {
+            let mut reader = BufReader::new(File::open(self.dir.join(&entry.file))?);
+            reader.seek(SeekFrom::Start(self.offsets[idx]))?;
+            let mut line = Vec::new();
+            reader.read_until(b'\n', &mut line)?;
+            return serde_json::from_slice(&line)
+                .map(Some)
+                .map_err(|e| StoreError::Corrupt { line: seq as usize + 1, reason: e.to_string() });
+        }
+        let mut decoded = self.decoded();
+        if decoded.as_ref().is_none_or(|(segment, _)| *segment != entry.segment) {
+            *decoded = Some((entry.segment, self.read_segment(entry)?));
+        }
+        Ok(decoded.as_ref().and_then(|(_, deeds)| deeds.get(idx).cloned()))
+    }
+
+    fn last_hash(&self) -> Option<String> {
+        self.last_hash.clone()
+    }
+
+    fn len(&self) -> u64 {
+        self.open_first_seq() + self.offsets.len() as u64
+    }
+}
+
+struct OpenSegment {
+    file: File,
+    offsets: Vec<u64>,
+    end: u64,
+    last_hash: Option<String>,
+}
+
+/// Open `entry`'s file for appending, creating it with its header if a
+/// rotation was interrupted before the file existed. A torn tail is added
+/// to `<file>.torn`, as in `JsonlStore`.
+fn open_segment(dir: &Path, entry: &SegmentEntry) -> Result<OpenSegment, StoreError> {
+    let path = dir.join(&entry.file);
+    if !path.exists() {
+        let mut header = serde_json::to_vec(&entry.header())?;
+        header.push(b'\n');
+        let mut file = File::create(&path)?;
+        file.write_all(&header)?;
+        file.sync_all()?;
+        sync_dir(dir)?;
+    }
+    let mut file = OpenOptions::new().read(true).append(true).open(&path)?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let complete = complete_len(&bytes);
+    if let Some(tail) = recover_torn_tail(&path, &file, &bytes)? {
+        log::warn!("recovered torn tail of {} ({} bytes)", path.display(), tail.len());
+    }
+
+    let (header, deeds) = parse_segment(&bytes[..complete], entry)?;
+    if header != entry.header() {
+        return Err(corrupt(entry.segment, "header does not match the manifest"));
+    }
+    let mut offsets = Vec::with_capacity(deeds.len());
+    let mut offset = 0u64;
+    for (n, line) in bytes[..complete].split_inclusive(|b| *b == b'\n').enumerate() {
+        if n > 0 && !line.iter().all(u8::is_ascii_whitespace) {
+            offsets.push(offset);
+        }
+        offset += line.len() as u64;
+    }
+    let last_hash = match deeds.last() {
+        Some(deed) => Some(deed.self_hash.clone()),
+        None if entry.first_seq == 0 => None,
+        None => Some(entry.prev_hash.clone()),
+    };
+    Ok(OpenSegment { file, offsets, end: complete as u64, last_hash })
+}
+
+/// Header and deeds of one segment file's content.
+fn parse_segment(bytes: &[u8], entry: &SegmentEntry) -> Result<(SegmentHeader, Vec<DeedEvent>), StoreError> {
+    let mut lines = bytes.split(|b| *b == b'\n');
+    let header = lines.next().filter(|l| !l.is_empty()).ok_or_else(|| corrupt(entry.segment, "missing header"))?;
+    let header: SegmentHeader =
+        serde_json::from_slice(header).map_err(|e| corrupt(entry.segment, format!("bad header: {}", e)))?;
+    let mut deeds = Vec::new();
+    for (idx, line) in lines.enumerate() {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let deed = serde_json::from_slice(line).map_err(|e| StoreError::Corrupt {
+            line: entry.first_seq as usize + idx + 1,
+            reason: e.to_string(),
+        })?;
+        deeds.push(deed);
+    }
+    Ok((header, deeds))
+}
+
+fn file_sha256(path: &Path) -> Result<String, StoreError> {
+    Ok(hex::encode(Sha256::digest(std::fs::read(path)?)))
+}
+
+fn gzip(bytes: &[u8]) -> Result<Vec<u8>, StoreError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(bytes)?;
+    Ok(encoder.finish()?)
+}
+
+/// Write `bytes` to `path` through a synced temporary file and a rename.
+fn write_replacing(path: &Path, bytes: &[u8]) -> Result<(), StoreError> {
+    let tmp = with_suffix(path, ".tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp, path)?;
+    if let Some(dir) = path.parent() {
+        sync_dir(dir)?;
+    }
+    Ok(())
+}
+
+/// Make a created or renamed entry of `dir` durable, as `JsonlStore` does.
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+fn save_manifest(dir: &Path, manifest: &SegmentManifest) -> Result<(), StoreError> {
+    write_replacing(&dir.join(MANIFEST), &serde_json::to_vec_pretty(manifest)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+
+    fn fill(store: &mut SegmentedStore, n: usize) -> Vec<DeedEvent> {
+        let mut prev = store.last_hash().unwrap_or_else(|| "0".repeat(64));
+        let mut deeds = Vec::new();
+        for i in 0..n {
+            let deed = DeedEvent::new_math_science_education("actor".into(), format!("crate-{}", i), &SystemClock, &RandomIds)
+                .finalize_hash_chain(prev.clone());
+            store.append(&deed).unwrap();
+            prev = deed.self_hash.clone();
+            deeds.push(deed);
+        }
+        deeds
+    }
+
+    #[test]
+    fn segments_rotate_link_and_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SegmentedStore::open(dir.path().to_path_buf(), 3).unwrap();
+        let deeds = fill(&mut store, 8);
+        assert_eq!(store.manifest().segments.len(), 3);
+        assert_eq!(store.manifest().segments[1].prev_hash, deeds[2].self_hash);
+
+        let store = SegmentedStore::open(dir.path().to_path_buf(), 100).unwrap();
+        assert_eq!(store.manifest().deeds_per_segment, 3);
+        assert_eq!(store.len(), 8);
+        assert_eq!(store.last_hash(), Some(deeds[7].self_hash.clone()));
+        for (seq, deed) in deeds.iter().enumerate() {
+            assert_eq!(store.get(seq as u64).unwrap().unwrap().event_id, deed.event_id);
+        }
+        assert_eq!(store.read_from(2).unwrap().len(), 6);
+        assert!(store.verify_manifest().unwrap());
+        assert!((0..3).all(|s| store.verify_segment(s).unwrap()));
+    }
+
+    #[test]
+    fn archived_segments_stay_readable_and_verifiable() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SegmentedStore::open(dir.path().to_path_buf(), 2).unwrap();
+        let deeds = fill(&mut store, 5);
+        assert_eq!(store.archive_closed().unwrap(), vec![0, 1]);
+        assert!(dir.path().join("segment-000000.jsonl.gz").exists());
+        assert!(!dir.path().join("segment-000000.jsonl").exists());
+        assert!(store.verify_manifest().unwrap());
+        assert!(store.verify_segment(1).unwrap());
+        assert_eq!(store.get(1).unwrap().unwrap().event_id, deeds[1].event_id);
+        assert_eq!(store.read_all().unwrap().len(), 5);
+
+        // Tampering with one archive is caught from the manifest digest alone.
+        std::fs::write(dir.path().join("segment-000001.jsonl.gz"), gzip(b"{}\n").unwrap()).unwrap();
+        assert!(!store.verify_manifest().unwrap());
+        assert!(!store.verify_segment(1).unwrap());
+        assert!(store.verify_segment(0).unwrap());
+    }
+
+    #[test]
+    fn redaction_rewrites_an_archived_segment() {
+        use crate::ledger::{validate_ledger, MoralLedger};
+        let dir = tempfile::tempdir().unwrap();
+        let mut ledger = MoralLedger::open_segmented(dir.path().to_path_buf(), 2).unwrap();
+        let context = serde_json::json!({ "site": "Phoenix, AZ" });
+        let deed = DeedEvent::new("a".into(), vec![], "homelessness_relief".into(), vec![], context, &SystemClock, &RandomIds);
+        ledger.append(deed.conceal(&["site"]).unwrap()).unwrap();
+        for i in 0..3 {
+            ledger.append(DeedEvent::new_math_science_education("a".into(), format!("crate-{}", i), &SystemClock, &RandomIds)).unwrap();
+        }
+        drop(ledger);
+        SegmentedStore::open(dir.path().to_path_buf(), 2).unwrap().archive_closed().unwrap();
+
+        let mut ledger = MoralLedger::open_segmented(dir.path().to_path_buf(), 2).unwrap();
+        assert!(ledger.redact(0, "site").unwrap());
+        assert!(validate_ledger(ledger.store()).unwrap());
+        assert!(ledger.store().verify_manifest().unwrap());
+        assert_eq!(ledger.store().get(0).unwrap().unwrap().redacted_fields(), vec!["site".to_string()]);
+    }
+
+    #[test]
+    fn truncate_reopens_an_archived_segment() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SegmentedStore::open(dir.path().to_path_buf(), 2).unwrap();
+        let deeds = fill(&mut store, 5);
+        store.archive_closed().unwrap();
+        store.truncate(1).unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.last_hash(), Some(deeds[0].self_hash.clone()));
+        assert_eq!(store.manifest().segments.len(), 1);
+        assert!(dir.path().join("segment-000000.jsonl").exists());
+        let more = fill(&mut store, 3);
+        assert_eq!(more[0].prev_hash, deeds[0].self_hash);
+        assert!(store.verify_manifest().unwrap());
+    }
+}
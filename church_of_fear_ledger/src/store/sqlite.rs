@@ -0,0 +1,134 @@
+use super::{LedgerStore, StoreError};
+use crate::deed::DeedEvent;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+
+/// Embedded SQLite store (`--features sqlite`). Runs in WAL mode with
+/// `synchronous = FULL`, so a committed append survives power loss.
+pub struct SqliteStore {
+    conn: Connection,
+    last_hash: Option<String>,
+    len: u64,
+}
+
+impl SqliteStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StoreError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, StoreError> {
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "FULL")?;
+        // Redacted rows must not linger in free pages.
+        conn.pragma_update(None, "secure_delete", "ON")?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS deeds (
+                seq        INTEGER PRIMARY KEY,
+                event_id   TEXT NOT NULL UNIQUE,
+                prev_hash  TEXT NOT NULL,
+                self_hash  TEXT NOT NULL,
+                json       TEXT NOT NULL
+            );",
+        )?;
+        let last_hash = conn
+            .query_row("SELECT self_hash FROM deeds ORDER BY seq DESC LIMIT 1", [], |row| row.get(0))
+            .optional()?;
+        let len: i64 = conn.query_row("SELECT COUNT(*) FROM deeds", [], |row| row.get(0))?;
+        Ok(Self { conn, last_hash, len: len as u64 })
+    }
+}
+
+impl LedgerStore for SqliteStore {
+    fn append(&mut self, event: &DeedEvent) -> Result<(), StoreError> {
+        let json = serde_json::to_string(event)?;
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO deeds (event_id, prev_hash, self_hash, json) VALUES (?1, ?2, ?3, ?4)",
+            params![event.event_id.to_string(), event.prev_hash, event.self_hash, json],
+        )?;
+        tx.commit()?;
+        self.last_hash = Some(event.self_hash.clone());
+        self.len += 1;
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: u64) -> Result<(), StoreError> {
+        self.conn.execute("DELETE FROM deeds WHERE seq > ?1", params![len as i64])?;
+        self.len = self.len.min(len);
+        self.last_hash = self
+            .conn
+            .query_row("SELECT self_hash FROM deeds ORDER BY seq DESC LIMIT 1", [], |row| row.get(0))
+            .optional()?;
+        Ok(())
+    }
+
+    fn rewrite(&mut self, seq: u64, event: &DeedEvent) -> Result<(), StoreError> {
+        let json = serde_json::to_string(event)?;
+        self.conn.execute(
+            "UPDATE deeds SET json = ?1 WHERE seq = ?2 AND self_hash = ?3",
+            params![json, seq as i64 + 1, event.self_hash],
+        )?;
+        // ...nor in the write-ahead log.
+        self.conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
+
+    fn read_all(&self) -> Result<Vec<DeedEvent>, StoreError> {
+        let mut stmt = self.conn.prepare("SELECT seq, json FROM deeds ORDER BY seq")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+        let mut events = Vec::new();
+        for row in rows {
+            let (seq, json) = row?;
+            let event = serde_json::from_str(&json)
+                .map_err(|e| StoreError::Corrupt { line: seq as usize, reason: e.to_string() })?;
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    fn get(&self, seq: u64) -> Result<Option<DeedEvent>, StoreError> {
+        // rowids start at 1
+        let json: Option<String> = self
+            .conn
+            .query_row("SELECT json FROM deeds WHERE seq = ?1", params![seq as i64 + 1], |row| row.get(0))
+            .optional()?;
+        json.map(|j| {
+            serde_json::from_str(&j).map_err(|e| StoreError::Corrupt { line: seq as usize + 1, reason: e.to_string() })
+        })
+        .transpose()
+    }
+
+    fn last_hash(&self) -> Option<String> {
+        self.last_hash.clone()
+    }
+
+    fn len(&self) -> u64 {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+
+    #[test]
+    fn append_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.sqlite");
+        let event = DeedEvent::new_math_science_education("actor".into(), "crate".into(), &SystemClock, &RandomIds)
+            .finalize_hash_chain("0".repeat(64));
+        {
+            let mut store = SqliteStore::open(&path).unwrap();
+            store.append(&event).unwrap();
+        }
+        let store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.last_hash(), Some(event.self_hash.clone()));
+        assert_eq!(store.read_all().unwrap()[0].event_id, event.event_id);
+    }
+}
@@ -0,0 +1,260 @@
+use super::protocol::PeerConnection;
+use super::{append_fork_evidence, checkpoint_covers, fork_winner, Branch, ForkEvidence, ForkSide, SyncError};
+use crate::keys::KeyRegistry;
+use crate::ledger::MoralLedger;
+use crate::store::LedgerStore;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug)]
+pub enum SyncOutcome {
+    UpToDate,
+    /// The peer's chain is a prefix of ours; it will pull from us.
+    PeerBehind,
+    Fetched { deeds: usize },
+    Fork { evidence: Box<ForkEvidence>, adopted: usize },
+}
+
+fn lock<S: LedgerStore>(ledger: &Mutex<MoralLedger<S>>) -> MutexGuard<'_, MoralLedger<S>> {
+    ledger.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Pull from `peer` once. The ledger is only locked for local reads and the
+/// final commit, never across a network call, so two nodes pulling from
+/// each other cannot deadlock. Forks are resolved by `fork_winner`, with
+/// checkpoints trusted only if signed by a key in `notaries`, and recorded
+/// in `fork_log` before anything is rewound.
+pub fn pull_from_peer<S: LedgerStore>(
+    ledger: &Mutex<MoralLedger<S>>,
+    peer: &str,
+    notaries: &KeyRegistry,
+    fork_log: &Path,
+) -> Result<SyncOutcome, SyncError> {
+    let mut conn = PeerConnection::connect(peer)?;
+    let (peer_len, peer_head) = conn.head()?;
+    let (local_len, local_head) = {
+        let ledger = lock(ledger);
+        (ledger.len(), ledger.last_hash().to_string())
+    };
+    if peer_len == local_len && peer_head == local_head {
+        return Ok(SyncOutcome::UpToDate);
+    }
+
+    let common = common_prefix(ledger, &mut conn, local_len.min(peer_len))?;
+    if common == peer_len {
+        return Ok(SyncOutcome::PeerBehind);
+    }
+    let unchanged = |l: &MoralLedger<S>| l.len() == local_len && l.last_hash() == local_head;
+
+    if common == local_len {
+        let deeds = conn.deeds(local_len, peer_len)?;
+        let mut ledger = lock(ledger);
+        if !unchanged(&ledger) {
+            return Err(SyncError::Raced);
+        }
+        ledger.verify_branch(&local_head, &deeds)?;
+        for deed in &deeds {
+            ledger.append_sealed(deed)?;
+        }
+        log::info!("fetched {} deeds from {}", deeds.len(), peer);
+        return Ok(SyncOutcome::Fetched { deeds: deeds.len() });
+    }
+
+    // Both chains extend the first `common` deeds differently.
+    let theirs = conn.deeds(common, peer_len)?;
+    let theirs_checkpoint = conn.checkpoint()?.filter(|cp| checkpoint_covers(notaries, cp, common, &theirs));
+    let (ours, ours_checkpoint) = {
+        let ledger = lock(ledger);
+        let ours = ledger.store().read_from(common)?;
+        let checkpoint = ledger.latest_checkpoint()?.filter(|cp| checkpoint_covers(notaries, cp, common, &ours));
+        (ours, checkpoint)
+    };
+    let (Some(our_deed), Some(their_deed)) = (ours.first(), theirs.first()) else {
+        return Err(SyncError::Raced);
+    };
+    let kept = fork_winner(
+        ForkSide { len: local_len, deed: our_deed, checkpointed: ours_checkpoint.is_some() },
+        ForkSide { len: peer_len, deed: their_deed, checkpointed: theirs_checkpoint.is_some() },
+    );
+
+    let mut ledger = lock(ledger);
+    if !unchanged(&ledger) {
+        return Err(SyncError::Raced);
+    }
+    let evidence = ForkEvidence {
+        position: common,
+        prev_hash: their_deed.prev_hash.clone(),
+        peer: peer.to_string(),
+        kept,
+        ours,
+        theirs,
+        ours_len: local_len,
+        theirs_len: peer_len,
+        ours_checkpoint,
+        theirs_checkpoint,
+        detected_at: ledger.clock().unix_seconds(),
+    };
+    if kept == Branch::Theirs {
+        ledger.verify_branch(&evidence.prev_hash, &evidence.theirs)?;
+    }
+    append_fork_evidence(fork_log, &evidence)?;
+    log::warn!("fork with {} at position {}: keeping {:?} branch", peer, common, kept);
+
+    let mut adopted = 0;
+    if kept == Branch::Theirs {
+        ledger.rewind_to(common)?;
+        for deed in &evidence.theirs {
+            ledger.append_sealed(deed)?;
+        }
+        adopted = evidence.theirs.len();
+    }
+    Ok(SyncOutcome::Fork { evidence: Box::new(evidence), adopted })
+}
+
+/// Length of the longest prefix both chains share (hash chaining means
+/// chains that differ at one position differ at every later one).
+fn common_prefix<S: LedgerStore>(
+    ledger: &Mutex<MoralLedger<S>>,
+    conn: &mut PeerConnection,
+    max: u64,
+) -> Result<u64, SyncError> {
+    let mut matches = |n: u64| -> Result<bool, SyncError> {
+        if n == 0 {
+            return Ok(true);
+        }
+        let ours = lock(ledger).store().get(n - 1)?.map(|e| e.self_hash.clone());
+        Ok(ours.is_some() && ours == conn.hash_at(n - 1)?)
+    };
+    if matches(max)? {
+        return Ok(max);
+    }
+    let (mut lo, mut hi) = (0, max);
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if matches(mid)? {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+    use crate::deed::DeedEvent;
+    use crate::store::MemoryStore;
+    use crate::sync::serve;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    fn node(deeds: &[&str]) -> (Arc<Mutex<MoralLedger<MemoryStore>>>, String) {
+        serve_node(MoralLedger::with_store(MemoryStore::new()).unwrap(), deeds)
+    }
+
+    fn serve_node(mut ledger: MoralLedger<MemoryStore>, deeds: &[&str]) -> (Arc<Mutex<MoralLedger<MemoryStore>>>, String) {
+        for name in deeds {
+            ledger.append(DeedEvent::new_math_science_education("a".into(), name.to_string(), &SystemClock, &RandomIds)).unwrap();
+        }
+        let ledger = Arc::new(Mutex::new(ledger));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let served = Arc::clone(&ledger);
+        std::thread::spawn(move || serve(listener, served));
+        (ledger, addr)
+    }
+
+    fn copy_prefix(from: &Mutex<MoralLedger<MemoryStore>>, to: &Mutex<MoralLedger<MemoryStore>>, n: u64) {
+        let deeds = lock(from).store().read_all().unwrap();
+        for deed in deeds.iter().take(n as usize) {
+            lock(to).append_sealed(deed).unwrap();
+        }
+    }
+
+    #[test]
+    fn lagging_node_fetches_missing_deeds() {
+        let (a, a_addr) = node(&["x", "y", "z"]);
+        let (b, b_addr) = node(&[]);
+        copy_prefix(&a, &b, 1);
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("forks.jsonl");
+
+        assert!(matches!(pull_from_peer(&b, &a_addr, &KeyRegistry::new(), &log).unwrap(), SyncOutcome::Fetched { deeds: 2 }));
+        assert_eq!(lock(&b).last_hash(), lock(&a).last_hash());
+        assert!(matches!(pull_from_peer(&a, &b_addr, &KeyRegistry::new(), &log).unwrap(), SyncOutcome::UpToDate));
+    }
+
+    #[test]
+    fn fork_is_resolved_deterministically_and_recorded() {
+        let (a, a_addr) = node(&["shared"]);
+        let (b, b_addr) = node(&[]);
+        copy_prefix(&a, &b, 1);
+        lock(&a).append(DeedEvent::new_math_science_education("a".into(), "a-only".into(), &SystemClock, &RandomIds)).unwrap();
+        for name in ["b-1", "b-2"] {
+            lock(&b).append(DeedEvent::new_math_science_education("b".into(), name.into(), &SystemClock, &RandomIds)).unwrap();
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("forks.jsonl");
+
+        // b's branch is longer, so both nodes keep it.
+        match pull_from_peer(&b, &a_addr, &KeyRegistry::new(), &log).unwrap() {
+            SyncOutcome::Fork { evidence, adopted } => {
+                assert_eq!((evidence.position, evidence.kept, adopted), (1, Branch::Ours, 0));
+            }
+            other => panic!("expected fork, got {:?}", other),
+        }
+        match pull_from_peer(&a, &b_addr, &KeyRegistry::new(), &log).unwrap() {
+            SyncOutcome::Fork { evidence, adopted } => {
+                assert_eq!((evidence.kept, adopted), (Branch::Theirs, 2));
+                assert_eq!((evidence.ours.len(), evidence.theirs.len()), (1, 2));
+            }
+            other => panic!("expected fork, got {:?}", other),
+        }
+        assert_eq!(lock(&a).last_hash(), lock(&b).last_hash());
+        assert!(crate::ledger::validate_ledger(lock(&a).store()).unwrap());
+        assert_eq!(std::fs::read_to_string(&log).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn notarised_branch_beats_longer_filler() {
+        use crate::checkpoint::NodeKey;
+        use ed25519_dalek::SigningKey;
+        let dir = tempfile::tempdir().unwrap();
+        let node_key = NodeKey { key_id: "did:bostrom:node-a#checkpoint".into(), signing_key: SigningKey::from_bytes(&[7; 32]) };
+        let mut notaries = KeyRegistry::new();
+        notaries.register(&node_key.key_id, &node_key.signing_key.verifying_key()).unwrap();
+        let ledger = MoralLedger::with_store_and_checkpoints(
+            MemoryStore::new(),
+            dir.path().join("a.idx.json"),
+            dir.path().join("a.ckpt.jsonl"),
+            node_key,
+        )
+        .unwrap();
+        let (a, a_addr) = serve_node(ledger, &["shared", "a-only"]);
+        lock(&a).checkpoint().unwrap();
+        let (b, b_addr) = node(&[]);
+        copy_prefix(&a, &b, 1);
+        for name in ["filler-1", "filler-2", "filler-3"] {
+            lock(&b).append(DeedEvent::new_math_science_education("b".into(), name.into(), &SystemClock, &RandomIds)).unwrap();
+        }
+        let log = dir.path().join("forks.jsonl");
+
+        // Without trusting a's key, b's longer branch would stand.
+        match pull_from_peer(&b, &a_addr, &KeyRegistry::new(), &log).unwrap() {
+            SyncOutcome::Fork { evidence, .. } => assert_eq!(evidence.kept, Branch::Ours),
+            other => panic!("expected fork, got {:?}", other),
+        }
+        match pull_from_peer(&b, &a_addr, &notaries, &log).unwrap() {
+            SyncOutcome::Fork { evidence, adopted } => {
+                assert_eq!((evidence.kept, adopted), (Branch::Theirs, 1));
+                assert_eq!((evidence.ours.len(), evidence.theirs.len()), (3, 1));
+                assert_eq!(evidence.theirs_checkpoint.as_ref().map(|cp| cp.event_count), Some(2));
+            }
+            other => panic!("expected fork, got {:?}", other),
+        }
+        assert_eq!(lock(&b).last_hash(), lock(&a).last_hash());
+        assert!(matches!(pull_from_peer(&a, &b_addr, &notaries, &log).unwrap(), SyncOutcome::UpToDate));
+    }
+}
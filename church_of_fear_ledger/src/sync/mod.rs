@@ -0,0 +1,138 @@
+//! Peer-to-peer replication of the moral ledger over TCP.
+//!
+//! Nodes serve their chain (`server`) and pull from their peers (`client`)
+//! with a line-delimited JSON protocol (`protocol`). A pull finds the
+//! longest common prefix by binary search over chain positions, then either
+//! fetches the missing tail or – when both sides extend the common prefix
+//! differently – resolves a fork.
+//!
+//! # Fork rule
+//!
+//! Two deeds at the same position sharing a `prev_hash` are a fork. The
+//! branch that is kept is decided only by data both nodes see, so every node
+//! converges on the same chain:
+//!
+//! 1. a branch covered past the fork position by a signed checkpoint from a
+//!    trusted notary beats one that is not;
+//! 2. otherwise the longer branch wins;
+//! 3. on equal length, the branch whose deed at the fork position has the
+//!    lexicographically smaller `self_hash` wins.
+//!
+//! Length alone would let any peer override history by appending filler
+//! deeds; a notarised checkpoint cannot be forged that way. Each side offers
+//! its newest own checkpoint, and it counts only if its key is in the
+//! notary registry and its `chain_head` is on that side's branch.
+//!
+//! The losing branch is never silently dropped: both branches are written,
+//! with the fork position and the checkpoints that decided it, to the fork
+//! log as `ForkEvidence`.
+
+pub mod client;
+pub mod protocol;
+pub mod server;
+
+pub use client::{pull_from_peer, SyncOutcome};
+pub use server::serve;
+
+use crate::checkpoint::Checkpoint;
+use crate::deed::DeedEvent;
+use crate::keys::KeyRegistry;
+use crate::store::StoreError;
+use crate::validator::ValidationError;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("peer sent an invalid deed: {0}")]
+    InvalidDeed(#[from] ValidationError),
+    #[error("protocol error: {0}")]
+    Protocol(String),
+    #[error("local ledger changed during sync; retry")]
+    Raced,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Branch {
+    Ours,
+    Theirs,
+}
+
+/// One side of a fork as the rule sees it.
+#[derive(Debug, Clone, Copy)]
+pub struct ForkSide<'a> {
+    pub len: u64,                       // full chain length
+    pub deed: &'a DeedEvent,            // deed at the fork position
+    pub checkpointed: bool,             // a trusted checkpoint covers the fork
+}
+
+/// Which side of a fork the rule keeps.
+pub fn fork_winner(ours: ForkSide<'_>, theirs: ForkSide<'_>) -> Branch {
+    match (ours.checkpointed, theirs.checkpointed) {
+        (true, false) => return Branch::Ours,
+        (false, true) => return Branch::Theirs,
+        _ => {}
+    }
+    match ours.len.cmp(&theirs.len) {
+        std::cmp::Ordering::Greater => Branch::Ours,
+        std::cmp::Ordering::Less => Branch::Theirs,
+        std::cmp::Ordering::Equal if theirs.deed.self_hash < ours.deed.self_hash => Branch::Theirs,
+        std::cmp::Ordering::Equal => Branch::Ours,
+    }
+}
+
+/// Whether `checkpoint` is signed by a key in `notaries` and vouches for
+/// `branch`, the deeds from fork position `position` on.
+pub fn checkpoint_covers(notaries: &KeyRegistry, checkpoint: &Checkpoint, position: u64, branch: &[DeedEvent]) -> bool {
+    let Some(idx) = checkpoint.event_count.checked_sub(position + 1) else {
+        return false;
+    };
+    let on_branch = branch.get(idx as usize).is_some_and(|e| e.self_hash == checkpoint.chain_head);
+    let trusted = checkpoint
+        .signature
+        .as_ref()
+        .and_then(|sig| notaries.resolve(&sig.key_id).ok().flatten())
+        .is_some_and(|key| checkpoint.verify(&key));
+    on_branch && trusted
+}
+
+/// Record of one detected fork, kept in the fork log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForkEvidence {
+    pub position: u64,
+    pub prev_hash: String,
+    pub peer: String,
+    pub kept: Branch,
+    /// Our deeds from `position` on.
+    pub ours: Vec<DeedEvent>,
+    /// The peer's deeds from `position` on.
+    pub theirs: Vec<DeedEvent>,
+    pub ours_len: u64,
+    pub theirs_len: u64,
+    /// Trusted checkpoints covering each branch, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ours_checkpoint: Option<Checkpoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theirs_checkpoint: Option<Checkpoint>,
+    pub detected_at: i64,
+}
+
+/// Append one fork record to `path` and fsync it.
+pub fn append_fork_evidence(path: &Path, evidence: &ForkEvidence) -> Result<(), SyncError> {
+    let mut line = serde_json::to_vec(evidence)?;
+    line.push(b'\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&line)?;
+    file.sync_data()?;
+    Ok(())
+}
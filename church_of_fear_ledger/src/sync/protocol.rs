@@ -0,0 +1,104 @@
+//! Wire format: one JSON request per line, one JSON response per line.
+
+use super::SyncError;
+use crate::checkpoint::Checkpoint;
+use crate::deed::DeedEvent;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Most deeds returned by one `Deeds` request.
+pub const MAX_BATCH: u64 = 512;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncRequest {
+    Head,
+    HashAt { seq: u64 },
+    Deeds { from: u64, limit: u64 },
+    Checkpoint,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncResponse {
+    Head { len: u64, head: String },
+    HashAt { seq: u64, self_hash: Option<String> },
+    Deeds { from: u64, deeds: Vec<DeedEvent> },
+    Checkpoint { checkpoint: Option<Box<Checkpoint>> },
+    Error { message: String },
+}
+
+/// Client side of one peer connection.
+pub struct PeerConnection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl PeerConnection {
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, SyncError> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+        Ok(Self { reader: BufReader::new(stream.try_clone()?), writer: stream })
+    }
+
+    pub fn request(&mut self, request: &SyncRequest) -> Result<SyncResponse, SyncError> {
+        let mut line = serde_json::to_vec(request)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        let mut response = String::new();
+        if self.reader.read_line(&mut response)? == 0 {
+            return Err(SyncError::Protocol("peer closed the connection".to_string()));
+        }
+        match serde_json::from_str(&response)? {
+            SyncResponse::Error { message } => Err(SyncError::Protocol(message)),
+            response => Ok(response),
+        }
+    }
+
+    pub fn head(&mut self) -> Result<(u64, String), SyncError> {
+        match self.request(&SyncRequest::Head)? {
+            SyncResponse::Head { len, head } => Ok((len, head)),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    pub fn hash_at(&mut self, seq: u64) -> Result<Option<String>, SyncError> {
+        match self.request(&SyncRequest::HashAt { seq })? {
+            SyncResponse::HashAt { self_hash, .. } => Ok(self_hash),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    /// The peer's newest own signed checkpoint still on its chain.
+    pub fn checkpoint(&mut self) -> Result<Option<Checkpoint>, SyncError> {
+        match self.request(&SyncRequest::Checkpoint)? {
+            SyncResponse::Checkpoint { checkpoint } => Ok(checkpoint.map(|cp| *cp)),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    /// Deeds `from..to` (exclusive), fetched in `MAX_BATCH` pieces.
+    pub fn deeds(&mut self, from: u64, to: u64) -> Result<Vec<DeedEvent>, SyncError> {
+        let mut deeds = Vec::new();
+        let mut next = from;
+        while next < to {
+            let limit = (to - next).min(MAX_BATCH);
+            let batch = match self.request(&SyncRequest::Deeds { from: next, limit })? {
+                SyncResponse::Deeds { deeds, .. } => deeds,
+                other => return Err(unexpected(&other)),
+            };
+            if batch.is_empty() {
+                return Err(SyncError::Protocol(format!("peer has no deed at {}", next)));
+            }
+            next += batch.len() as u64;
+            deeds.extend(batch);
+        }
+        Ok(deeds)
+    }
+}
+
+fn unexpected(response: &SyncResponse) -> SyncError {
+    SyncError::Protocol(format!("unexpected response: {:?}", response))
+}
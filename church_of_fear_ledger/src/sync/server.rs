@@ -0,0 +1,145 @@
+use super::protocol::{SyncRequest, SyncResponse, MAX_BATCH};
+use crate::ledger::MoralLedger;
+use crate::store::{LedgerStore, StoreError};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Longest request line accepted, newline included. Requests are a few
+/// dozen bytes; anything longer is dropped with its connection.
+pub const MAX_LINE: u64 = 4096;
+/// Most peers served at once; further connections are closed on accept.
+pub const MAX_PEERS: usize = 64;
+/// A peer silent this long gives up its slot.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Serve `ledger` to peers, one thread per connection and at most
+/// `MAX_PEERS` at once. Blocks for as long as the listener accepts
+/// connections.
+pub fn serve<S>(listener: TcpListener, ledger: Arc<Mutex<MoralLedger<S>>>) -> std::io::Result<()>
+where
+    S: LedgerStore + Send + 'static,
+{
+    log::info!("ledger sync listening on {}", listener.local_addr()?);
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let Some(slot) = PeerSlot::claim(&active) else {
+                    log::warn!("sync peer limit reached; refusing {:?}", stream.peer_addr().ok());
+                    continue;
+                };
+                let ledger = Arc::clone(&ledger);
+                thread::spawn(move || {
+                    handle_peer(stream, ledger);
+                    drop(slot);
+                });
+            }
+            Err(e) => log::error!("sync accept error: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// One of the `MAX_PEERS` connection slots, released on drop.
+struct PeerSlot(Arc<AtomicUsize>);
+
+impl PeerSlot {
+    fn claim(active: &Arc<AtomicUsize>) -> Option<Self> {
+        active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < MAX_PEERS).then_some(n + 1))
+            .ok()
+            .map(|_| Self(Arc::clone(active)))
+    }
+}
+
+impl Drop for PeerSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+fn handle_peer<S: LedgerStore>(stream: TcpStream, ledger: Arc<Mutex<MoralLedger<S>>>) {
+    let peer = stream.peer_addr().ok();
+    if let Err(e) = stream.set_read_timeout(Some(IDLE_TIMEOUT)) {
+        log::warn!("sync peer {:?}: {}", peer, e);
+        return;
+    }
+    let Ok(read_half) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(read_half);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match (&mut reader).take(MAX_LINE).read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) if !line.ends_with('\n') && line.len() as u64 == MAX_LINE => {
+                log::warn!("sync request from {:?} exceeds {} bytes; closing", peer, MAX_LINE);
+                break;
+            }
+            Ok(_) if line.trim().is_empty() => continue,
+            Ok(_) => {}
+            Err(e) => {
+                log::warn!("sync read error from {:?}: {}", peer, e);
+                break;
+            }
+        }
+        let response = match serde_json::from_str::<SyncRequest>(&line) {
+            Ok(request) => answer(&ledger, request)
+                .unwrap_or_else(|e| SyncResponse::Error { message: e.to_string() }),
+            Err(e) => SyncResponse::Error { message: format!("bad request: {}", e) },
+        };
+        let mut out = serde_json::to_vec(&response).unwrap_or_default();
+        out.push(b'\n');
+        if let Err(e) = (&stream).write_all(&out) {
+            log::warn!("sync write error to {:?}: {}", peer, e);
+            break;
+        }
+    }
+}
+
+fn answer<S: LedgerStore>(ledger: &Mutex<MoralLedger<S>>, request: SyncRequest) -> Result<SyncResponse, StoreError> {
+    let ledger = ledger.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    Ok(match request {
+        SyncRequest::Head => SyncResponse::Head { len: ledger.len(), head: ledger.last_hash().to_string() },
+        SyncRequest::HashAt { seq } => {
+            SyncResponse::HashAt { seq, self_hash: ledger.store().get(seq)?.map(|e| e.self_hash.clone()) }
+        }
+        SyncRequest::Deeds { from, limit } => {
+            let to = from.saturating_add(limit.min(MAX_BATCH)).min(ledger.len());
+            let mut deeds = Vec::new();
+            for seq in from..to {
+                deeds.extend(ledger.store().get(seq)?);
+            }
+            SyncResponse::Deeds { from, deeds }
+        }
+        SyncRequest::Checkpoint => SyncResponse::Checkpoint { checkpoint: ledger.latest_checkpoint()?.map(Box::new) },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use crate::sync::protocol::PeerConnection;
+
+    #[test]
+    fn oversized_request_closes_the_connection() {
+        let ledger = Arc::new(Mutex::new(MoralLedger::with_store(MemoryStore::new()).unwrap()));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, ledger));
+
+        let mut conn = PeerConnection::connect(addr).unwrap();
+        assert_eq!(conn.head().unwrap().0, 0);
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(&vec![b' '; MAX_LINE as usize]).unwrap();
+        let mut rest = Vec::new();
+        assert_eq!(stream.read_to_end(&mut rest).unwrap(), 0);
+    }
+}
@@ -1,5 +1,9 @@
 use crate::deed::DeedEvent;
+use crate::evidence::{evidence_refs, EvidenceError, EvidenceStore};
+use crate::keys::{KeyError, KeyRegistry};
+use crate::store::StoreError;
 use thiserror::Error;
+use token_journal::CeilingError;
 
 #[derive(Error, Debug)]
 pub enum ValidationError {
@@ -13,6 +17,28 @@ pub enum ValidationError {
     Io(#[from] std::io::Error),
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("deed by {0} is not signed")]
+    Unsigned(String),
+    #[error("key {key_id} is unknown, revoked or not controlled by {actor_id}")]
+    UnauthorizedKey { key_id: String, actor_id: String },
+    #[error("bad signature: {0}")]
+    BadSignature(String),
+    #[error("key registry error: {0}")]
+    Keys(#[from] KeyError),
+    #[error("invalid correction: {0}")]
+    InvalidCorrection(String),
+    #[error("invalid dispute step: {0}")]
+    InvalidDispute(String),
+    #[error("invalid cross-ledger reference: {0}")]
+    InvalidReference(String),
+    #[error("evidence error: {0}")]
+    Evidence(#[from] EvidenceError),
+    #[error("timestamp {timestamp} is before the previous deed's {previous}")]
+    TimestampBeforePrevious { previous: i64, timestamp: i64 },
+    #[error("debt ceiling: {0}")]
+    Ceiling(#[from] CeilingError),
 }
 
 pub struct LedgerValidator;
@@ -25,7 +51,8 @@ impl LedgerValidator {
         if !event.ethics_flags.is_empty() {
             return Err(ValidationError::EthicsViolation(event.ethics_flags.clone()));
         }
-        if expected_prev_hash != "genesis" && event.prev_hash != expected_prev_hash {
+        // An empty prev_hash means "not chained yet" – the ledger fills it in.
+        if expected_prev_hash != "genesis" && !event.prev_hash.is_empty() && event.prev_hash != expected_prev_hash {
             return Err(ValidationError::HashMismatch {
                 expected: expected_prev_hash.to_string(),
                 actual: event.prev_hash.clone(),
@@ -33,4 +60,87 @@ impl LedgerValidator {
         }
         Ok(())
     }
+
+    /// Timestamps never decrease along the chain, so the deeds up to a
+    /// point in time are always a prefix of it (`DeedIndex::height_at`).
+    pub fn validate_timestamp(event: &DeedEvent, previous: Option<i64>) -> Result<(), ValidationError> {
+        match previous {
+            Some(previous) if event.timestamp < previous => {
+                Err(ValidationError::TimestampBeforePrevious { previous, timestamp: event.timestamp })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The deed must be signed by an active registry key that belongs to its
+    /// `actor_id`.
+    pub fn validate_signature(event: &DeedEvent, keys: &KeyRegistry) -> Result<(), ValidationError> {
+        let Some(sig) = &event.signature else {
+            return Err(ValidationError::Unsigned(event.actor_id.clone()));
+        };
+        let unauthorized =
+            || ValidationError::UnauthorizedKey { key_id: sig.key_id.clone(), actor_id: event.actor_id.clone() };
+        if deed_schema::key_controller(&sig.key_id) != Some(event.actor_id.as_str()) {
+            return Err(unauthorized());
+        }
+        let key = keys.resolve(&sig.key_id)?.ok_or_else(unauthorized)?;
+        deed_schema::DeedEvent::from(event)
+            .verify_signature(&key)
+            .map_err(|e| ValidationError::BadSignature(e.to_string()))
+    }
+
+    /// `ledger://` targets must parse, and anchor deeds must carry an anchor.
+    pub fn validate_references(event: &DeedEvent) -> Result<(), ValidationError> {
+        let invalid = |e: deed_schema::SchemaError| ValidationError::InvalidReference(e.to_string());
+        deed_schema::ledger_refs(&event.target_ids).map_err(invalid)?;
+        if let Some(anchor) = deed_schema::Anchor::from_deed(&event.deed_type, &event.context_json) {
+            anchor.map_err(invalid)?;
+        }
+        Ok(())
+    }
+
+    /// Every content-addressed reference in the deed's context must be in
+    /// `evidence` and match its hash.
+    pub fn validate_evidence(event: &DeedEvent, evidence: &EvidenceStore) -> Result<(), ValidationError> {
+        Ok(evidence.check_context(&event.context_json)?)
+    }
+}
+
+/// The optional checks a ledger runs on new deeds: actor signatures against
+/// a `KeyRegistry` and cited evidence against an `EvidenceStore`.
+#[derive(Debug, Default)]
+pub(crate) struct DeedChecks {
+    pub(crate) keys: Option<KeyRegistry>,
+    pub(crate) evidence: Option<EvidenceStore>,
+}
+
+impl DeedChecks {
+    /// Every configured check, for a deed appended here.
+    pub(crate) fn check_new(&self, event: &DeedEvent) -> Result<(), ValidationError> {
+        self.check_signature(event)?;
+        if let Some(evidence) = &self.evidence {
+            LedgerValidator::validate_evidence(event, evidence)?;
+        }
+        Ok(())
+    }
+
+    /// The signature check alone; deeds sealed by peers cite evidence this
+    /// node need not hold.
+    pub(crate) fn check_signature(&self, event: &DeedEvent) -> Result<(), ValidationError> {
+        match &self.keys {
+            Some(keys) => LedgerValidator::validate_signature(event, keys),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether a good deed may grow its actor's debt ceiling: its stored
+    /// `original` is signed by one of the actor's keys, or its effective
+    /// form `deed` names evidence the store holds.
+    pub(crate) fn is_verified(&self, original: &DeedEvent, deed: &DeedEvent) -> bool {
+        let signed = self.keys.as_ref().is_some_and(|keys| LedgerValidator::validate_signature(original, keys).is_ok());
+        let evidenced = self.evidence.as_ref().is_some_and(|store| {
+            !evidence_refs(&deed.context_json).is_empty() && LedgerValidator::validate_evidence(deed, store).is_ok()
+        });
+        signed || evidenced
+    }
 }
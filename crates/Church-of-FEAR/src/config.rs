@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use token_journal::{CeilingBook, CeilingError, CeilingPolicy, Journal, JournalError, PowerRule, RewardSchedule};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LedgerConfig {
@@ -6,6 +8,12 @@ pub struct LedgerConfig {
     pub decay_max: f64,
     pub token_reward_factor: u64,
     pub repair_pwr_threshold: f64,
+    #[serde(default)]
+    pub power_rule: PowerRule,  // POWER <= k * CHURCH, enforced on every posting to a journal from `open_journal`
+    #[serde(default = "builtin_schedule")]
+    pub reward_schedule: RewardSchedule,  // what every mint and recommendation pays; the builtin one unless configured
+    #[serde(default)]
+    pub ceiling_policy: CeilingPolicy,  // how much one mint may be, per the requesting account's debt ceiling
 }
 
 impl Default for LedgerConfig {
@@ -15,6 +23,25 @@ impl Default for LedgerConfig {
             decay_max: 1.0,
             token_reward_factor: 100,
             repair_pwr_threshold: 0.8,
+            power_rule: PowerRule::default(),
+            reward_schedule: builtin_schedule(),
+            ceiling_policy: CeilingPolicy::default(),
         }
     }
 }
+
+fn builtin_schedule() -> RewardSchedule {
+    RewardSchedule::builtin().clone()
+}
+
+impl LedgerConfig {
+    /// Open the token journal at `path` under this deployment's power rule.
+    pub fn open_journal(&self, path: &Path) -> Result<Journal, JournalError> {
+        Journal::open(path)?.with_power_rule(self.power_rule)
+    }
+
+    /// Empty debt ceilings under this deployment's ceiling policy.
+    pub fn ceiling_book(&self) -> Result<CeilingBook, CeilingError> {
+        CeilingBook::new(self.ceiling_policy.clone())
+    }
+}
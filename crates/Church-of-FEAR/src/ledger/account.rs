@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
+use token_journal::{Asset, Journal, JournalError};
+use uuid::Uuid;
 
+/// A holder's view of the token journal. The balances are a cache of
+/// `Journal::held`; every change goes through the journal as an entry
+/// linked to the deed that justified it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
     pub id: String,
@@ -18,15 +23,32 @@ impl Account {
         }
     }
 
-    pub fn credit_church(&mut self, amount: u64) {
-        self.balance_church = self.balance_church.saturating_add(amount);
+    pub fn from_journal(id: String, owner: String, journal: &Journal) -> Self {
+        let mut account = Self::new(id, owner);
+        account.refresh(journal);
+        account
     }
 
-    pub fn debit_church(&mut self, amount: u64) {
-        self.balance_church = self.balance_church.saturating_sub(amount);
+    pub fn refresh(&mut self, journal: &Journal) {
+        self.balance_church = journal.held(&self.id, Asset::Church);
+        self.balance_pwr = journal.held(&self.id, Asset::Pwr);
     }
 
-    pub fn credit_pwr(&mut self, amount: u64) {
-        self.balance_pwr = self.balance_pwr.saturating_add(amount);
+    pub fn credit_church(&mut self, journal: &mut Journal, amount: u64, deed_id: Uuid) -> Result<(), JournalError> {
+        journal.mint(Asset::Church, &self.id, amount, deed_id)?;
+        self.refresh(journal);
+        Ok(())
+    }
+
+    pub fn debit_church(&mut self, journal: &mut Journal, amount: u64, deed_id: Uuid) -> Result<(), JournalError> {
+        journal.burn(Asset::Church, &self.id, amount, deed_id)?;
+        self.refresh(journal);
+        Ok(())
+    }
+
+    pub fn credit_pwr(&mut self, journal: &mut Journal, amount: u64, deed_id: Uuid) -> Result<(), JournalError> {
+        journal.mint(Asset::Pwr, &self.id, amount, deed_id)?;
+        self.refresh(journal);
+        Ok(())
     }
 }
@@ -2,11 +2,12 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::fmt;
 use thiserror::Error;
-use uuid::Uuid;
-use chrono::Utc;
+use deed_schema::{Clock, IdGenerator};
 use nalgebra::VectorN;  // For biophysical vector computations (e.g., RoH vector)
 use rand::Rng;  // For simulation in tests
 use rayon::prelude::*;  // Parallel validation
+use token_journal::schedule::rules::{BIOLOAD_REDUCTION_BONUS, ECOLOGICAL_SUSTAINABILITY_REWARD};
+use token_journal::{CeilingDeed, RewardInput, RewardSchedule};  // Reward amounts from the policy schedule
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DeedEvent {
 pub event_id: String,  // UUID
@@ -22,7 +23,8 @@ pub ethics_flags: Vec<String>,
 pub life_harm_flag: bool,
 }
 impl DeedEvent {
-/// Creates a new DeedEvent with auto-generated fields.
+/// Creates a new DeedEvent, taking `event_id` from `ids` and `timestamp`
+/// from `clock`.
 pub fn new(
 prev_hash: String,
 actor_id: String,
@@ -32,9 +34,11 @@ tags: Vec<String>,
 context_json: serde_json::Value,
 ethics_flags: Vec<String>,
 life_harm_flag: bool,
+clock: &dyn Clock,
+ids: &dyn IdGenerator,
 ) -> Self {
-let event_id = Uuid::new_v4().to_string();
-let timestamp = Utc::now().timestamp();
+let event_id = ids.next_id().to_string();
+let timestamp = clock.unix_seconds();
 let mut event = Self {
 event_id,
 timestamp,
@@ -58,15 +62,56 @@ return Err(DeedError::InvariantViolation("Biophysical ceiling breached".to_strin
 }
 Ok(())
 }
-/// Computes CHURCH token reward based on deed impact.
-pub fn compute_church_reward(&self, bioload_delta: f64) -> u64 {
-if self.life_harm_flag || !self.ethics_flags.is_empty() {
-0
-} else if bioload_delta < 0.0 && self.deed_type == "ecological_sustainability" {
-(biolad_delta.abs() * 100.0) as u64  // Earn for reduction
-} else {
-0
+/// Facts about this deed for the reward schedule.
+pub fn reward_input(&self) -> RewardInput {
+RewardInput::new(&self.deed_type, &self.tags, !self.life_harm_flag && self.ethics_flags.is_empty())
+}
+/// Computes CHURCH token reward based on deed impact, per `schedule`'s
+/// `ecological_sustainability_reward`.
+pub fn compute_church_reward(&self, schedule: &RewardSchedule, bioload_delta: f64) -> u64 {
+let input = self.reward_input().with_bioload_delta(bioload_delta);
+schedule.evaluate(&input).amount_of(ECOLOGICAL_SUSTAINABILITY_REWARD)
+}
+}
+/// Lossless conversion into the canonical deed-schema row.
+impl TryFrom<DeedEvent> for deed_schema::DeedEvent {
+type Error = deed_schema::SchemaError;
+fn try_from(e: DeedEvent) -> Result<Self, Self::Error> {
+Ok(deed_schema::DeedEvent {
+schema_version: deed_schema::UNVERSIONED,
+event_id: deed_schema::parse_event_id(&e.event_id)?,
+timestamp: e.timestamp,
+timestamp_nanos: 0,
+prev_hash: e.prev_hash,
+self_hash: e.self_hash,
+actor_id: e.actor_id,
+target_ids: e.target_ids,
+deed_type: e.deed_type,
+tags: e.tags,
+context_json: e.context_json,
+ethics_flags: e.ethics_flags,
+life_harm_flag: e.life_harm_flag,
+extensions: serde_json::Map::new(),
+signature: None,
+disclosures: deed_schema::Disclosures::new(),
+})
+}
+}
+impl CeilingDeed for DeedEvent {
+fn event_id(&self) -> String {
+self.event_id.clone()
+}
+fn deed_type(&self) -> &str {
+&self.deed_type
+}
+fn tags(&self) -> &[String] {
+&self.tags
 }
+fn ethics_flags(&self) -> &[String] {
+&self.ethics_flags
+}
+fn life_harm_flag(&self) -> bool {
+self.life_harm_flag
 }
 }
 /// Hashes the DeedEvent (excluding self_hash) using SHA-256.
@@ -84,6 +129,17 @@ let current = &window[1];
 current.prev_hash == prev.self_hash
 })
 }
+/// Detailed counterpart of `validate_chain`: per-record issues (bad hash,
+/// broken link) and the consistent stretches after the first break. The
+/// first event's `prev_hash` is taken as given.
+pub fn audit_chain(events: &[DeedEvent]) -> Result<deed_schema::LedgerAuditReport, serde_json::Error> {
+let mut bytes = Vec::new();
+for event in events {
+serde_json::to_writer(&mut bytes, event)?;
+bytes.push(b'\n');
+}
+Ok(deed_schema::audit_ledger_bytes(&bytes, None))
+}
 /// XR-Grid visualization using Bevy for Jetson-Line deeds.
 pub fn xr_visualize_ledger(events: &[DeedEvent]) -> bevy::prelude::App {
 let mut app = bevy::prelude::App::new();
@@ -106,12 +162,11 @@ impl BioloadReducer {
 pub fn new(delta: f64) -> Self {
 Self { delta }
 }
-pub fn earn_church(&self) -> u64 {
-if self.delta < 0.0 {
-(self.delta.abs() * 50.0) as u64  // Reward for reduction
-} else {
-0
-}
+pub fn earn_church(&self, schedule: &RewardSchedule) -> u64 {
+// Advisory reward for reduction, per `schedule`'s `bioload_reduction_bonus`;
+// the ecological reward already pays for the same reduction
+let input = RewardInput::new("", &[], false).with_bioload_delta(self.delta);
+schedule.evaluate(&input).amount_of(BIOLOAD_REDUCTION_BONUS)
 }
 }
 /// Rare-item: KO_REPAIR_HERO
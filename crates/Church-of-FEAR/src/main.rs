@@ -7,13 +7,17 @@ mod sponsor;
 mod rpc;
 
 use crate::ledger::deed_event::{DeedEvent, BioloadReducer, RepairHero};
+use deed_schema::{RandomIds, SystemClock};
 use crate::ledger::metrics::BioloadMetrics;
-use crate::token::mint::mint_church;
+use crate::config::LedgerConfig;
+use crate::token::mint::post_church_mint;
 use crate::compliance::validator::validate_deed;
 use crate::utils::time::now_timestamp;
 use crate::rpc::server::start_rpc_server;
 use log::info;
 use serde_json::json;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 fn main() {
@@ -21,9 +25,19 @@ fn main() {
 
     info!("Starting Church-of-FEAR ledger node…");
 
+    // Every posting is held to the deployment's POWER <= k * CHURCH rule.
+    // The RPC server and this node share one journal so heights stay in order,
+    // and both pay out under the configured reward schedule.
+    let config = LedgerConfig::default();
+    let journal = config.open_journal(Path::new("church_journal.jsonl")).expect("token journal must open");
+    let journal = Arc::new(Mutex::new(journal));
+
     // Spawn Auto_Church RPC in the background
-    thread::spawn(|| {
-        if let Err(e) = start_rpc_server("127.0.0.1:4040") {
+    let rpc_journal = Arc::clone(&journal);
+    let rpc_schedule = Arc::new(config.reward_schedule.clone());
+    let rpc_ceilings = Arc::new(Mutex::new(config.ceiling_book().expect("ceiling policy must be valid")));
+    thread::spawn(move || {
+        if let Err(e) = start_rpc_server("127.0.0.1:4040", rpc_journal, rpc_schedule, rpc_ceilings) {
             eprintln!("RPC server failed: {}", e);
         }
     });
@@ -45,6 +59,8 @@ fn main() {
         context,
         vec![],
         false,
+        &SystemClock,
+        &RandomIds,
     );
 
     let roh = 0.2;
@@ -52,7 +68,14 @@ fn main() {
     validate_deed(&deed, roh, decay).expect("deed must be compliant");
 
     let metrics = BioloadMetrics::new(-0.12, roh, decay);
-    let church_delta = mint_church(&deed, &metrics);
+    let church_delta = post_church_mint(
+        &mut journal.lock().expect("token journal lock"),
+        &config.reward_schedule,
+        &deed.actor_id,
+        &deed,
+        &metrics,
+    )
+    .expect("mint must post");
 
     info!(
         "Deed {} at {} minted {} CHURCH tokens",
@@ -62,8 +85,8 @@ fn main() {
     );
 
     let reducer = BioloadReducer::new(metrics.bioload_delta);
-    let extra_church = reducer.earn_church();
-    info!("BioloadReducer added {} bonus CHURCH", extra_church);
+    let extra_church = reducer.earn_church(&config.reward_schedule);
+    info!("BioloadReducer recommends {} bonus CHURCH (advisory)", extra_church);
 
     let hero = RepairHero { impact_score: 0.9 };
     let pwr = hero.grant_pwr();
@@ -1,5 +1,6 @@
 use std::io::{BufRead, BufReader, Write};
 use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 use log::{error, info};
@@ -7,8 +8,11 @@ use serde_json::json;
 
 use crate::compliance::validator::validate_deed;
 use crate::ledger::deed_event::{DeedEvent};
+use deed_schema::{RandomIds, SystemClock};
 use crate::ledger::metrics::BioloadMetrics;
-use crate::token::mint::mint_church;
+use crate::token::mint::{mint_church, post_church_mint};
+use token_journal::ceiling::gates::MINT;
+use token_journal::{CeilingBook, Journal, RewardSchedule};
 
 use super::types::{
     AutoChurchMintParams, AutoChurchMintResult, AutoChurchValidateParams,
@@ -18,14 +22,25 @@ use super::types::{
 
 /// Start a simple line-delimited JSON-RPC 2.0 TCP server.
 /// Each line is a full JSON-RPC request, response is a single line.
-pub fn start_rpc_server(addr: &str) -> std::io::Result<()> {
+/// `auto_church.mint_deed` posts the CHURCH `schedule` gives to `journal`,
+/// if it is within the `mint` allowance of the actor's debt ceiling in
+/// `ceilings`.
+pub fn start_rpc_server(
+    addr: &str,
+    journal: Arc<Mutex<Journal>>,
+    schedule: Arc<RewardSchedule>,
+    ceilings: Arc<Mutex<CeilingBook>>,
+) -> std::io::Result<()> {
     let listener = TcpListener::bind(addr)?;
     info!("Auto_Church RPC server listening on {}", addr);
 
     for stream in listener.incoming() {
         match stream {
             Ok(stream) => {
-                thread::spawn(|| handle_client(stream));
+                let journal = Arc::clone(&journal);
+                let schedule = Arc::clone(&schedule);
+                let ceilings = Arc::clone(&ceilings);
+                thread::spawn(move || handle_client(stream, &journal, &schedule, &ceilings));
             }
             Err(e) => {
                 error!("RPC accept error: {}", e);
@@ -36,7 +51,7 @@ pub fn start_rpc_server(addr: &str) -> std::io::Result<()> {
     Ok(())
 }
 
-fn handle_client(stream: TcpStream) {
+fn handle_client(stream: TcpStream, journal: &Mutex<Journal>, schedule: &RewardSchedule, ceilings: &Mutex<CeilingBook>) {
     let peer = stream.peer_addr().ok();
     info!("RPC client connected: {:?}", peer);
 
@@ -44,7 +59,7 @@ fn handle_client(stream: TcpStream) {
     for line in reader.lines() {
         match line {
             Ok(line) if !line.trim().is_empty() => {
-                let response_text = dispatch_request(&line);
+                let response_text = dispatch_request(&line, journal, schedule, ceilings);
                 if let Err(e) = writeln!(&mut &stream, "{}", response_text) {
                     error!("RPC write error: {}", e);
                     break;
@@ -61,11 +76,11 @@ fn handle_client(stream: TcpStream) {
     info!("RPC client disconnected: {:?}", peer);
 }
 
-fn dispatch_request(raw: &str) -> String {
+fn dispatch_request(raw: &str, journal: &Mutex<Journal>, schedule: &RewardSchedule, ceilings: &Mutex<CeilingBook>) -> String {
     let parsed: Result<JsonRpcRequest, _> = serde_json::from_str(raw);
     match parsed {
         Ok(req) => {
-            let resp = handle_rpc(req);
+            let resp = handle_rpc(req, journal, schedule, ceilings);
             serde_json::to_string(&resp).unwrap_or_else(|e| {
                 serde_json::to_string(&JsonRpcResponse {
                     jsonrpc: "2.0".to_string(),
@@ -94,7 +109,12 @@ fn dispatch_request(raw: &str) -> String {
     }
 }
 
-fn handle_rpc(req: JsonRpcRequest) -> JsonRpcResponse {
+fn handle_rpc(
+    req: JsonRpcRequest,
+    journal: &Mutex<Journal>,
+    schedule: &RewardSchedule,
+    ceilings: &Mutex<CeilingBook>,
+) -> JsonRpcResponse {
     match req.method.as_str() {
         // Auto_Church surface:
 
@@ -113,6 +133,8 @@ fn handle_rpc(req: JsonRpcRequest) -> JsonRpcResponse {
                         params.context_json,
                         params.ethics_flags,
                         params.life_harm_flag,
+                        &SystemClock,
+                        &RandomIds,
                     );
 
                     let metrics =
@@ -131,7 +153,51 @@ fn handle_rpc(req: JsonRpcRequest) -> JsonRpcResponse {
                         };
                     }
 
-                    let church_minted = mint_church(&deed, &metrics);
+                    // The mint is held to the actor's current debt ceiling.
+                    let requested = mint_church(schedule, &deed, &metrics) as f64;
+                    let allowed = match ceilings.lock() {
+                        Ok(book) => book.check_request(&deed.actor_id, MINT, requested).map_err(|e| e.to_string()),
+                        Err(_) => Err("debt ceiling lock poisoned".to_string()),
+                    };
+                    if let Err(e) = allowed {
+                        return JsonRpcResponse {
+                            jsonrpc: "2.0".to_string(),
+                            result: None,
+                            error: Some(JsonRpcError {
+                                code: 1003,
+                                message: "Mint over the debt ceiling allowance".to_string(),
+                                data: Some(json!({ "error": e })),
+                            }),
+                            id: req.id,
+                        };
+                    }
+
+                    let posted = match journal.lock() {
+                        Ok(mut journal) => post_church_mint(&mut journal, schedule, &deed.actor_id, &deed, &metrics)
+                            .map_err(|e| e.to_string()),
+                        Err(_) => Err("token journal lock poisoned".to_string()),
+                    };
+                    let church_minted = match posted {
+                        Ok(amount) => amount,
+                        Err(e) => {
+                            return JsonRpcResponse {
+                                jsonrpc: "2.0".to_string(),
+                                result: None,
+                                error: Some(JsonRpcError {
+                                    code: 1002,
+                                    message: "Token journal posting failed".to_string(),
+                                    data: Some(json!({ "error": e })),
+                                }),
+                                id: req.id,
+                            };
+                        }
+                    };
+
+                    // RPC deeds carry no signature or evidence, so they are
+                    // folded in unverified: only their consequences count.
+                    if let Ok(mut book) = ceilings.lock() {
+                        book.fold(&deed.actor_id, &deed, false);
+                    }
 
                     let payload = AutoChurchMintResult {
                         deed,
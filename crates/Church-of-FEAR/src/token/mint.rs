@@ -1,6 +1,37 @@
 use crate::ledger::deed_event::DeedEvent;
 use crate::ledger::metrics::BioloadMetrics;
+use crate::token::rewards::reward_breakdown;
+use token_journal::{Asset, Journal, JournalError, RewardBreakdown, RewardSchedule};
 
-pub fn mint_church(event: &DeedEvent, metrics: &BioloadMetrics) -> u64 {
-    event.compute_church_reward(metrics.bioload_delta)
+pub fn mint_church(schedule: &RewardSchedule, event: &DeedEvent, metrics: &BioloadMetrics) -> u64 {
+    event.compute_church_reward(schedule, metrics.bioload_delta)
+}
+
+/// Mint the CHURCH `schedule` gives `event` to `account`, through
+/// the same postings as `post_rewards`. Returns the amount minted, which is
+/// `mint_church`'s; nothing is posted for zero.
+pub fn post_church_mint(
+    journal: &mut Journal,
+    schedule: &RewardSchedule,
+    account: &str,
+    event: &DeedEvent,
+    metrics: &BioloadMetrics,
+) -> Result<u64, JournalError> {
+    let church = reward_breakdown(schedule, event, metrics).only(Asset::Church);
+    journal.post_rewards(account, &church, token_journal::deed_id(&event.event_id)?)?;
+    Ok(church.minted(Asset::Church))
+}
+
+/// Mint everything `schedule` gives `event` to `account` in one journal
+/// entry, and return the itemised breakdown.
+pub fn post_rewards(
+    journal: &mut Journal,
+    schedule: &RewardSchedule,
+    account: &str,
+    event: &DeedEvent,
+    metrics: &BioloadMetrics,
+) -> Result<RewardBreakdown, JournalError> {
+    let breakdown = reward_breakdown(schedule, event, metrics);
+    journal.post_rewards(account, &breakdown, token_journal::deed_id(&event.event_id)?)?;
+    Ok(breakdown)
 }
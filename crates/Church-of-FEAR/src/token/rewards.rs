@@ -1,10 +1,19 @@
 use crate::ledger::deed_event::DeedEvent;
 use crate::ledger::metrics::BioloadMetrics;
+use token_journal::schedule::rules::SAFE_DEED_TECH;
+use token_journal::{RewardBreakdown, RewardSchedule};
 
-pub fn compute_tech_reward(event: &DeedEvent, metrics: &BioloadMetrics) -> u64 {
-    if !event.life_harm_flag && event.ethics_flags.is_empty() && metrics.roh <= 0.3 {
-        10
-    } else {
-        0
-    }
+pub fn compute_tech_reward(schedule: &RewardSchedule, event: &DeedEvent, metrics: &BioloadMetrics) -> u64 {
+    let input = event.reward_input().with_roh(metrics.roh);
+    schedule.evaluate(&input).amount_of(SAFE_DEED_TECH)
+}
+
+/// Every reward `schedule` gives `event`, itemised.
+pub fn reward_breakdown(schedule: &RewardSchedule, event: &DeedEvent, metrics: &BioloadMetrics) -> RewardBreakdown {
+    let input = event
+        .reward_input()
+        .with_bioload_delta(metrics.bioload_delta)
+        .with_roh(metrics.roh)
+        .with_decay(metrics.decay);
+    schedule.evaluate(&input)
 }
@@ -1,5 +1,6 @@
 use church_of_fear::ledger::deed_event::DeedEvent;
 use church_of_fear::compliance::validator::validate_deed;
+use deed_schema::{RandomIds, SystemClock};
 
 #[test]
 fn compliant_deed_passes() {
@@ -13,6 +14,8 @@ fn compliant_deed_passes() {
         serde_json::json!({}),
         vec![],
         false,
+        &SystemClock,
+        &RandomIds,
     );
     assert!(validate_deed(&deed, 0.1, 0.2).is_ok());
 }
@@ -29,6 +32,8 @@ fn biophysical_violation_fails() {
         serde_json::json!({}),
         vec![],
         false,
+        &SystemClock,
+        &RandomIds,
     );
     assert!(validate_deed(&deed, 0.9, 1.5).is_err());
 }
@@ -1,4 +1,5 @@
 use church_of_fear::ledger::deed_event::{DeedEvent, validate_chain};
+use deed_schema::{RandomIds, SystemClock};
 
 #[test]
 fn chain_integrity_holds() {
@@ -12,6 +13,8 @@ fn chain_integrity_holds() {
         serde_json::json!({}),
         vec![],
         false,
+        &SystemClock,
+        &RandomIds,
     );
     let d2 = DeedEvent::new(
         d1.self_hash.clone(),
@@ -22,6 +25,8 @@ fn chain_integrity_holds() {
         serde_json::json!({}),
         vec![],
         false,
+        &SystemClock,
+        &RandomIds,
     );
 
     let chain = vec![genesis, d1, d2];
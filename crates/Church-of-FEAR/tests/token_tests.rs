@@ -1,6 +1,10 @@
+use church_of_fear::config::LedgerConfig;
 use church_of_fear::ledger::deed_event::DeedEvent;
 use church_of_fear::ledger::metrics::BioloadMetrics;
-use church_of_fear::token::mint::mint_church;
+use church_of_fear::token::mint::{mint_church, post_church_mint};
+use token_journal::{Asset, Journal, OnBreach, PowerRule, RewardSchedule};
+use deed_schema::{RandomIds, SystemClock};
+use uuid::Uuid;
 
 #[test]
 fn mint_for_ecological_negative_bioload() {
@@ -14,8 +18,76 @@ fn mint_for_ecological_negative_bioload() {
         serde_json::json!({}),
         vec![],
         false,
+        &SystemClock,
+        &RandomIds,
     );
     let metrics = BioloadMetrics::new(-0.5, 0.1, 0.2);
-    let amount = mint_church(&event, &metrics);
+    let amount = mint_church(RewardSchedule::builtin(), &event, &metrics);
     assert!(amount > 0);
 }
+
+#[test]
+fn minted_church_is_posted_against_the_deed() {
+    let genesis = DeedEvent::genesis();
+    let event = DeedEvent::new(
+        genesis.self_hash,
+        "actor".into(),
+        vec![],
+        "ecological_sustainability".into(),
+        vec![],
+        serde_json::json!({}),
+        vec![],
+        false,
+        &SystemClock,
+        &RandomIds,
+    );
+    let metrics = BioloadMetrics::new(-0.5, 0.1, 0.2);
+    let mut journal = Journal::new();
+    let amount = post_church_mint(&mut journal, RewardSchedule::builtin(), "actor", &event, &metrics).unwrap();
+    assert_eq!(amount, mint_church(RewardSchedule::builtin(), &event, &metrics));
+    assert_eq!(journal.held("actor", Asset::Church), amount);
+    assert_eq!(journal.supply(Asset::Church), i128::from(amount));
+    let deed_id = token_journal::deed_id(&event.event_id).unwrap();
+    assert_eq!(journal.entries_for_deed(&deed_id).len(), 1);
+}
+
+#[test]
+fn opened_journal_enforces_the_configured_power_rule() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = LedgerConfig { power_rule: PowerRule::new(2.0, OnBreach::Reject), ..LedgerConfig::default() };
+    let mut journal = config.open_journal(&dir.path().join("tokens.jsonl")).unwrap();
+    assert_eq!(journal.power_rule(), Some(&config.power_rule));
+
+    let deed_id = Uuid::new_v4();
+    journal.mint(Asset::Church, "actor", 5, deed_id).unwrap();
+    assert!(journal.mint(Asset::Pwr, "actor", 11, deed_id).is_err());
+    journal.mint(Asset::Pwr, "actor", 10, deed_id).unwrap();
+}
+
+#[test]
+fn configured_reward_schedule_sets_the_minted_amount() {
+    let config: LedgerConfig =
+        serde_json::from_str(r#"{"roh_max":0.3,"decay_max":1.0,"token_reward_factor":100,"repair_pwr_threshold":0.8}"#).unwrap();
+    assert_eq!(&config.reward_schedule, RewardSchedule::builtin());
+
+    let mut schedule = config.reward_schedule.clone();
+    let rule = schedule.rules.iter_mut().find(|rule| rule.name == "ecological_sustainability_reward").unwrap();
+    rule.amount.per_bioload_reduction = 10.0;
+    let genesis = DeedEvent::genesis();
+    let event = DeedEvent::new(
+        genesis.self_hash,
+        "actor".into(),
+        vec![],
+        "ecological_sustainability".into(),
+        vec![],
+        serde_json::json!({}),
+        vec![],
+        false,
+        &SystemClock,
+        &RandomIds,
+    );
+    let metrics = BioloadMetrics::new(-0.5, 0.1, 0.2);
+    let mut journal = Journal::new();
+    assert_eq!(post_church_mint(&mut journal, &schedule, "actor", &event, &metrics).unwrap(), 5);
+    assert_eq!(mint_church(&config.reward_schedule, &event, &metrics), 50);
+}
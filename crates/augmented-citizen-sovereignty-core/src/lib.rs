@@ -79,6 +79,70 @@ impl DeedEvent {
     }
 }
 
+/// Lossless conversion into the canonical deed-schema row. This shape has no
+/// targets or tags; the graph `node` it was logged against goes to `extensions`.
+impl TryFrom<DeedEvent> for deed_schema::DeedEvent {
+    type Error = deed_schema::SchemaError;
+
+    fn try_from(e: DeedEvent) -> Result<Self, Self::Error> {
+        let mut extensions = serde_json::Map::new();
+        extensions.insert("node".into(), serde_json::to_value(&e.node)?);
+        Ok(deed_schema::DeedEvent {
+            schema_version: deed_schema::UNVERSIONED,
+            event_id: deed_schema::parse_event_id(&e.event_id)?,
+            timestamp: e.timestamp,
+            timestamp_nanos: 0,
+            prev_hash: e.prev_hash,
+            self_hash: e.self_hash,
+            actor_id: e.actor_id,
+            target_ids: vec![],
+            deed_type: e.deed_type,
+            tags: vec![],
+            context_json: e.context_json,
+            ethics_flags: e.ethics_flags,
+            life_harm_flag: e.life_harm_flag,
+            extensions,
+            signature: None,
+            disclosures: deed_schema::Disclosures::new(),
+        })
+    }
+}
+
+/// Corrections are logged like any other deed (`log_event` with deed type
+/// `correction`); this shape has no targets or tags to supersede.
+impl deed_schema::CorrectableDeed for DeedEvent {
+    fn id(&self) -> String {
+        self.event_id.clone()
+    }
+
+    fn actor(&self) -> &str {
+        &self.actor_id
+    }
+
+    fn kind(&self) -> &str {
+        &self.deed_type
+    }
+
+    fn context(&self) -> &serde_json::Value {
+        &self.context_json
+    }
+
+    fn ethics_flags(&self) -> &[String] {
+        &self.ethics_flags
+    }
+
+    fn fields_mut(&mut self) -> deed_schema::DeedFieldsMut<'_> {
+        deed_schema::DeedFieldsMut {
+            deed_type: &mut self.deed_type,
+            target_ids: None,
+            tags: None,
+            context_json: &mut self.context_json,
+            ethics_flags: &mut self.ethics_flags,
+            life_harm_flag: &mut self.life_harm_flag,
+        }
+    }
+}
+
 pub struct SovereigntyCore {
     pub graph: DiGraph<Node, Edge>,
     pub reputation: ReputationVector,
@@ -93,9 +93,11 @@ impl ReputationEngine {
         let mut signed_trials = 0;
         let mut life_harm_flags = 0;
         let mut recovery_events = 0;
-        let mut total_events = core.deed_log.len();
+        // Corrected deeds count as corrected; the corrections themselves do not count.
+        let deeds = deed_schema::effective_view(&core.deed_log).deeds;
+        let mut total_events = deeds.len();
 
-        for deed in &core.deed_log {
+        for deed in &deeds {
             match deed.node {
                 Node::Did => did_bound = true,
                 Node::ScopeEeg | Node::ScopeBci => consent_ok = true,
@@ -3,14 +3,17 @@
 // Rust 1.85+, no unsafe, full Serde + SHA-256 chain
 
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use deed_schema::{append_synced, complete_len, recover_torn_tail, sync_parent_dir, Clock, IdGenerator};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sha2::{Digest, Sha256};
 use std::fs::{File, OpenOptions};
-use std::io::{self, BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
-use uuid::Uuid;
+use token_journal::schedule::rules::ECO_GRANT_RECOMMENDATION;
+use token_journal::ceiling::gates::GRANT;
+use token_journal::ceiling::GOOD_DEED_TAGS;
+use token_journal::{CeilingDeed, CeilingError, CeilingPolicy, DebtCeiling, RewardInput, RewardSchedule};
 
 /// Core DeedEvent - immutable moral ledger row. Exactly matches the schema in the Moral Ledger PDF.
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -33,7 +36,7 @@ impl DeedEvent {
     fn canonical_json(&self) -> Result<String> {
         let mut map = serde_json::Map::new();
         map.insert("event_id".to_string(), serde_json::to_value(&self.event_id)?);
-        map.insert("timestamp".to_string(), serde_json::to_value(&self.timestamp)?);
+        map.insert("timestamp".to_string(), serde_json::to_value(self.timestamp)?);
         map.insert("prev_hash".to_string(), serde_json::to_value(&self.prev_hash)?);
         map.insert("actor_id".to_string(), serde_json::to_value(&self.actor_id)?);
         map.insert("target_ids".to_string(), serde_json::to_value(&self.target_ids)?);
@@ -41,7 +44,7 @@ impl DeedEvent {
         map.insert("tags".to_string(), serde_json::to_value(&self.tags)?);
         map.insert("context_json".to_string(), self.context_json.clone());
         map.insert("ethics_flags".to_string(), serde_json::to_value(&self.ethics_flags)?);
-        map.insert("life_harm_flag".to_string(), serde_json::to_value(&self.life_harm_flag)?);
+        map.insert("life_harm_flag".to_string(), serde_json::to_value(self.life_harm_flag)?);
 
         // Sorted keys for deterministic canonical form
         let mut sorted: Vec<_> = map.into_iter().collect();
@@ -58,7 +61,9 @@ impl DeedEvent {
         Ok(format!("{:x}", hasher.finalize()))
     }
 
-    /// Create a new DeedEvent with correct self_hash (pure function)
+    /// Create a new DeedEvent with correct self_hash; `event_id` comes from
+    /// `ids` and `timestamp` from `clock` (pure function)
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         prev_hash: String,
         actor_id: String,
@@ -68,9 +73,11 @@ impl DeedEvent {
         context_json: Value,
         ethics_flags: Vec<String>,
         life_harm_flag: bool,
+        clock: &dyn Clock,
+        ids: &dyn IdGenerator,
     ) -> Result<Self> {
-        let event_id = Uuid::new_v4().to_string();
-        let timestamp = Utc::now().timestamp();
+        let event_id = ids.next_id().to_string();
+        let timestamp = clock.unix_seconds();
 
         let mut event = DeedEvent {
             event_id,
@@ -100,21 +107,60 @@ impl DeedEvent {
         (base - ethics_penalty + good_tags).clamp(0.0, 1.0)
     }
 
-    /// Advisory eco_grant recommendation in CHURCH-equivalent units (real NPO distribution logic)
-    pub fn eco_grant_recommendation(&self) -> f64 {
-        let mp = self.moral_position_score();
-        let base_grant = match self.deed_type.as_str() {
-            "ecological_sustainability" => 25.0,
-            "homelessness_relief" => 30.0,
-            "math_science_education" => 20.0,
-            _ => 10.0,
-        };
-        base_grant * mp * (1.0 + self.tags.len() as f64 * 0.05)
+    /// Advisory eco_grant recommendation in CHURCH-equivalent units (real NPO distribution logic),
+    /// per `schedule`'s `eco_grant_recommendation`, for an actor whose current debt ceiling is
+    /// `ceiling` (see `debt_ceiling`). Refused with `CeilingError::OverAllowance` if it is over
+    /// the ceiling's `grant` allowance under `policy`.
+    pub fn eco_grant_recommendation(
+        &self,
+        schedule: &RewardSchedule,
+        policy: &CeilingPolicy,
+        ceiling: &DebtCeiling,
+    ) -> std::result::Result<f64, CeilingError> {
+        let input = RewardInput::new(&self.deed_type, &self.tags, self.is_clean()).with_moral_position(self.moral_position_score());
+        let recommendation = schedule.evaluate(&input).value_of(ECO_GRANT_RECOMMENDATION);
+        ceiling.check_request(policy, GRANT, recommendation)?;
+        Ok(recommendation)
+    }
+}
+
+impl CeilingDeed for DeedEvent {
+    fn event_id(&self) -> String {
+        self.event_id.clone()
+    }
+
+    fn deed_type(&self) -> &str {
+        &self.deed_type
+    }
+
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    fn ethics_flags(&self) -> &[String] {
+        &self.ethics_flags
+    }
+
+    fn life_harm_flag(&self) -> bool {
+        self.life_harm_flag
+    }
+
+    /// Clean and typed or tagged with one of `GOOD_DEED_TAGS`; rows here name
+    /// the good deed in `deed_type`.
+    fn is_good_deed(&self) -> bool {
+        self.is_clean() && GOOD_DEED_TAGS.iter().any(|mark| self.is_marked(mark))
     }
 }
 
 /// Append a new event to .church-ledger.jsonl and return the new self_hash
 /// Pure observer - never touches capability or consent.
+///
+/// The record is one `write_all` followed by `sync_data`, so it is either
+/// fully on disk or the torn tail of the file, and a failed write is cut off
+/// before returning. A torn tail left by an earlier append is added to
+/// `<ledger>.torn` and cut off before writing, with the same
+/// `deed_schema::durable` helpers as the JSONL store in church_of_fear_ledger.
+#[allow(clippy::too_many_arguments)]
 pub fn append_deed_event<P: AsRef<Path>>(
     ledger_path: P,
     actor_id: String,
@@ -124,17 +170,28 @@ pub fn append_deed_event<P: AsRef<Path>>(
     context_json: Value,
     ethics_flags: Vec<String>,
     life_harm_flag: bool,
+    clock: &dyn Clock,
+    ids: &dyn IdGenerator,
 ) -> Result<String> {
     let path = ledger_path.as_ref();
-    let mut prev_hash = "0".repeat(64); // genesis
+    let created = !path.exists();
+    let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+    if created {
+        sync_parent_dir(path)?;
+    }
 
-    if path.exists() {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        if let Some(last_line) = reader.lines().last() {
-            let last_event: DeedEvent = serde_json::from_str(&last_line?)?;
-            prev_hash = last_event.self_hash;
-        }
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let complete = complete_len(&bytes);
+    recover_torn_tail(path, &file, &bytes)?;
+
+    let mut prev_hash = "0".repeat(64); // genesis
+    let last_line = bytes[..complete]
+        .split(|b| *b == b'\n')
+        .rfind(|line| !line.iter().all(u8::is_ascii_whitespace));
+    if let Some(last_line) = last_line {
+        let last_event: DeedEvent = serde_json::from_slice(last_line).context("parsing the last ledger record")?;
+        prev_hash = last_event.self_hash;
     }
 
     let event = DeedEvent::new(
@@ -146,14 +203,13 @@ pub fn append_deed_event<P: AsRef<Path>>(
         context_json,
         ethics_flags,
         life_harm_flag,
+        clock,
+        ids,
     )?;
 
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)?;
-
-    writeln!(file, "{}", serde_json::to_string(&event)?)?;
+    let mut line = serde_json::to_vec(&event)?;
+    line.push(b'\n');
+    append_synced(&mut file, &line)?;
     Ok(event.self_hash)
 }
 
@@ -179,14 +235,86 @@ pub fn validate_ledger<P: AsRef<Path>>(ledger_path: P) -> Result<bool> {
     Ok(true)
 }
 
+/// `actor_id`'s debt ceiling under `policy`, folded from the ledger in chain order, with
+/// every step. Rows here carry no signature, so `verified` decides which good deeds count,
+/// e.g. those whose evidence in `context_json` the caller has checked; no other good deed
+/// grows the ceiling.
+pub fn debt_ceiling<P: AsRef<Path>>(
+    ledger_path: P,
+    actor_id: &str,
+    policy: &CeilingPolicy,
+    verified: impl Fn(&DeedEvent) -> bool,
+) -> Result<DebtCeiling> {
+    let reader = BufReader::new(File::open(ledger_path)?);
+    let mut ceiling = DebtCeiling::new(policy);
+    let mut position = 0;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() { continue; }
+        let event: DeedEvent = serde_json::from_str(&line)?;
+        if event.actor_id == actor_id {
+            ceiling.apply(policy, position, &event, verified(&event));
+        }
+        position += 1;
+    }
+    Ok(ceiling)
+}
+
+/// Forensic audit: which line broke, why, and which later records still
+/// verify. See `deed_schema::audit`.
+pub fn audit_ledger<P: AsRef<Path>>(ledger_path: P) -> Result<deed_schema::LedgerAuditReport> {
+    let path = ledger_path.as_ref();
+    deed_schema::audit_ledger_file(path, Some(&"0".repeat(64)))
+        .with_context(|| format!("auditing {}", path.display()))
+}
+
+/// Keep the verified prefix in place and move everything after the first
+/// break to `<ledger>.quarantine-<unix secs>`. History is never rewritten.
+pub fn repair_ledger<P: AsRef<Path>>(ledger_path: P, clock: &dyn Clock) -> Result<deed_schema::RepairOutcome> {
+    let path = ledger_path.as_ref();
+    deed_schema::repair_ledger_file(path, Some(&"0".repeat(64)), clock)
+        .with_context(|| format!("repairing {}", path.display()))
+}
+
+/// Lossless conversion into the canonical schema (hashes copied verbatim).
+impl TryFrom<DeedEvent> for deed_schema::DeedEvent {
+    type Error = deed_schema::SchemaError;
+
+    fn try_from(e: DeedEvent) -> std::result::Result<Self, Self::Error> {
+        Ok(deed_schema::DeedEvent {
+            schema_version: deed_schema::UNVERSIONED,
+            event_id: deed_schema::parse_event_id(&e.event_id)?,
+            timestamp: e.timestamp,
+            timestamp_nanos: 0,
+            prev_hash: e.prev_hash,
+            self_hash: e.self_hash,
+            actor_id: e.actor_id,
+            target_ids: e.target_ids,
+            deed_type: e.deed_type,
+            tags: e.tags,
+            context_json: e.context_json,
+            ethics_flags: e.ethics_flags,
+            life_harm_flag: e.life_harm_flag,
+            extensions: serde_json::Map::new(),
+            signature: None,
+            disclosures: deed_schema::Disclosures::new(),
+        })
+    }
+}
+
 // Short-abbreviation system objects for CHURCH/POWER/TECH earning (real-world reusable)
 pub fn mp_score(deed: &DeedEvent) -> f64 { deed.moral_position_score() }
-pub fn eco_grant(deed: &DeedEvent) -> f64 { deed.eco_grant_recommendation() }
+pub fn eco_grant(deed: &DeedEvent, schedule: &RewardSchedule, policy: &CeilingPolicy, ceiling: &DebtCeiling) -> std::result::Result<f64, CeilingError> {
+    deed.eco_grant_recommendation(schedule, policy, ceiling)
+}
 pub fn nano_stable_hash(event: &DeedEvent) -> String { event.self_hash.clone() } // for bchainproof integration
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use deed_schema::{RandomIds, SystemClock};
+    use std::io::Write;
+    use token_journal::ceiling::Gate;
     #[test]
     fn test_append_and_validate() {
         let tmp = tempfile::NamedTempFile::new().unwrap();
@@ -199,8 +327,125 @@ mod tests {
             serde_json::json!({"project": "arizona-desert-restoration", "volunteers": 12}),
             vec![],
             false,
+            &SystemClock,
+            &RandomIds,
         ).unwrap();
         assert!(!hash1.is_empty());
         assert!(validate_ledger(tmp.path()).unwrap());
     }
+
+    #[test]
+    fn test_append_recovers_torn_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".church-ledger.jsonl");
+        let append = |lesson: u32| append_deed_event(
+            &path,
+            "user-xboxtj".to_string(),
+            vec![],
+            "math_science_education".to_string(),
+            vec![],
+            serde_json::json!({"lesson": lesson}),
+            vec![],
+            false,
+            &SystemClock,
+            &RandomIds,
+        );
+        append(0).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"event_id\":\"to").unwrap();
+        drop(file);
+
+        append(1).unwrap();
+        assert!(validate_ledger(&path).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+        assert_eq!(std::fs::read(dir.path().join(".church-ledger.jsonl.torn")).unwrap(), b"{\"event_id\":\"to\n");
+
+        // A later recovery keeps the earlier one's bytes.
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"event_id\":\"again").unwrap();
+        append(2).unwrap();
+        assert_eq!(std::fs::read(dir.path().join(".church-ledger.jsonl.torn")).unwrap(), b"{\"event_id\":\"to\n{\"event_id\":\"again\n");
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3);
+    }
+
+    #[test]
+    fn test_eco_grant_is_held_to_the_debt_ceiling() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        for (deed_type, harm) in [("homelessness_relief", false), ("river_dumping", true)] {
+            append_deed_event(
+                tmp.path(),
+                "user-xboxtj".to_string(),
+                vec![],
+                deed_type.to_string(),
+                vec![],
+                serde_json::json!({"meals_served": 45}),
+                vec![],
+                harm,
+                &SystemClock,
+                &RandomIds,
+            ).unwrap();
+        }
+        let policy = CeilingPolicy { gates: [(GRANT.to_string(), Gate { per_ceiling: 30.0, max: 500.0 })].into(), ..CeilingPolicy::default() };
+        let deed: DeedEvent = serde_json::from_str(std::fs::read_to_string(tmp.path()).unwrap().lines().next().unwrap()).unwrap();
+        let schedule = RewardSchedule::builtin();
+
+        // Unverified, the good deed does not offset the harm: 0.8 x 30 = 24 is under the recommendation.
+        let unverified = debt_ceiling(tmp.path(), "user-xboxtj", &policy, |_| false).unwrap();
+        assert!(matches!(
+            deed.eco_grant_recommendation(schedule, &policy, &unverified),
+            Err(CeilingError::OverAllowance { allowance, .. }) if (allowance - 24.0).abs() < 1e-9
+        ));
+        let verified = debt_ceiling(tmp.path(), "user-xboxtj", &policy, |e| e.deed_type == "homelessness_relief").unwrap();
+        assert!((verified.ceiling - 0.9).abs() < 1e-9);
+        assert!(deed.eco_grant_recommendation(schedule, &policy, &verified).unwrap() > 24.0);
+    }
+
+    #[test]
+    fn test_audit_and_repair() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        for i in 0..3 {
+            append_deed_event(
+                tmp.path(),
+                "user-xboxtj".to_string(),
+                vec![],
+                "math_science_education".to_string(),
+                vec![],
+                serde_json::json!({"lesson": i}),
+                vec![],
+                false,
+                &SystemClock,
+                &RandomIds,
+            ).unwrap();
+        }
+        let text = std::fs::read_to_string(tmp.path()).unwrap();
+        std::fs::write(tmp.path(), text.replacen("\"lesson\":1", "\"lesson\":7", 1)).unwrap();
+
+        let report = audit_ledger(tmp.path()).unwrap();
+        assert_eq!(report.first_break, Some(2));
+        assert!(!validate_ledger(tmp.path()).unwrap());
+
+        let outcome = repair_ledger(tmp.path(), &SystemClock).unwrap();
+        assert_eq!(outcome.kept_records, 1);
+        assert!(validate_ledger(tmp.path()).unwrap());
+        std::fs::remove_file(outcome.quarantine.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_into_canonical_schema() {
+        let event = DeedEvent::new(
+            "0".repeat(64),
+            "user-xboxtj".to_string(),
+            vec![],
+            "homelessness_relief".to_string(),
+            vec![],
+            serde_json::json!({"meals_served": 45}),
+            vec![],
+            false,
+            &SystemClock,
+            &RandomIds,
+        ).unwrap();
+        let canonical = deed_schema::DeedEvent::try_from(event.clone()).unwrap();
+        assert_eq!(canonical.event_id.to_string(), event.event_id);
+        assert_eq!(canonical.self_hash, event.self_hash);
+        assert_eq!(canonical.schema_version, deed_schema::UNVERSIONED);
+    }
 }
@@ -0,0 +1,298 @@
+//! Ledger forensics: a record-by-record audit of a JSONL ledger and a
+//! repair mode that never rewrites history.
+//!
+//! The audit works on raw lines, like `verify_chain_lines`, so it covers
+//! every legacy hash scheme. It does not stop at the first problem: each
+//! record is checked on its own (JSON, recomputed hash) and against its
+//! predecessor (`prev_hash`), so the report also shows which stretches after
+//! a break still hang together.
+//!
+//! Repair keeps the verified prefix byte for byte. The rest of the file is
+//! moved to a quarantine file next to it; nothing is re-hashed or re-linked.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::clock::Clock;
+use crate::durable::sync_parent_dir;
+use crate::disclosure::audit_record;
+use crate::error::SchemaError;
+use crate::hash::{detect_scheme, HashScheme};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditIssue {
+    /// The line is not valid UTF-8 / JSON.
+    BadJson { reason: String },
+    /// The stored `self_hash` matches no known scheme.
+    SelfHashMismatch { stored: Option<String> },
+    /// `prev_hash` does not name the previous record's hash.
+    PrevHashMismatch { expected: String, found: String },
+    /// A disclosed context field does not match its committed digest.
+    DisclosureMismatch { reason: String },
+    /// Bytes after the last newline: an append that never completed.
+    TruncatedTail { bytes: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordAudit {
+    pub line: usize,                        // 1-based line number
+    pub byte_offset: u64,
+    pub event_id: Option<String>,
+    pub scheme: Option<HashScheme>,
+    pub issues: Vec<AuditIssue>,
+    /// Concealed fields whose value has been redacted; not an issue.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redacted: Vec<String>,
+}
+
+impl RecordAudit {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Records `first_line..=last_line` whose hashes verify and link to each
+/// other (the first one's own `prev_hash` is not trusted).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistentRun {
+    pub first_line: usize,
+    pub last_line: usize,
+    pub records: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerAuditReport {
+    /// One entry per non-blank line, plus the torn tail if there is one.
+    pub records: Vec<RecordAudit>,
+    /// Records before the first problem.
+    pub verified_records: usize,
+    /// Length of the file prefix holding those records.
+    pub verified_bytes: u64,
+    /// Line of the first problem, `None` for an intact ledger.
+    pub first_break: Option<usize>,
+    /// Internally consistent stretches after `first_break`.
+    pub runs_after_break: Vec<ConsistentRun>,
+}
+
+impl LedgerAuditReport {
+    pub fn is_intact(&self) -> bool {
+        self.first_break.is_none()
+    }
+}
+
+/// Audit a whole ledger held in memory. `genesis` is the expected
+/// `prev_hash` of the first record; `None` accepts whatever it names (for
+/// auditing a slice of a longer chain).
+pub fn audit_ledger_bytes(bytes: &[u8], genesis: Option<&str>) -> LedgerAuditReport {
+    let complete = bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    let mut records = Vec::new();
+    // Hash the next record must link to; `None` when the previous line was unreadable.
+    let mut expected_prev: Option<String> = genesis.map(str::to_string);
+    let mut first_record = true;
+    let mut offset = 0u64;
+    let mut line_no = 0;
+
+    for raw in bytes[..complete].split_inclusive(|b| *b == b'\n') {
+        line_no += 1;
+        let start = offset;
+        offset += raw.len() as u64;
+        let Ok(line) = std::str::from_utf8(raw) else {
+            records.push(broken(line_no, start, AuditIssue::BadJson { reason: "invalid UTF-8".to_string() }));
+            expected_prev = None;
+            first_record = false;
+            continue;
+        };
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Value = match serde_json::from_str(line) {
+            Ok(record) => record,
+            Err(e) => {
+                records.push(broken(line_no, start, AuditIssue::BadJson { reason: e.to_string() }));
+                expected_prev = None;
+                first_record = false;
+                continue;
+            }
+        };
+        let field = |key: &str| record.get(key).and_then(Value::as_str).map(str::to_string);
+        let mut audit = RecordAudit {
+            line: line_no,
+            byte_offset: start,
+            event_id: field("event_id"),
+            scheme: None,
+            issues: vec![],
+            redacted: vec![],
+        };
+
+        let prev = field("prev_hash").unwrap_or_default();
+        match (&expected_prev, first_record) {
+            (Some(expected), _) if *expected != prev => {
+                audit.issues.push(AuditIssue::PrevHashMismatch { expected: expected.clone(), found: prev });
+            }
+            (None, false) => audit.issues.push(AuditIssue::PrevHashMismatch { expected: String::new(), found: prev }),
+            _ => {}
+        }
+        expected_prev = match detect_scheme(line) {
+            Ok(Some(check)) => {
+                audit.scheme = Some(check.scheme);
+                Some(check.computed)
+            }
+            _ => {
+                audit.issues.push(AuditIssue::SelfHashMismatch { stored: field("self_hash") });
+                field("self_hash")
+            }
+        };
+        let (redacted, disclosures) = audit_record(&record);
+        audit.redacted = redacted;
+        if let Err(e) = disclosures {
+            audit.issues.push(AuditIssue::DisclosureMismatch { reason: e.to_string() });
+        }
+        first_record = false;
+        records.push(audit);
+    }
+
+    if complete < bytes.len() {
+        line_no += 1;
+        let tail = AuditIssue::TruncatedTail { bytes: (bytes.len() - complete) as u64 };
+        records.push(broken(line_no, complete as u64, tail));
+    }
+
+    let verified_records = records.iter().take_while(|r| r.is_ok()).count();
+    let first_break = records.get(verified_records).map(|r| r.line);
+    let verified_bytes = records.get(verified_records).map_or(bytes.len() as u64, |r| r.byte_offset);
+    let runs_after_break = consistent_runs(&records[verified_records..]);
+    LedgerAuditReport { records, verified_records, verified_bytes, first_break, runs_after_break }
+}
+
+fn broken(line: usize, byte_offset: u64, issue: AuditIssue) -> RecordAudit {
+    RecordAudit { line, byte_offset, event_id: None, scheme: None, issues: vec![issue], redacted: vec![] }
+}
+
+fn consistent_runs(records: &[RecordAudit]) -> Vec<ConsistentRun> {
+    let mut runs: Vec<ConsistentRun> = Vec::new();
+    let mut open = false;
+    for record in records {
+        let hash_ok = record.scheme.is_some();
+        let linked = !record.issues.iter().any(|i| matches!(i, AuditIssue::PrevHashMismatch { .. }));
+        match runs.last_mut() {
+            Some(run) if open && hash_ok && linked => {
+                run.last_line = record.line;
+                run.records += 1;
+            }
+            _ if hash_ok => {
+                runs.push(ConsistentRun { first_line: record.line, last_line: record.line, records: 1 });
+                open = true;
+            }
+            _ => open = false,
+        }
+    }
+    runs
+}
+
+pub fn audit_ledger_file(path: &Path, genesis: Option<&str>) -> Result<LedgerAuditReport, SchemaError> {
+    Ok(audit_ledger_bytes(&std::fs::read(path)?, genesis))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepairOutcome {
+    pub report: LedgerAuditReport,
+    pub kept_records: usize,
+    /// Where the unverified suffix went; `None` when nothing needed repair.
+    pub quarantine: Option<PathBuf>,
+}
+
+/// Split a damaged ledger into its verified prefix (left in place, byte for
+/// byte) and a quarantined suffix (`<file>.quarantine-<unix secs>`, read
+/// from `clock`). The quarantine file is written and synced before the
+/// ledger is truncated.
+pub fn repair_ledger_file(path: &Path, genesis: Option<&str>, clock: &dyn Clock) -> Result<RepairOutcome, SchemaError> {
+    let bytes = std::fs::read(path)?;
+    let report = audit_ledger_bytes(&bytes, genesis);
+    let kept_records = report.verified_records;
+    if report.is_intact() {
+        return Ok(RepairOutcome { report, kept_records, quarantine: None });
+    }
+
+    let cut = report.verified_bytes as usize;
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".quarantine-{}", clock.unix_seconds()));
+    let quarantine = PathBuf::from(name);
+    let mut file = OpenOptions::new().write(true).create_new(true).open(&quarantine)?;
+    file.write_all(&bytes[cut..])?;
+    file.sync_all()?;
+
+    let ledger = OpenOptions::new().write(true).open(path)?;
+    ledger.set_len(cut as u64)?;
+    ledger.sync_all()?;
+    sync_parent_dir(&quarantine)?;
+    Ok(RepairOutcome { report, kept_records, quarantine: Some(quarantine) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeedEvent, FixedClock};
+
+    fn chain(n: usize) -> Vec<String> {
+        let mut prev = crate::genesis_hash();
+        (0..n)
+            .map(|i| {
+                let event = DeedEvent::new("a".into(), vec![], "x".into(), vec![], serde_json::json!({ "i": i }))
+                    .finalize_hash_chain(prev.clone())
+                    .unwrap();
+                prev = event.self_hash.clone();
+                serde_json::to_string(&event).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn intact_chain_has_no_break() {
+        let text = chain(3).join("\n") + "\n";
+        let report = audit_ledger_bytes(text.as_bytes(), Some(&crate::genesis_hash()));
+        assert!(report.is_intact());
+        assert_eq!(report.verified_records, 3);
+        assert_eq!(report.verified_bytes, text.len() as u64);
+    }
+
+    #[test]
+    fn pinpoints_break_and_later_consistent_records() {
+        let mut lines = chain(5);
+        lines[1] = lines[1].replace("\"i\":1", "\"i\":9");
+        let text = lines.join("\n") + "\n{\"event_id\":";
+        let report = audit_ledger_bytes(text.as_bytes(), Some(&crate::genesis_hash()));
+
+        assert_eq!(report.first_break, Some(2));
+        assert_eq!(report.verified_records, 1);
+        assert!(matches!(report.records[1].issues[..], [AuditIssue::SelfHashMismatch { .. }]));
+        // Line 3 still links to line 2's stored hash, so 3..=5 hang together.
+        assert_eq!(report.runs_after_break, vec![ConsistentRun { first_line: 3, last_line: 5, records: 3 }]);
+        assert!(matches!(report.records.last().unwrap().issues[..], [AuditIssue::TruncatedTail { bytes: 12 }]));
+    }
+
+    #[test]
+    fn repair_keeps_prefix_and_quarantines_the_rest() {
+        let dir = std::env::temp_dir().join(format!("deed-audit-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ledger.jsonl");
+        let mut lines = chain(3);
+        lines.insert(1, "not json".into());
+        let text = lines.join("\n") + "\n";
+        std::fs::write(&path, &text).unwrap();
+
+        let outcome = repair_ledger_file(&path, Some(&crate::genesis_hash()), &FixedClock::new(1_767_225_600)).unwrap();
+        assert_eq!(outcome.kept_records, 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), format!("{}\n", lines[0]));
+        let quarantine = outcome.quarantine.unwrap();
+        assert_eq!(quarantine, dir.join("ledger.jsonl.quarantine-1767225600"));
+        let quarantined = std::fs::read_to_string(quarantine).unwrap();
+        assert_eq!(quarantined, text[lines[0].len() + 1..]);
+        assert!(audit_ledger_file(&path, Some(&crate::genesis_hash())).unwrap().is_intact());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+//! RFC 8785 (JCS) style canonical JSON.
+//!
+//! - object members sorted by the UTF-16 code units of their keys
+//! - no insignificant whitespace
+//! - strings escaped exactly like ECMAScript `JSON.stringify`
+//! - floats printed with the ECMAScript `Number.prototype.toString` rules
+//!
+//! One deliberate deviation: integers that serde_json parsed as i64/u64 are
+//! printed exactly, even beyond 2^53 where a strict JCS implementation would
+//! first round them to an IEEE double.
+
+use serde_json::Value;
+
+/// Canonical form of any JSON value.
+pub fn to_canonical_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(value, &mut out);
+    out
+}
+
+fn write_value(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                out.push_str(&i.to_string());
+            } else if let Some(u) = n.as_u64() {
+                out.push_str(&u.to_string());
+            } else {
+                // serde_json never holds NaN / infinity
+                out.push_str(&format_f64(n.as_f64().unwrap_or(0.0)));
+            }
+        }
+        Value::String(s) => write_string(s, out),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_value(item, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// ECMAScript `Number.prototype.toString` for a finite double.
+pub fn format_f64(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string(); // also covers -0
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+
+    // Rust's `{:e}` gives the shortest round-tripping digits, e.g. "1.2345e-7".
+    let sci = format!("{:e}", value.abs());
+    let (mantissa, exp) = sci.split_once('e').expect("LowerExp always has an exponent");
+    let exp = exp.parse::<i32>().expect("LowerExp exponent is an integer");
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let digits = break_tie_to_even(value.abs(), digits, exp);
+    let k = digits.len() as i32;
+    let n = exp + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat((-n) as usize), digits)
+    } else {
+        let e = n - 1;
+        let e_sign = if e < 0 { '-' } else { '+' };
+        if k == 1 {
+            format!("{}e{}{}", digits, e_sign, e.abs())
+        } else {
+            format!("{}.{}e{}{}", &digits[..1], &digits[1..], e_sign, e.abs())
+        }
+    };
+    format!("{}{}", sign, body)
+}
+
+/// Rust rounds a halfway shortest candidate up; ECMAScript picks the even
+/// one. A tie is only possible when the exact binary value has exactly one
+/// more significant digit than the candidate and that digit is a 5.
+fn break_tie_to_even(abs: f64, digits: String, exp: i32) -> String {
+    let exact = format!("{:.800e}", abs);
+    let (mantissa, exact_exp) = exact.split_once('e').expect("LowerExp always has an exponent");
+    if exact_exp.parse::<i32>().ok() != Some(exp) {
+        return digits;
+    }
+    let exact_digits = mantissa.replace('.', "");
+    let exact_digits = exact_digits.trim_end_matches('0');
+    let k = digits.len();
+    if exact_digits.len() != k + 1 || !exact_digits.ends_with('5') {
+        return digits;
+    }
+    let down = &exact_digits[..k];
+    if down.ends_with(['0', '2', '4', '6', '8'])
+        && down != digits
+        && format!("0.{}e{}", down, exp + 1).parse::<f64>() == Ok(abs)
+    {
+        return down.to_string();
+    }
+    digits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sorts_keys_and_strips_whitespace() {
+        let v = json!({"b": [1, 2, {"z": null, "a": true}], "a": "x"});
+        assert_eq!(to_canonical_string(&v), r#"{"a":"x","b":[1,2,{"a":true,"z":null}]}"#);
+    }
+
+    #[test]
+    fn integral_floats_drop_the_fraction() {
+        assert_eq!(format_f64(12.0), "12");
+        assert_eq!(format_f64(-0.5), "-0.5");
+        assert_eq!(format_f64(1e21), "1e+21");
+        assert_eq!(format_f64(1e-7), "1e-7");
+    }
+}
@@ -0,0 +1,283 @@
+//! Correction deeds: append-only fixes to earlier deeds.
+//!
+//! A deed of type `correction` carries, in `context_json`,
+//!
+//! ```text
+//! { "corrects": "<event_id>", "set": { <field>: <new value>, … }, "reason": "…" }
+//! ```
+//!
+//! Only the fields in `CorrectedFields` can be superseded; identity, time
+//! and chain fields cannot. The original row is never touched, so both it
+//! and the correction stay auditable.
+//!
+//! Readers fold corrections in chain order (`effective_view`). A correction
+//! applies only if its target appears earlier in the chain, is not itself a
+//! correction, and has the same `actor_id`. Later corrections of the same
+//! field win.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::dispute::is_dispute_step;
+use crate::error::SchemaError;
+use crate::event::DeedEvent;
+
+pub const CORRECTION_DEED_TYPE: &str = "correction";
+
+/// The supersedable fields; `None` leaves a field as it is.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CorrectedFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deed_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_ids: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_json: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ethics_flags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub life_harm_flag: Option<bool>,
+}
+
+impl CorrectedFields {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Correction {
+    pub corrects: String,                   // event_id of the original deed
+    pub set: CorrectedFields,
+    pub reason: String,
+}
+
+impl Correction {
+    /// `context_json` of the correction deed.
+    pub fn to_context(&self) -> Value {
+        serde_json::to_value(self).expect("correction serializes")
+    }
+
+    /// The correction a deed carries; `None` if it is not a correction deed.
+    pub fn from_deed(deed_type: &str, context: &Value) -> Option<Result<Self, SchemaError>> {
+        if deed_type != CORRECTION_DEED_TYPE {
+            return None;
+        }
+        Some(serde_json::from_value::<Self>(context.clone()).map_err(SchemaError::from).and_then(|c| {
+            if c.set.is_empty() {
+                Err(SchemaError::InvalidCorrection("correction sets no field".to_string()))
+            } else if c.set.deed_type.as_deref().is_some_and(|t| t == CORRECTION_DEED_TYPE || is_dispute_step(t)) {
+                Err(SchemaError::InvalidCorrection("a deed cannot be turned into a correction or dispute step".to_string()))
+            } else {
+                Ok(c)
+            }
+        }))
+    }
+}
+
+/// The supersedable fields of one deed, borrowed for `supersede`. `None`
+/// for a field the deed's shape lacks.
+pub struct DeedFieldsMut<'a> {
+    pub deed_type: &'a mut String,
+    pub target_ids: Option<&'a mut Vec<String>>,
+    pub tags: Option<&'a mut Vec<String>>,
+    pub context_json: &'a mut Value,
+    pub ethics_flags: &'a mut Vec<String>,
+    pub life_harm_flag: &'a mut bool,
+}
+
+/// What `effective_view` needs from a ledger's own deed type. A shape only
+/// supplies its field accessors; superseding is the same for all of them.
+pub trait CorrectableDeed: Clone {
+    fn id(&self) -> String;
+    fn actor(&self) -> &str;
+    fn kind(&self) -> &str;
+    fn context(&self) -> &Value;
+    fn ethics_flags(&self) -> &[String];
+    fn fields_mut(&mut self) -> DeedFieldsMut<'_>;
+
+    /// Overwrite the fields `set` names; fields the shape lacks are ignored.
+    fn supersede(&mut self, set: &CorrectedFields) {
+        let fields = self.fields_mut();
+        if let Some(v) = &set.deed_type {
+            *fields.deed_type = v.clone();
+        }
+        if let (Some(v), Some(field)) = (&set.target_ids, fields.target_ids) {
+            *field = v.clone();
+        }
+        if let (Some(v), Some(field)) = (&set.tags, fields.tags) {
+            *field = v.clone();
+        }
+        if let Some(v) = &set.context_json {
+            *fields.context_json = v.clone();
+        }
+        if let Some(v) = &set.ethics_flags {
+            *fields.ethics_flags = v.clone();
+        }
+        if let Some(v) = set.life_harm_flag {
+            *fields.life_harm_flag = v;
+        }
+    }
+
+    fn correction(&self) -> Option<Result<Correction, SchemaError>> {
+        Correction::from_deed(self.kind(), self.context())
+    }
+}
+
+/// Check `correction` (carried by `by`) against the deed it names, in its
+/// current effective form.
+pub fn check_correction<D: CorrectableDeed>(by: &D, correction: &Correction, target: Option<&D>) -> Result<(), SchemaError> {
+    let Some(target) = target else {
+        return Err(SchemaError::InvalidCorrection(format!("{} is not an earlier deed", correction.corrects)));
+    };
+    if target.kind() == CORRECTION_DEED_TYPE || is_dispute_step(target.kind()) {
+        return Err(SchemaError::InvalidCorrection("corrections and dispute steps cannot be corrected".to_string()));
+    }
+    if target.actor() != by.actor() {
+        return Err(SchemaError::InvalidCorrection(format!(
+            "{} cannot correct a deed by {}",
+            by.actor(),
+            target.actor()
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectedCorrection {
+    pub correction_id: String,
+    pub reason: String,
+}
+
+/// A chain as readers should see it.
+#[derive(Debug, Clone)]
+pub struct EffectiveView<D> {
+    /// Every deed that is not a correction, in chain order, corrected.
+    pub deeds: Vec<D>,
+    /// Original event_id -> ids of the corrections applied to it, in order.
+    pub corrected_by: BTreeMap<String, Vec<String>>,
+    /// Correction deeds that had no effect.
+    pub rejected: Vec<RejectedCorrection>,
+}
+
+pub fn effective_view<'a, D, I>(chain: I) -> EffectiveView<D>
+where
+    D: CorrectableDeed + 'a,
+    I: IntoIterator<Item = &'a D>,
+{
+    let mut view = EffectiveView { deeds: Vec::new(), corrected_by: BTreeMap::new(), rejected: Vec::new() };
+    let mut position: HashMap<String, usize> = HashMap::new();
+    for deed in chain {
+        let Some(correction) = deed.correction() else {
+            position.insert(deed.id(), view.deeds.len());
+            view.deeds.push(deed.clone());
+            continue;
+        };
+        let applied = correction.and_then(|c| {
+            let idx = position.get(&c.corrects).copied();
+            check_correction(deed, &c, idx.map(|i| &view.deeds[i]))?;
+            Ok((idx.expect("checked above"), c))
+        });
+        match applied {
+            Ok((idx, c)) => {
+                view.deeds[idx].supersede(&c.set);
+                view.corrected_by.entry(c.corrects).or_default().push(deed.id());
+            }
+            Err(e) => view.rejected.push(RejectedCorrection { correction_id: deed.id(), reason: e.to_string() }),
+        }
+    }
+    view
+}
+
+impl CorrectableDeed for DeedEvent {
+    fn id(&self) -> String {
+        self.event_id.to_string()
+    }
+
+    fn actor(&self) -> &str {
+        &self.actor_id
+    }
+
+    fn kind(&self) -> &str {
+        &self.deed_type
+    }
+
+    fn context(&self) -> &Value {
+        &self.context_json
+    }
+
+    fn ethics_flags(&self) -> &[String] {
+        &self.ethics_flags
+    }
+
+    fn fields_mut(&mut self) -> DeedFieldsMut<'_> {
+        DeedFieldsMut {
+            deed_type: &mut self.deed_type,
+            target_ids: Some(&mut self.target_ids),
+            tags: Some(&mut self.tags),
+            context_json: &mut self.context_json,
+            ethics_flags: &mut self.ethics_flags,
+            life_harm_flag: &mut self.life_harm_flag,
+        }
+    }
+}
+
+impl DeedEvent {
+    /// New, unchained correction of `corrects` by `actor_id`.
+    pub fn new_correction(actor_id: String, corrects: &str, set: CorrectedFields, reason: String) -> Self {
+        let correction = Correction { corrects: corrects.to_string(), set, reason };
+        DeedEvent::new(actor_id, vec![], CORRECTION_DEED_TYPE.to_string(), vec![], correction.to_context())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn deed(actor: &str, deed_type: &str) -> DeedEvent {
+        DeedEvent::new(actor.into(), vec![], deed_type.into(), vec![], json!({}))
+    }
+
+    #[test]
+    fn corrections_fold_in_chain_order() {
+        let original = deed("alice", "tree_planting");
+        let other = deed("bob", "math_science_education");
+        let fix_type = DeedEvent::new_correction(
+            "alice".into(),
+            &original.event_id.to_string(),
+            CorrectedFields { deed_type: Some("ecological_sustainability".into()), ..Default::default() },
+            "wrong deed_type".into(),
+        );
+        let add_target = DeedEvent::new_correction(
+            "alice".into(),
+            &original.event_id.to_string(),
+            CorrectedFields { target_ids: Some(vec!["npo-7".into()]), ..Default::default() },
+            "missing target".into(),
+        );
+        let foreign = DeedEvent::new_correction(
+            "mallory".into(),
+            &other.event_id.to_string(),
+            CorrectedFields { life_harm_flag: Some(true), ..Default::default() },
+            "".into(),
+        );
+        let chain = [original.clone(), other.clone(), fix_type.clone(), foreign.clone(), add_target.clone()];
+
+        let view = effective_view(&chain);
+        assert_eq!(view.deeds.len(), 2);
+        assert_eq!(view.deeds[0].deed_type, "ecological_sustainability");
+        assert_eq!(view.deeds[0].target_ids, vec!["npo-7".to_string()]);
+        assert!(!view.deeds[1].life_harm_flag);
+        assert_eq!(view.corrected_by[&original.event_id.to_string()], vec![fix_type.id(), add_target.id()]);
+        assert_eq!(view.rejected.len(), 1);
+        assert_eq!(view.rejected[0].correction_id, foreign.id());
+
+        // A correction that precedes its target does nothing.
+        let early = [fix_type, original];
+        assert_eq!(effective_view(&early).deeds[0].deed_type, "tree_planting");
+    }
+}
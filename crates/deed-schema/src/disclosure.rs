@@ -0,0 +1,175 @@
+//! Field-level redaction of `context_json` via salted commitments.
+//!
+//! A concealed field is moved out of `context_json` into the row's
+//! `disclosures` and replaced by a digest under `context_json._sd`:
+//!
+//! ```text
+//! _sd.<field> = SHA-256(JCS([salt, field, value]))
+//! ```
+//!
+//! Every hash scheme and the actor signature leave `disclosures` out, so the
+//! chain only commits to the digest. Deleting a disclosure (redacting the
+//! field) keeps `self_hash` and the signature valid; the digest left behind
+//! without a disclosure is the visible record that a redaction happened.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::canonical::to_canonical_string;
+use crate::error::SchemaError;
+use crate::hash::sha256_hex;
+
+/// Key of the digest map inside `context_json`.
+pub const SD_KEY: &str = "_sd";
+/// Row field holding the disclosures; never part of a hash preimage.
+pub const DISCLOSURES_KEY: &str = "disclosures";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Disclosure {
+    pub salt: String,   // hex, 256 random bits
+    pub value: Value,
+}
+
+/// Disclosed values by field name.
+pub type Disclosures = BTreeMap<String, Disclosure>;
+
+/// Commitment to one field value.
+pub fn disclosure_digest(field: &str, disclosure: &Disclosure) -> String {
+    let preimage = Value::Array(vec![
+        Value::String(disclosure.salt.clone()),
+        Value::String(field.to_string()),
+        disclosure.value.clone(),
+    ]);
+    sha256_hex(to_canonical_string(&preimage).as_bytes())
+}
+
+fn fresh_salt() -> String {
+    let mut salt = Uuid::new_v4().as_bytes().to_vec();
+    salt.extend_from_slice(Uuid::new_v4().as_bytes());
+    hex::encode(salt)
+}
+
+fn digests(context: &Value) -> Result<Option<&Map<String, Value>>, SchemaError> {
+    match context.get(SD_KEY) {
+        None => Ok(None),
+        Some(Value::Object(map)) => Ok(Some(map)),
+        Some(_) => Err(SchemaError::InvalidDisclosure(format!("context_json.{} is not an object", SD_KEY))),
+    }
+}
+
+/// Move `fields` of `context` into `disclosures`, leaving salted digests.
+/// Must happen before the deed is signed and chained.
+pub fn conceal_fields(context: &mut Value, fields: &[&str], disclosures: &mut Disclosures) -> Result<(), SchemaError> {
+    let Value::Object(map) = context else {
+        return Err(SchemaError::InvalidDisclosure("context_json is not an object".to_string()));
+    };
+    for field in fields {
+        if *field == SD_KEY {
+            return Err(SchemaError::InvalidDisclosure(format!("{} cannot be concealed", SD_KEY)));
+        }
+        let value = map
+            .remove(*field)
+            .ok_or_else(|| SchemaError::InvalidDisclosure(format!("context_json has no field {}", field)))?;
+        let disclosure = Disclosure { salt: fresh_salt(), value };
+        let digest = disclosure_digest(field, &disclosure);
+        let Value::Object(sd) = map.entry(SD_KEY).or_insert_with(|| Value::Object(Map::new())) else {
+            return Err(SchemaError::InvalidDisclosure(format!("context_json.{} is not an object", SD_KEY)));
+        };
+        sd.insert(field.to_string(), Value::String(digest));
+        disclosures.insert(field.to_string(), disclosure);
+    }
+    Ok(())
+}
+
+/// Every disclosure must match the digest committed for its field.
+pub fn check_disclosures(context: &Value, disclosures: &Disclosures) -> Result<(), SchemaError> {
+    if disclosures.is_empty() {
+        return Ok(());
+    }
+    let digests = digests(context)?;
+    for (field, disclosure) in disclosures {
+        let committed = digests.and_then(|d| d.get(field)).and_then(Value::as_str);
+        if committed != Some(disclosure_digest(field, disclosure).as_str()) {
+            return Err(SchemaError::InvalidDisclosure(format!("{} does not match its commitment", field)));
+        }
+    }
+    Ok(())
+}
+
+/// `context` with the disclosed values put back and `_sd` removed; redacted
+/// fields are simply absent.
+pub fn reveal(context: &Value, disclosures: &Disclosures) -> Result<Value, SchemaError> {
+    check_disclosures(context, disclosures)?;
+    let mut revealed = context.clone();
+    if let Value::Object(map) = &mut revealed {
+        map.remove(SD_KEY);
+        for (field, disclosure) in disclosures {
+            map.insert(field.clone(), disclosure.value.clone());
+        }
+    }
+    Ok(revealed)
+}
+
+/// Committed fields whose disclosure has been deleted.
+pub fn redacted_fields(context: &Value, disclosures: &Disclosures) -> Vec<String> {
+    digests(context)
+        .ok()
+        .flatten()
+        .map(|d| d.keys().filter(|f| !disclosures.contains_key(*f)).cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Redacted fields and disclosure problems of a raw record, for the audit.
+pub fn audit_record(record: &Value) -> (Vec<String>, Result<(), SchemaError>) {
+    let context = record.get("context_json").cloned().unwrap_or(Value::Null);
+    let disclosures: Disclosures = match record.get(DISCLOSURES_KEY) {
+        None => Disclosures::new(),
+        Some(raw) => match serde_json::from_value(raw.clone()) {
+            Ok(d) => d,
+            Err(e) => return (vec![], Err(e.into())),
+        },
+    };
+    (redacted_fields(&context, &disclosures), check_disclosures(&context, &disclosures))
+}
+
+/// The raw line as it was hashed: struct-order ledgers serialize
+/// `disclosures` last, so it is cut off the end of the line exactly as
+/// serde wrote it.
+pub(crate) fn strip_from_line(line: &str) -> Result<Cow<'_, str>, SchemaError> {
+    let record: Value = serde_json::from_str(line)?;
+    let Some(disclosures) = record.get(DISCLOSURES_KEY) else {
+        return Ok(Cow::Borrowed(line));
+    };
+    let suffix = format!(",\"{}\":{}}}", DISCLOSURES_KEY, serde_json::to_string(disclosures)?);
+    Ok(match line.strip_suffix(&suffix) {
+        Some(prefix) => Cow::Owned(format!("{}}}", prefix)),
+        None => Cow::Borrowed(line),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn conceal_reveal_and_redact() {
+        let mut context = json!({ "crate": "ledger", "location": "Phoenix, AZ", "meals": 40 });
+        let mut disclosures = Disclosures::new();
+        conceal_fields(&mut context, &["location", "meals"], &mut disclosures).unwrap();
+        assert!(context.get("location").is_none());
+        assert_eq!(context[SD_KEY].as_object().unwrap().len(), 2);
+        assert_eq!(reveal(&context, &disclosures).unwrap(), json!({ "crate": "ledger", "location": "Phoenix, AZ", "meals": 40 }));
+
+        let mut forged = disclosures.clone();
+        forged.get_mut("meals").unwrap().value = json!(400);
+        assert!(check_disclosures(&context, &forged).is_err());
+
+        disclosures.remove("location");
+        assert_eq!(redacted_fields(&context, &disclosures), vec!["location".to_string()]);
+        assert_eq!(reveal(&context, &disclosures).unwrap(), json!({ "crate": "ledger", "meals": 40 }));
+    }
+}
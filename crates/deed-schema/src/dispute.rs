@@ -0,0 +1,419 @@
+//! Disputes: challenging a deed, reviewing it and appealing the outcome.
+//!
+//! Every step is an ordinary deed whose `context_json` carries the step:
+//!
+//! ```text
+//! dispute         { "disputes": "<event_id>", "grounds": "…", "evidence": [ … ] }
+//! dispute_vote    { "dispute": "<event_id>", "round": 0, "verdict": "clear" | "freeze_rewards"
+//!                   | "flag_ethics", "flags": [ … ], "reason": "…" }
+//! dispute_appeal  { "appeals": "<event_id>", "grounds": "…", "evidence": [ … ] }
+//! ```
+//!
+//! Only reviewers on the `ReviewPanel` may vote, once per round, and never
+//! on a dispute they are a party to. A round is decided as soon as one
+//! verdict has been cast by reviewers holding `quorum` distinct roles; later
+//! votes in that round have no effect. Either party may appeal a decided
+//! dispute once, which opens a new round; the earlier outcome stands until
+//! the new round is decided.
+//!
+//! Outcomes do not touch the disputed row. Readers apply the deed's
+//! `Standing` to its effective view: `flag_ethics` adds the flags to its
+//! `ethics_flags`, `freeze_rewards` adds `REWARDS_FROZEN_FLAG`, and `clear`
+//! adds nothing.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::correction::CorrectableDeed;
+use crate::error::SchemaError;
+
+pub const DISPUTE_DEED_TYPE: &str = "dispute";
+pub const DISPUTE_VOTE_DEED_TYPE: &str = "dispute_vote";
+pub const DISPUTE_APPEAL_DEED_TYPE: &str = "dispute_appeal";
+
+/// Marker a frozen deed carries in its effective `ethics_flags`.
+pub const REWARDS_FROZEN_FLAG: &str = "dispute:rewards_frozen";
+
+/// Roles that count towards a review quorum.
+pub const QUORUM_ROLES: [&str; 4] = ["Host", "OrganicCPUOwner", "Regulator", "SovereignKernel"];
+
+/// Appeals allowed per dispute.
+pub const MAX_APPEALS: u32 = 1;
+
+/// Whether `kind` is one of the dispute deed types.
+pub fn is_dispute_step(kind: &str) -> bool {
+    matches!(kind, DISPUTE_DEED_TYPE | DISPUTE_VOTE_DEED_TYPE | DISPUTE_APPEAL_DEED_TYPE)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisputeFiling {
+    pub disputes: String,                   // event_id of the contested deed
+    pub grounds: String,                    // e.g. "misleading impact declarations"
+    #[serde(default)]
+    pub evidence: Vec<String>,              // URLs or content hashes
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+pub enum Verdict {
+    Clear,
+    FreezeRewards,
+    FlagEthics { flags: Vec<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisputeVote {
+    pub dispute: String,                    // event_id of the dispute deed
+    pub round: u32,
+    #[serde(flatten)]
+    pub verdict: Verdict,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Appeal {
+    pub appeals: String,                    // event_id of the dispute deed
+    pub grounds: String,
+    #[serde(default)]
+    pub evidence: Vec<String>,
+}
+
+/// Reviewers by actor_id, each with one of `QUORUM_ROLES`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewPanel {
+    pub reviewers: BTreeMap<String, String>,
+    pub quorum: usize,                      // distinct roles needed to decide
+}
+
+impl ReviewPanel {
+    pub fn new(quorum: usize) -> Result<Self, SchemaError> {
+        if quorum == 0 || quorum > QUORUM_ROLES.len() {
+            return Err(SchemaError::InvalidDispute(format!("quorum must be 1..={}", QUORUM_ROLES.len())));
+        }
+        Ok(Self { reviewers: BTreeMap::new(), quorum })
+    }
+
+    pub fn with_reviewer(mut self, actor_id: &str, role: &str) -> Result<Self, SchemaError> {
+        if !QUORUM_ROLES.contains(&role) {
+            return Err(SchemaError::InvalidDispute(format!("{} is not a quorum role", role)));
+        }
+        self.reviewers.insert(actor_id.to_string(), role.to_string());
+        Ok(self)
+    }
+}
+
+/// What the outcomes of a deed's disputes add to its effective view.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Standing {
+    pub frozen: bool,
+    pub flags: Vec<String>,
+}
+
+impl Standing {
+    pub fn is_clear(&self) -> bool {
+        !self.frozen && self.flags.is_empty()
+    }
+
+    /// Add the flags (and the freeze marker) to `deed`'s `ethics_flags`.
+    pub fn apply<D: CorrectableDeed>(&self, deed: &mut D) {
+        if self.is_clear() {
+            return;
+        }
+        let mut flags = deed.ethics_flags().to_vec();
+        let added = self.flags.iter().map(String::as_str).chain(self.frozen.then_some(REWARDS_FROZEN_FLAG));
+        for flag in added {
+            if !flags.iter().any(|f| f == flag) {
+                flags.push(flag.to_string());
+            }
+        }
+        deed.supersede(&crate::CorrectedFields { ethics_flags: Some(flags), ..Default::default() });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisputeCase {
+    pub deed: String,                       // event_id of the contested deed
+    pub accused: String,
+    pub challenger: String,
+    pub grounds: String,
+    pub evidence: Vec<String>,
+    pub round: u32,
+    pub votes: BTreeMap<String, Verdict>,   // reviewer -> verdict, current round
+    pub decided: bool,                      // current round decided
+    pub outcome: Option<Verdict>,           // latest decided outcome
+    pub appeals: Vec<String>,               // event_ids of the appeal deeds
+}
+
+/// What a dispute deed did.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisputeStep {
+    Filed { dispute: String },
+    Voted { dispute: String },
+    Decided {
+        dispute: String,
+        deed: String,
+        accused: String,
+        previous: Option<Verdict>,
+        verdict: Verdict,
+        before: Standing,
+        after: Standing,
+    },
+    Appealed { dispute: String },
+}
+
+/// Dispute deeds applied in chain order. Serializable so a checkpoint can
+/// carry it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisputeBook {
+    panel: ReviewPanel,
+    cases: BTreeMap<String, DisputeCase>,
+    by_deed: HashMap<String, Vec<String>>,  // contested event_id -> dispute ids
+}
+
+enum Action {
+    File(DisputeFiling, String),            // filing, accused
+    Vote(DisputeVote),
+    Appeal(Appeal),
+}
+
+fn parse<T: serde::de::DeserializeOwned>(context: &Value) -> Result<T, SchemaError> {
+    serde_json::from_value(context.clone()).map_err(|e| SchemaError::InvalidDispute(e.to_string()))
+}
+
+impl DisputeBook {
+    pub fn new(panel: ReviewPanel) -> Self {
+        Self { panel, cases: BTreeMap::new(), by_deed: HashMap::new() }
+    }
+
+    pub fn panel(&self) -> &ReviewPanel {
+        &self.panel
+    }
+
+    pub fn case(&self, dispute_id: &str) -> Option<&DisputeCase> {
+        self.cases.get(dispute_id)
+    }
+
+    /// Disputes filed against the deed `event_id`, oldest first.
+    pub fn cases_against(&self, event_id: &str) -> Vec<(&str, &DisputeCase)> {
+        self.by_deed
+            .get(event_id)
+            .map(|ids| ids.iter().map(|id| (id.as_str(), &self.cases[id])).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn standing(&self, event_id: &str) -> Standing {
+        let mut standing = Standing::default();
+        for (_, case) in self.cases_against(event_id) {
+            match &case.outcome {
+                Some(Verdict::FreezeRewards) => standing.frozen = true,
+                Some(Verdict::FlagEthics { flags }) => {
+                    for flag in flags {
+                        if !standing.flags.contains(flag) {
+                            standing.flags.push(flag.clone());
+                        }
+                    }
+                }
+                Some(Verdict::Clear) | None => {}
+            }
+        }
+        standing
+    }
+
+    /// Check a dispute deed without applying it. `earlier` looks up a deed
+    /// that precedes `deed` in the chain by event_id.
+    pub fn check<D: CorrectableDeed>(&self, deed: &D, earlier: impl FnOnce(&str) -> Option<D>) -> Result<(), SchemaError> {
+        self.plan(deed, earlier).map(|_| ())
+    }
+
+    /// Apply `deed`. Ordinary deeds give `Ok(None)`; a dispute deed that
+    /// fails the rules gives an error and changes nothing.
+    pub fn apply<D: CorrectableDeed>(
+        &mut self,
+        deed: &D,
+        earlier: impl FnOnce(&str) -> Option<D>,
+    ) -> Result<Option<DisputeStep>, SchemaError> {
+        let Some(action) = self.plan(deed, earlier)? else {
+            return Ok(None);
+        };
+        let step = match action {
+            Action::File(filing, accused) => {
+                let id = deed.id();
+                self.by_deed.entry(filing.disputes.clone()).or_default().push(id.clone());
+                self.cases.insert(
+                    id.clone(),
+                    DisputeCase {
+                        deed: filing.disputes,
+                        accused,
+                        challenger: deed.actor().to_string(),
+                        grounds: filing.grounds,
+                        evidence: filing.evidence,
+                        round: 0,
+                        votes: BTreeMap::new(),
+                        decided: false,
+                        outcome: None,
+                        appeals: Vec::new(),
+                    },
+                );
+                DisputeStep::Filed { dispute: id }
+            }
+            Action::Vote(vote) => {
+                let case = self.cases.get_mut(&vote.dispute).expect("checked in plan");
+                case.votes.insert(deed.actor().to_string(), vote.verdict.clone());
+                let roles: BTreeSet<&str> = case
+                    .votes
+                    .iter()
+                    .filter(|(_, v)| **v == vote.verdict)
+                    .filter_map(|(reviewer, _)| self.panel.reviewers.get(reviewer).map(String::as_str))
+                    .collect();
+                if roles.len() < self.panel.quorum {
+                    return Ok(Some(DisputeStep::Voted { dispute: vote.dispute }));
+                }
+                let deed_id = case.deed.clone();
+                let before = self.standing(&deed_id);
+                let case = self.cases.get_mut(&vote.dispute).expect("checked in plan");
+                case.decided = true;
+                let previous = case.outcome.replace(vote.verdict.clone());
+                let accused = case.accused.clone();
+                DisputeStep::Decided {
+                    dispute: vote.dispute,
+                    after: self.standing(&deed_id),
+                    deed: deed_id,
+                    accused,
+                    previous,
+                    verdict: vote.verdict,
+                    before,
+                }
+            }
+            Action::Appeal(appeal) => {
+                let case = self.cases.get_mut(&appeal.appeals).expect("checked in plan");
+                case.round += 1;
+                case.votes.clear();
+                case.decided = false;
+                case.appeals.push(deed.id());
+                DisputeStep::Appealed { dispute: appeal.appeals }
+            }
+        };
+        Ok(Some(step))
+    }
+
+    fn plan<D: CorrectableDeed>(&self, deed: &D, earlier: impl FnOnce(&str) -> Option<D>) -> Result<Option<Action>, SchemaError> {
+        let invalid = |reason: String| Err(SchemaError::InvalidDispute(reason));
+        let actor = deed.actor();
+        match deed.kind() {
+            DISPUTE_DEED_TYPE => {
+                let filing: DisputeFiling = parse(deed.context())?;
+                let Some(target) = earlier(&filing.disputes) else {
+                    return invalid(format!("{} is not an earlier deed", filing.disputes));
+                };
+                if is_dispute_step(target.kind()) || target.correction().is_some() {
+                    return invalid("only ordinary deeds can be disputed".to_string());
+                }
+                if target.actor() == actor {
+                    return invalid(format!("{} cannot dispute their own deed", actor));
+                }
+                Ok(Some(Action::File(filing, target.actor().to_string())))
+            }
+            DISPUTE_VOTE_DEED_TYPE => {
+                let vote: DisputeVote = parse(deed.context())?;
+                let Some(case) = self.cases.get(&vote.dispute) else {
+                    return invalid(format!("no dispute {}", vote.dispute));
+                };
+                if !self.panel.reviewers.contains_key(actor) {
+                    return invalid(format!("{} is not on the review panel", actor));
+                }
+                if actor == case.accused || actor == case.challenger {
+                    return invalid(format!("{} is a party to dispute {}", actor, vote.dispute));
+                }
+                if vote.round != case.round || case.decided {
+                    return invalid(format!("round {} of dispute {} is not open", vote.round, vote.dispute));
+                }
+                if case.votes.contains_key(actor) {
+                    return invalid(format!("{} already voted in round {}", actor, case.round));
+                }
+                Ok(Some(Action::Vote(vote)))
+            }
+            DISPUTE_APPEAL_DEED_TYPE => {
+                let appeal: Appeal = parse(deed.context())?;
+                let Some(case) = self.cases.get(&appeal.appeals) else {
+                    return invalid(format!("no dispute {}", appeal.appeals));
+                };
+                if actor != case.accused && actor != case.challenger {
+                    return invalid(format!("{} is not a party to dispute {}", actor, appeal.appeals));
+                }
+                if !case.decided {
+                    return invalid(format!("dispute {} is not decided", appeal.appeals));
+                }
+                if case.appeals.len() as u32 >= MAX_APPEALS {
+                    return invalid(format!("dispute {} has no appeals left", appeal.appeals));
+                }
+                Ok(Some(Action::Appeal(appeal)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::DeedEvent;
+    use serde_json::json;
+
+    fn deed(actor: &str, deed_type: &str, context: Value) -> DeedEvent {
+        DeedEvent::new(actor.into(), vec![], deed_type.into(), vec![], context)
+    }
+
+    fn vote(reviewer: &str, dispute: &DeedEvent, round: u32, verdict: Value) -> DeedEvent {
+        let mut context = json!({ "dispute": dispute.event_id, "round": round });
+        context.as_object_mut().unwrap().extend(verdict.as_object().unwrap().clone());
+        deed(reviewer, DISPUTE_VOTE_DEED_TYPE, context)
+    }
+
+    #[test]
+    fn dispute_is_decided_by_a_quorum_of_roles_and_appealed_once() {
+        let panel = ReviewPanel::new(2)
+            .unwrap()
+            .with_reviewer("host", "Host")
+            .unwrap()
+            .with_reviewer("host-2", "Host")
+            .unwrap()
+            .with_reviewer("regulator", "Regulator")
+            .unwrap();
+        let mut book = DisputeBook::new(panel);
+        let planted = deed("alice", "ecological_sustainability", json!({ "trees": 10_000 }));
+        let dispute = deed("bob", DISPUTE_DEED_TYPE, json!({ "disputes": planted.event_id, "grounds": "simulation of deeds" }));
+        let lookup = |id: &str| (id == planted.event_id.to_string()).then(|| planted.clone());
+
+        assert!(book.apply(&deed("alice", DISPUTE_DEED_TYPE, dispute.context_json.clone()), lookup).is_err());
+        assert_eq!(book.apply(&dispute, lookup).unwrap(), Some(DisputeStep::Filed { dispute: dispute.id() }));
+
+        let flag = json!({ "verdict": "flag_ethics", "flags": ["misleading impact declarations"] });
+        assert!(book.apply(&vote("bob", &dispute, 0, flag.clone()), lookup).is_err());
+        book.apply(&vote("host", &dispute, 0, flag.clone()), lookup).unwrap();
+        // A second Host does not make a quorum of two roles.
+        book.apply(&vote("host-2", &dispute, 0, flag.clone()), lookup).unwrap();
+        assert!(book.standing(&planted.id()).is_clear());
+        match book.apply(&vote("regulator", &dispute, 0, flag), lookup).unwrap() {
+            Some(DisputeStep::Decided { before, after, .. }) => {
+                assert!(before.is_clear());
+                assert_eq!(after.flags, vec!["misleading impact declarations".to_string()]);
+            }
+            other => panic!("expected a decision, got {:?}", other),
+        }
+
+        let mut effective = planted.clone();
+        book.standing(&planted.id()).apply(&mut effective);
+        assert_eq!(effective.ethics_flags, vec!["misleading impact declarations".to_string()]);
+
+        let appeal = deed("alice", DISPUTE_APPEAL_DEED_TYPE, json!({ "appeals": dispute.event_id, "grounds": "drone survey" }));
+        book.apply(&appeal, lookup).unwrap();
+        assert!(book.apply(&appeal, lookup).is_err());
+        assert!(!book.standing(&planted.id()).is_clear());
+        book.apply(&vote("host", &dispute, 1, json!({ "verdict": "clear" })), lookup).unwrap();
+        book.apply(&vote("regulator", &dispute, 1, json!({ "verdict": "clear" })), lookup).unwrap();
+        assert!(book.standing(&planted.id()).is_clear());
+        assert_eq!(book.case(&dispute.id()).unwrap().outcome, Some(Verdict::Clear));
+    }
+}
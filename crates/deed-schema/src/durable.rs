@@ -0,0 +1,100 @@
+//! Crash-safe file primitives shared by every append-only JSONL ledger.
+//!
+//! An append is one `write_all` followed by `sync_data`. If either fails,
+//! the file is cut back to its length before the append, so the next append
+//! never lands after a partial line.
+//!
+//! Bytes after the last newline of a ledger are an append that never
+//! returned. `recover_torn_tail` adds them, as one line, to `<file>.torn`
+//! and syncs it before the ledger is truncated, so every earlier recovery is
+//! kept and none is lost to a crash in between.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Append `bytes` to `file` (opened for appending) and sync them, or leave
+/// the file as it was.
+pub fn append_synced(file: &mut File, bytes: &[u8]) -> io::Result<()> {
+    let len = file.metadata()?.len();
+    if let Err(e) = file.write_all(bytes).and_then(|_| file.sync_data()) {
+        file.set_len(len)?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Length of `bytes` up to and including the last newline.
+pub fn complete_len(bytes: &[u8]) -> usize {
+    bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1)
+}
+
+/// `<path>.torn`, where recovered tails of `path` are kept.
+pub fn torn_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".torn");
+    PathBuf::from(name)
+}
+
+/// Move the torn tail of the ledger at `path` aside and truncate `file`
+/// back to its last complete line. `bytes` is the file's current content.
+/// Returns the tail, `None` when the file ends in a newline.
+pub fn recover_torn_tail<'a>(path: &Path, file: &File, bytes: &'a [u8]) -> io::Result<Option<&'a [u8]>> {
+    let complete = complete_len(bytes);
+    if complete == bytes.len() {
+        return Ok(None);
+    }
+    let tail = &bytes[complete..];
+    let torn = torn_path(path);
+    let created = !torn.exists();
+    let mut out = OpenOptions::new().append(true).create(true).open(&torn)?;
+    append_synced(&mut out, &[tail, b"\n"].concat())?;
+    if created {
+        sync_parent_dir(&torn)?;
+    }
+    file.set_len(complete as u64)?;
+    file.sync_all()?;
+    Ok(Some(tail))
+}
+
+/// A new file's directory entry is only durable once the directory is synced.
+pub fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let parent = match path.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p,
+            _ => Path::new("."),
+        };
+        File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_torn_tail_is_kept_on_its_own_line() {
+        let dir = std::env::temp_dir().join(format!("deed-durable-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ledger.jsonl");
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(&path).unwrap();
+
+        append_synced(&mut file, b"{\"a\":1}\n{\"b\"").unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(recover_torn_tail(&path, &file, &bytes).unwrap(), Some(&b"{\"b\""[..]));
+        assert_eq!(std::fs::read(&path).unwrap(), b"{\"a\":1}\n");
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(recover_torn_tail(&path, &file, &bytes).unwrap(), None);
+
+        append_synced(&mut file, b"{\"c\":").unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        recover_torn_tail(&path, &file, &bytes).unwrap();
+        assert_eq!(std::fs::read(torn_path(&path)).unwrap(), b"{\"b\"\n{\"c\":\n");
+        assert_eq!(complete_len(b"x\ny"), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
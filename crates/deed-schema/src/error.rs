@@ -0,0 +1,31 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("event_id is not a canonical UUID: {0}")]
+    InvalidEventId(String),
+    #[error("timestamp out of range: {0}")]
+    TimestampOutOfRange(String),
+    #[error("unsupported schema_version {0}")]
+    UnsupportedVersion(u32),
+    #[error("invalid proof request: {0}")]
+    InvalidProofRequest(String),
+    #[error("deed is not signed")]
+    MissingSignature,
+    #[error("invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("invalid correction: {0}")]
+    InvalidCorrection(String),
+    #[error("invalid dispute step: {0}")]
+    InvalidDispute(String),
+    #[error("invalid cross-ledger reference: {0}")]
+    InvalidReference(String),
+    #[error("cannot upgrade row: {0}")]
+    InvalidUpgrade(String),
+    #[error("invalid disclosure: {0}")]
+    InvalidDisclosure(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
@@ -0,0 +1,151 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::clock::{Clock, IdGenerator};
+use crate::disclosure::Disclosures;
+use crate::error::SchemaError;
+use crate::signature::DeedSignature;
+use crate::SCHEMA_VERSION;
+
+/// Canonical DeedEvent – the one row shape every Church-of-FEAR ledger can
+/// read, verify and hand on to another component.
+///
+/// Legacy crates keep their own structs and convert into this one with
+/// `From` / `TryFrom`. Nothing is dropped on the way in: fields a legacy shape
+/// carries beyond the core schema land in `extensions`, and `prev_hash` /
+/// `self_hash` are copied verbatim so the original chain stays checkable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeedEvent {
+    pub schema_version: u32,
+    pub event_id: Uuid,
+    pub timestamp: i64,                     // Unix epoch seconds (UTC)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub timestamp_nanos: u32,               // sub-second part, only for DateTime sources
+    pub prev_hash: String,                  // hex-encoded hash of the previous row
+    pub self_hash: String,                  // hex-encoded hash of this row
+    pub actor_id: String,
+    pub target_ids: Vec<String>,
+    pub deed_type: String,
+    pub tags: Vec<String>,
+    pub context_json: Value,
+    pub ethics_flags: Vec<String>,
+    pub life_harm_flag: bool,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub extensions: Map<String, Value>,     // component-specific fields (fear_level, node, …)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<DeedSignature>,   // actor's ed25519 signature, see `signature`
+    #[serde(default, skip_serializing_if = "Disclosures::is_empty")]
+    pub disclosures: Disclosures,           // concealed context fields, see `disclosure`; never hashed
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+impl DeedEvent {
+    /// Fresh, unchained event. `prev_hash` / `self_hash` are filled in by the
+    /// ledger that appends it.
+    pub fn new(
+        actor_id: String,
+        target_ids: Vec<String>,
+        deed_type: String,
+        tags: Vec<String>,
+        context_json: Value,
+    ) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            event_id: Uuid::new_v4(),
+            timestamp: Utc::now().timestamp(),
+            timestamp_nanos: 0,
+            prev_hash: String::new(),
+            self_hash: String::new(),
+            actor_id,
+            target_ids,
+            deed_type,
+            tags,
+            context_json,
+            ethics_flags: vec![],
+            life_harm_flag: false,
+            extensions: Map::new(),
+            signature: None,
+            disclosures: Disclosures::new(),
+        }
+    }
+
+    /// Take `event_id` and the timestamp from `ids` and `clock` instead of
+    /// the system defaults; chain after any constructor.
+    pub fn stamped(mut self, clock: &dyn Clock, ids: &dyn IdGenerator) -> Self {
+        self.event_id = ids.next_id();
+        (self.timestamp, self.timestamp_nanos) = timestamp_from_datetime(&clock.now());
+        self
+    }
+
+    /// Timestamp as a chrono value (lossless for DateTime-based sources).
+    pub fn datetime(&self) -> Option<DateTime<Utc>> {
+        Utc.timestamp_opt(self.timestamp, self.timestamp_nanos).single()
+    }
+
+    /// Reject rows written by a newer schema than this crate understands.
+    pub fn check_version(&self) -> Result<(), SchemaError> {
+        if self.schema_version == 0 || self.schema_version > SCHEMA_VERSION {
+            return Err(SchemaError::UnsupportedVersion(self.schema_version));
+        }
+        Ok(())
+    }
+}
+
+/// Parse a legacy String event_id. Only the canonical lowercase hyphenated
+/// form is accepted so that `event_id.to_string()` gives back the exact
+/// original – anything else would not be a lossless conversion.
+pub fn parse_event_id(raw: &str) -> Result<Uuid, SchemaError> {
+    let id = Uuid::parse_str(raw).map_err(|_| SchemaError::InvalidEventId(raw.to_string()))?;
+    if id.to_string() != raw {
+        return Err(SchemaError::InvalidEventId(raw.to_string()));
+    }
+    Ok(id)
+}
+
+/// Legacy u64 timestamps (root ledger) must fit the canonical i64.
+pub fn timestamp_from_u64(secs: u64) -> Result<i64, SchemaError> {
+    i64::try_from(secs).map_err(|_| SchemaError::TimestampOutOfRange(secs.to_string()))
+}
+
+/// Split a DateTime into canonical seconds + nanos.
+pub fn timestamp_from_datetime(ts: &DateTime<Utc>) -> (i64, u32) {
+    (ts.timestamp(), ts.timestamp_subsec_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_id_must_be_canonical() {
+        let id = Uuid::new_v4();
+        assert_eq!(parse_event_id(&id.to_string()).unwrap(), id);
+        assert!(parse_event_id(&id.to_string().to_uppercase()).is_err());
+        assert!(parse_event_id("deed-42").is_err());
+    }
+
+    #[test]
+    fn datetime_round_trips() {
+        let ts = Utc.timestamp_opt(1_767_225_600, 123_456_789).unwrap();
+        let (secs, nanos) = timestamp_from_datetime(&ts);
+        let mut event = DeedEvent::new("a".into(), vec![], "x".into(), vec![], Value::Null);
+        event.timestamp = secs;
+        event.timestamp_nanos = nanos;
+        assert_eq!(event.datetime(), Some(ts));
+    }
+
+    #[test]
+    fn serde_omits_empty_optional_fields() {
+        let event = DeedEvent::new("a".into(), vec![], "x".into(), vec![], Value::Null);
+        let json = serde_json::to_value(&event).unwrap();
+        assert!(json.get("timestamp_nanos").is_none());
+        assert!(json.get("extensions").is_none());
+        let back: DeedEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(back, event);
+    }
+}
@@ -0,0 +1,218 @@
+//! Deed hashing: the canonical scheme plus recognisers for every legacy one.
+//!
+//! Legacy ledgers hashed their rows in five different ways. The verifier
+//! works on the raw JSONL line, because the struct-order schemes can only be
+//! reproduced from the exact bytes that were written.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use sha2::{Digest, Sha256};
+
+use crate::canonical::to_canonical_string;
+use crate::disclosure::{strip_from_line, DISCLOSURES_KEY};
+use crate::error::SchemaError;
+use crate::event::DeedEvent;
+
+/// How a row's `self_hash` was produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashScheme {
+    /// SHA-256 over the JCS form of the row without `self_hash` (deed-schema v1).
+    CanonicalV1,
+    /// SHA-256 over serde_json with sorted keys, `self_hash` left out (`church-ledger`).
+    SortedKeysV0,
+    /// SHA-256 over the struct as serialized with `self_hash` still empty
+    /// (`church_of_fear_ledger`, `Church-of-FEAR::hash_deed`, `fear_spiderweb_ledger`).
+    StructOrderBlankSelfHash,
+    /// Like `StructOrderBlankSelfHash`, but computed a second time by
+    /// `link_to_prev` while the unlinked hash was still in `self_hash`
+    /// (`microspace-rights-observer`, `augmented-citizen-sovereignty-core`).
+    StructOrderRelinked,
+    /// `self_hash` is `skip_serializing`, so the line itself is the preimage
+    /// (root `src/ledger`). The stored hash only survives in the next `prev_hash`.
+    StructOrderSkipSelfHash,
+}
+
+/// Result of checking one raw ledger line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordCheck {
+    pub scheme: HashScheme,
+    pub computed: String,
+    pub stored: Option<String>,
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hex::encode(hasher.finalize())
+}
+
+/// Canonical preimage of a row: JCS of the record with `self_hash` and
+/// `disclosures` removed.
+pub fn canonical_preimage(record: &Value) -> String {
+    let mut record = record.clone();
+    if let Value::Object(map) = &mut record {
+        map.remove("self_hash");
+        map.remove(DISCLOSURES_KEY);
+    }
+    to_canonical_string(&record)
+}
+
+impl DeedEvent {
+    /// CanonicalV1 hash of this row.
+    pub fn compute_self_hash(&self) -> Result<String, SchemaError> {
+        let record = serde_json::to_value(self)?;
+        Ok(sha256_hex(canonical_preimage(&record).as_bytes()))
+    }
+
+    /// Chain this row after `prev_hash` and seal it with its canonical hash.
+    pub fn finalize_hash_chain(mut self, prev_hash: String) -> Result<Self, SchemaError> {
+        self.prev_hash = prev_hash;
+        self.self_hash = self.compute_self_hash()?;
+        Ok(self)
+    }
+}
+
+/// Recompute the hash of a raw line under one specific scheme.
+pub fn recompute(line: &str, scheme: HashScheme) -> Result<String, SchemaError> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let stripped = match scheme {
+        HashScheme::CanonicalV1 | HashScheme::SortedKeysV0 => Cow::Borrowed(line),
+        _ => strip_from_line(line)?,
+    };
+    let line = stripped.as_ref();
+    match scheme {
+        HashScheme::CanonicalV1 => {
+            let record: Value = serde_json::from_str(line)?;
+            Ok(sha256_hex(canonical_preimage(&record).as_bytes()))
+        }
+        HashScheme::SortedKeysV0 => {
+            let mut record: Value = serde_json::from_str(line)?;
+            if let Value::Object(map) = &mut record {
+                map.remove("self_hash");
+                map.remove(DISCLOSURES_KEY);
+            }
+            // serde_json's default Map is a BTreeMap, i.e. the same sorted
+            // order church-ledger built by hand.
+            Ok(sha256_hex(serde_json::to_string(&record)?.as_bytes()))
+        }
+        HashScheme::StructOrderBlankSelfHash => {
+            let stored = stored_field(line, "self_hash")?;
+            let blanked = replace_field(line, "self_hash", &stored, "");
+            Ok(sha256_hex(blanked.as_bytes()))
+        }
+        HashScheme::StructOrderRelinked => {
+            let stored = stored_field(line, "self_hash")?;
+            let prev = stored_field(line, "prev_hash")?;
+            let unlinked = replace_field(line, "self_hash", &stored, "");
+            let unlinked = replace_field(&unlinked, "prev_hash", &prev, "");
+            let first = sha256_hex(unlinked.as_bytes());
+            let relinked = replace_field(line, "self_hash", &stored, &first);
+            Ok(sha256_hex(relinked.as_bytes()))
+        }
+        HashScheme::StructOrderSkipSelfHash => Ok(sha256_hex(line.as_bytes())),
+    }
+}
+
+/// Identify which scheme produced a line's `self_hash`.
+///
+/// Rows carrying `schema_version` are deed-schema rows and are only checked
+/// as `CanonicalV1`; rows without it as one of the legacy schemes.
+///
+/// Lines without a `self_hash` can only be the root-ledger scheme; their
+/// `computed` hash has to be matched against the next row's `prev_hash`.
+pub fn detect_scheme(line: &str) -> Result<Option<RecordCheck>, SchemaError> {
+    let record: Value = serde_json::from_str(line.trim_end_matches(['\r', '\n']))?;
+    let stored = record.get("self_hash").and_then(Value::as_str).map(str::to_string);
+
+    let Some(stored) = stored else {
+        return Ok(Some(RecordCheck {
+            scheme: HashScheme::StructOrderSkipSelfHash,
+            computed: recompute(line, HashScheme::StructOrderSkipSelfHash)?,
+            stored: None,
+        }));
+    };
+
+    // Canonical and sorted-key preimages coincide for float-free rows, so the
+    // presence of `schema_version` decides which of the two a row can be.
+    let sorted = if record.get("schema_version").is_some() {
+        HashScheme::CanonicalV1
+    } else {
+        HashScheme::SortedKeysV0
+    };
+    for scheme in [
+        sorted,
+        HashScheme::StructOrderBlankSelfHash,
+        HashScheme::StructOrderRelinked,
+    ] {
+        let computed = recompute(line, scheme)?;
+        if computed == stored {
+            return Ok(Some(RecordCheck { scheme, computed, stored: Some(stored) }));
+        }
+    }
+    Ok(None)
+}
+
+/// Walk a mixed-scheme JSONL chain starting at `genesis`. Returns the scheme
+/// of every line, or the index of the first line that does not verify.
+pub fn verify_chain_lines<'a, I>(lines: I, genesis: &str) -> Result<Vec<HashScheme>, usize>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut expected_prev = genesis.to_string();
+    let mut schemes = Vec::new();
+    for (idx, line) in lines.into_iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let check = detect_scheme(line).ok().flatten().ok_or(idx)?;
+        let prev = serde_json::from_str::<Value>(line)
+            .ok()
+            .and_then(|v| v.get("prev_hash").and_then(Value::as_str).map(str::to_string))
+            .ok_or(idx)?;
+        if prev != expected_prev {
+            return Err(idx);
+        }
+        expected_prev = check.computed;
+        schemes.push(check.scheme);
+    }
+    Ok(schemes)
+}
+
+fn stored_field(line: &str, key: &str) -> Result<String, SchemaError> {
+    let record: Value = serde_json::from_str(line)?;
+    Ok(record.get(key).and_then(Value::as_str).unwrap_or_default().to_string())
+}
+
+/// Swap a top-level string field's value in the raw serialized text. Hash
+/// values are hex, so the `"key":"value"` needle cannot need escaping.
+fn replace_field(line: &str, key: &str, from: &str, to: &str) -> String {
+    let needle = format!("\"{}\":\"{}\"", key, from);
+    let replacement = format!("\"{}\":\"{}\"", key, to);
+    line.replacen(&needle, &replacement, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_event_round_trips_through_detection() {
+        let event = DeedEvent::new("a".into(), vec![], "x".into(), vec![], serde_json::json!({"k": 1.0}))
+            .finalize_hash_chain(crate::genesis_hash())
+            .unwrap();
+        let line = serde_json::to_string(&event).unwrap();
+        let check = detect_scheme(&line).unwrap().unwrap();
+        assert_eq!(check.scheme, HashScheme::CanonicalV1);
+        assert_eq!(check.computed, event.self_hash);
+    }
+
+    #[test]
+    fn tampered_line_is_not_recognised() {
+        let event = DeedEvent::new("a".into(), vec![], "x".into(), vec![], Value::Null)
+            .finalize_hash_chain(crate::genesis_hash())
+            .unwrap();
+        let line = serde_json::to_string(&event).unwrap().replace("\"a\"", "\"b\"");
+        assert!(detect_scheme(&line).unwrap().is_none());
+    }
+}
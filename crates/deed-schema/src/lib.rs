@@ -0,0 +1,60 @@
+//! Canonical DeedEvent schema for every Church-of-FEAR / Tree-of-Life ledger.
+//!
+//! Each component used to define its own `DeedEvent` (String vs Uuid ids,
+//! i64 vs u64 vs DateTime timestamps). This crate is the single versioned
+//! shape they all convert into, so a ledger written by one component can be
+//! verified and consumed by every other one.
+//!
+//! Hashing is specified in `canonical` (RFC 8785 style JSON) and `hash`
+//! (the canonical scheme plus recognisers for the legacy ones). Test vectors
+//! shared with other implementations live in `tests/vectors/`.
+
+pub mod audit;
+pub mod canonical;
+pub mod clock;
+pub mod correction;
+pub mod disclosure;
+pub mod dispute;
+pub mod durable;
+pub mod error;
+pub mod event;
+pub mod hash;
+pub mod merkle;
+pub mod signature;
+pub mod upgrade;
+pub mod xref;
+
+pub use audit::{
+    audit_ledger_bytes, audit_ledger_file, repair_ledger_file, AuditIssue, ConsistentRun, LedgerAuditReport,
+    RecordAudit, RepairOutcome,
+};
+pub use clock::{Clock, FixedClock, IdGenerator, RandomIds, SeededIds, SystemClock};
+pub use correction::{
+    effective_view, CorrectableDeed, CorrectedFields, Correction, DeedFieldsMut, EffectiveView, RejectedCorrection,
+    CORRECTION_DEED_TYPE,
+};
+pub use disclosure::{conceal_fields, disclosure_digest, redacted_fields, reveal, Disclosure, Disclosures};
+pub use dispute::{
+    DisputeBook, DisputeCase, DisputeStep, ReviewPanel, Standing, Verdict, DISPUTE_APPEAL_DEED_TYPE,
+    DISPUTE_DEED_TYPE, DISPUTE_VOTE_DEED_TYPE, QUORUM_ROLES, REWARDS_FROZEN_FLAG,
+};
+pub use durable::{append_synced, complete_len, recover_torn_tail, sync_parent_dir, torn_path};
+pub use error::SchemaError;
+pub use event::{parse_event_id, timestamp_from_datetime, timestamp_from_u64, DeedEvent};
+pub use hash::{detect_scheme, verify_chain_lines, HashScheme, RecordCheck};
+pub use signature::{key_controller, DeedSignature};
+pub use merkle::{verify_consistency, verify_inclusion, ConsistencyProof, InclusionProof, MerkleTree, TreeHead};
+pub use upgrade::{record_version, UpgradeStep, Upgraders};
+pub use xref::{ledger_refs, Anchor, LedgerRef, ANCHOR_DEED_TYPE, LEDGER_REF_SCHEME};
+
+/// Current canonical schema version written into every `DeedEvent`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Version of rows written without a `schema_version`, which is every row of
+/// the per-crate ledgers. Conversions keep it; `Upgraders` lifts it.
+pub const UNVERSIONED: u32 = 0;
+
+/// prev_hash of the first row in any chain (64 hex zeros).
+pub fn genesis_hash() -> String {
+    "0".repeat(64)
+}
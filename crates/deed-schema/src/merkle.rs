@@ -0,0 +1,346 @@
+//! Certificate-Transparency style Merkle tree over a ledger's `self_hash`es
+//! (RFC 6962 / RFC 9162 §2.1).
+//!
+//! The hash chain proves order; the tree makes it cheap to prove membership.
+//! An actor can hand an NPO one deed, an `InclusionProof` and a signed
+//! `TreeHead` instead of the whole ledger, and an auditor holding an older
+//! head can check that a newer one only appended (`ConsistencyProof`).
+//!
+//! - leaf = SHA-256(0x00 || self_hash as ASCII hex)
+//! - node = SHA-256(0x01 || left || right)
+//! - empty tree root = SHA-256("")
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::SchemaError;
+
+pub type Hash = [u8; 32];
+
+pub fn leaf_hash(self_hash: &str) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(self_hash.as_bytes());
+    hasher.finalize().into()
+}
+
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Size and root of a tree at some point in the ledger's history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeHead {
+    pub tree_size: u64,
+    pub root: String, // hex
+}
+
+/// Audit path for the leaf at `leaf_index` in a tree of `tree_size` leaves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub leaf_index: u64,
+    pub tree_size: u64,
+    pub path: Vec<String>, // hex
+}
+
+/// Proof that the tree of `new_size` leaves extends the one of `old_size`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistencyProof {
+    pub old_size: u64,
+    pub new_size: u64,
+    pub path: Vec<String>, // hex
+}
+
+/// Leaf hashes of one ledger, in chain order. Serializes as a list of hex
+/// leaf hashes so it can ride along in index snapshots.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "Vec<String>", into = "Vec<String>")]
+pub struct MerkleTree {
+    leaves: Vec<Hash>,
+}
+
+impl MerkleTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_leaves(leaves: Vec<Hash>) -> Self {
+        Self { leaves }
+    }
+
+    pub fn push_self_hash(&mut self, self_hash: &str) -> Hash {
+        let leaf = leaf_hash(self_hash);
+        self.leaves.push(leaf);
+        leaf
+    }
+
+    pub fn leaves(&self) -> &[Hash] {
+        &self.leaves
+    }
+
+    pub fn len(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Head of the tree made of the first `tree_size` leaves.
+    pub fn head_at(&self, tree_size: u64) -> Result<TreeHead, SchemaError> {
+        self.check_size(tree_size)?;
+        Ok(TreeHead { tree_size, root: hex::encode(mth(&self.leaves[..tree_size as usize])) })
+    }
+
+    pub fn head(&self) -> TreeHead {
+        TreeHead { tree_size: self.len(), root: hex::encode(mth(&self.leaves)) }
+    }
+
+    pub fn inclusion_proof(&self, leaf_index: u64, tree_size: u64) -> Result<InclusionProof, SchemaError> {
+        self.check_size(tree_size)?;
+        if leaf_index >= tree_size {
+            return Err(SchemaError::InvalidProofRequest(format!(
+                "leaf {} is outside a tree of {}",
+                leaf_index, tree_size
+            )));
+        }
+        let path = audit_path(leaf_index as usize, &self.leaves[..tree_size as usize]);
+        Ok(InclusionProof { leaf_index, tree_size, path: path.iter().map(hex::encode).collect() })
+    }
+
+    pub fn consistency_proof(&self, old_size: u64, new_size: u64) -> Result<ConsistencyProof, SchemaError> {
+        self.check_size(new_size)?;
+        if old_size > new_size {
+            return Err(SchemaError::InvalidProofRequest(format!(
+                "old size {} is larger than new size {}",
+                old_size, new_size
+            )));
+        }
+        let path = if old_size == 0 || old_size == new_size {
+            vec![]
+        } else {
+            subproof(old_size as usize, &self.leaves[..new_size as usize], true)
+        };
+        Ok(ConsistencyProof { old_size, new_size, path: path.iter().map(hex::encode).collect() })
+    }
+
+    fn check_size(&self, tree_size: u64) -> Result<(), SchemaError> {
+        if tree_size > self.len() {
+            return Err(SchemaError::InvalidProofRequest(format!(
+                "tree has {} leaves, {} requested",
+                self.len(),
+                tree_size
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl From<MerkleTree> for Vec<String> {
+    fn from(tree: MerkleTree) -> Self {
+        tree.leaves.iter().map(hex::encode).collect()
+    }
+}
+
+impl TryFrom<Vec<String>> for MerkleTree {
+    type Error = String;
+
+    fn try_from(leaves: Vec<String>) -> Result<Self, Self::Error> {
+        decode(&leaves).map(MerkleTree::from_leaves).ok_or_else(|| "leaf is not a 32-byte hex hash".to_string())
+    }
+}
+
+/// Largest power of two strictly smaller than `n` (n > 1).
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k << 1 < n {
+        k <<= 1;
+    }
+    k
+}
+
+fn mth(leaves: &[Hash]) -> Hash {
+    match leaves.len() {
+        0 => Sha256::digest([]).into(),
+        1 => leaves[0],
+        n => {
+            let k = split_point(n);
+            node_hash(&mth(&leaves[..k]), &mth(&leaves[k..]))
+        }
+    }
+}
+
+fn audit_path(m: usize, leaves: &[Hash]) -> Vec<Hash> {
+    let n = leaves.len();
+    if n <= 1 {
+        return vec![];
+    }
+    let k = split_point(n);
+    if m < k {
+        let mut path = audit_path(m, &leaves[..k]);
+        path.push(mth(&leaves[k..]));
+        path
+    } else {
+        let mut path = audit_path(m - k, &leaves[k..]);
+        path.push(mth(&leaves[..k]));
+        path
+    }
+}
+
+fn subproof(m: usize, leaves: &[Hash], complete: bool) -> Vec<Hash> {
+    let n = leaves.len();
+    if m == n {
+        return if complete { vec![] } else { vec![mth(leaves)] };
+    }
+    let k = split_point(n);
+    if m <= k {
+        let mut path = subproof(m, &leaves[..k], complete);
+        path.push(mth(&leaves[k..]));
+        path
+    } else {
+        let mut path = subproof(m - k, &leaves[k..], false);
+        path.push(mth(&leaves[..k]));
+        path
+    }
+}
+
+fn decode(path: &[String]) -> Option<Vec<Hash>> {
+    path.iter().map(|h| hex::decode(h).ok()?.try_into().ok()).collect()
+}
+
+fn decode_root(root: &str) -> Option<Hash> {
+    hex::decode(root).ok()?.try_into().ok()
+}
+
+/// Standalone check that the row with `self_hash` is leaf `proof.leaf_index`
+/// of the tree summarised by `head` (RFC 9162 §2.1.3.2).
+pub fn verify_inclusion(self_hash: &str, proof: &InclusionProof, head: &TreeHead) -> bool {
+    let (Some(path), Some(root)) = (decode(&proof.path), decode_root(&head.root)) else {
+        return false;
+    };
+    if proof.tree_size != head.tree_size || proof.leaf_index >= proof.tree_size {
+        return false;
+    }
+    let (mut fn_, mut sn) = (proof.leaf_index, proof.tree_size - 1);
+    let mut r = leaf_hash(self_hash);
+    for p in &path {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            r = node_hash(p, &r);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            r = node_hash(&r, p);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sn == 0 && r == root
+}
+
+/// Standalone check that `new` is an append-only extension of `old`
+/// (RFC 9162 §2.1.4.2).
+pub fn verify_consistency(old: &TreeHead, new: &TreeHead, proof: &ConsistencyProof) -> bool {
+    let (Some(mut path), Some(old_root), Some(new_root)) =
+        (decode(&proof.path), decode_root(&old.root), decode_root(&new.root))
+    else {
+        return false;
+    };
+    if proof.old_size != old.tree_size || proof.new_size != new.tree_size || old.tree_size > new.tree_size {
+        return false;
+    }
+    if old.tree_size == new.tree_size {
+        return path.is_empty() && old_root == new_root;
+    }
+    if old.tree_size == 0 {
+        return path.is_empty();
+    }
+    if path.is_empty() {
+        return false;
+    }
+    if old.tree_size.is_power_of_two() {
+        path.insert(0, old_root);
+    }
+    let (mut fn_, mut sn) = (old.tree_size - 1, new.tree_size - 1);
+    while fn_ & 1 == 1 {
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    let (mut fr, mut sr) = (path[0], path[0]);
+    for c in &path[1..] {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            fr = node_hash(c, &fr);
+            sr = node_hash(c, &sr);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            sr = node_hash(&sr, c);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sn == 0 && fr == old_root && sr == new_root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(n: usize) -> MerkleTree {
+        let mut t = MerkleTree::new();
+        for i in 0..n {
+            t.push_self_hash(&format!("{:064x}", i));
+        }
+        t
+    }
+
+    #[test]
+    fn every_inclusion_proof_verifies() {
+        let t = tree(13);
+        for size in 1..=13 {
+            let head = t.head_at(size).unwrap();
+            for i in 0..size {
+                let proof = t.inclusion_proof(i, size).unwrap();
+                assert!(verify_inclusion(&format!("{:064x}", i), &proof, &head), "leaf {} size {}", i, size);
+                assert!(!verify_inclusion(&format!("{:064x}", i + 1), &proof, &head));
+            }
+        }
+    }
+
+    #[test]
+    fn every_consistency_proof_verifies() {
+        let t = tree(13);
+        for new in 1..=13 {
+            for old in 0..=new {
+                let proof = t.consistency_proof(old, new).unwrap();
+                let (old_head, new_head) = (t.head_at(old).unwrap(), t.head_at(new).unwrap());
+                assert!(verify_consistency(&old_head, &new_head, &proof), "{} -> {}", old, new);
+            }
+        }
+    }
+
+    #[test]
+    fn rewritten_history_is_not_consistent() {
+        let honest = tree(8);
+        let mut forked = tree(3);
+        forked.push_self_hash("forged");
+        for i in 4..8 {
+            forked.push_self_hash(&format!("{:064x}", i));
+        }
+        let proof = forked.consistency_proof(5, 8).unwrap();
+        assert!(!verify_consistency(&honest.head_at(5).unwrap(), &forked.head(), &proof));
+    }
+}
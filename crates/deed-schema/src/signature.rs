@@ -0,0 +1,113 @@
+//! Actor signatures over deeds.
+//!
+//! The actor signs before the deed is chained, so the signed bytes are the
+//! canonical JSON of the event without `prev_hash`, `self_hash` and
+//! `signature`. The chain hash then commits to the signature like any other
+//! field. Concealed fields are signed through their `_sd` digest, so a
+//! redaction (see `disclosure`) does not invalidate the signature.
+//!
+//! `key_id` is a DID URL (`did:method:id#fragment`, the same form as
+//! `DidSignature.key_id` in `neuro_eco_manifest`); the part before `#` must
+//! be the deed's `actor_id`.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::canonical::to_canonical_string;
+use crate::disclosure::DISCLOSURES_KEY;
+use crate::error::SchemaError;
+use crate::event::DeedEvent;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeedSignature {
+    pub key_id: String,     // DID URL of the signing key
+    pub signature: String,  // hex-encoded ed25519 signature
+}
+
+/// DID that controls `key_id` (everything before `#`), if it looks like one.
+pub fn key_controller(key_id: &str) -> Option<&str> {
+    let (did, fragment) = key_id.split_once('#')?;
+    let mut parts = did.splitn(3, ':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some("did"), Some(method), Some(id)) if !method.is_empty() && !id.is_empty() && !fragment.is_empty() => {
+            Some(did)
+        }
+        _ => None,
+    }
+}
+
+impl DeedEvent {
+    /// Bytes covered by the actor's signature.
+    pub fn signing_bytes(&self) -> Result<Vec<u8>, SchemaError> {
+        let mut record = serde_json::to_value(self)?;
+        if let Value::Object(map) = &mut record {
+            map.remove("prev_hash");
+            map.remove("self_hash");
+            map.remove("signature");
+            map.remove(DISCLOSURES_KEY);
+        }
+        Ok(to_canonical_string(&record).into_bytes())
+    }
+
+    /// Sign as `key_id`; fails if the key does not belong to `actor_id`.
+    pub fn sign(&mut self, key_id: &str, key: &SigningKey) -> Result<(), SchemaError> {
+        if key_controller(key_id) != Some(self.actor_id.as_str()) {
+            return Err(SchemaError::InvalidSignature(format!(
+                "key {} is not controlled by actor {}",
+                key_id, self.actor_id
+            )));
+        }
+        let signature = key.sign(&self.signing_bytes()?);
+        self.signature = Some(DeedSignature { key_id: key_id.to_string(), signature: hex::encode(signature.to_bytes()) });
+        Ok(())
+    }
+
+    /// Check the attached signature against `key`. Which key is acceptable
+    /// for `signature.key_id` is the caller's (key registry's) decision.
+    pub fn verify_signature(&self, key: &VerifyingKey) -> Result<(), SchemaError> {
+        let Some(sig) = &self.signature else {
+            return Err(SchemaError::MissingSignature);
+        };
+        let bytes: [u8; 64] = hex::decode(&sig.signature)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| SchemaError::InvalidSignature("signature is not 64 hex-encoded bytes".to_string()))?;
+        key.verify(&self.signing_bytes()?, &Signature::from_bytes(&bytes))
+            .map_err(|e| SchemaError::InvalidSignature(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "did:bostrom:alice";
+
+    fn deed() -> DeedEvent {
+        DeedEvent::new(ALICE.into(), vec![], "homelessness_relief".into(), vec![], serde_json::json!({ "meals": 40 }))
+    }
+
+    #[test]
+    fn signature_survives_chaining_but_not_edits() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let mut event = deed();
+        event.sign("did:bostrom:alice#key-1", &key).unwrap();
+        let sealed = event.finalize_hash_chain("0".repeat(64)).unwrap();
+        assert!(sealed.verify_signature(&key.verifying_key()).is_ok());
+
+        let mut edited = sealed.clone();
+        edited.context_json = serde_json::json!({ "meals": 400 });
+        assert!(edited.verify_signature(&key.verifying_key()).is_err());
+        let other = SigningKey::from_bytes(&[8; 32]);
+        assert!(sealed.verify_signature(&other.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn key_must_belong_to_actor() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        assert!(deed().sign("did:bostrom:mallory#key-1", &key).is_err());
+        assert!(deed().sign("alice#key-1", &key).is_err());
+        assert_eq!(key_controller("did:bostrom:alice#key-1"), Some(ALICE));
+    }
+}
@@ -0,0 +1,150 @@
+//! Schema upgraders: raw ledger rows from older versions to newer ones.
+//!
+//! Rows without a `schema_version` are version 0. Upgrading works on the
+//! JSON record rather than on a struct, because an old row may no longer
+//! deserialize into the current shape. Each registered step takes a row
+//! from version `n` to `n + 1`; `Upgraders::upgrade` chains them.
+//!
+//! Upgrading changes the row, so its hashes no longer hold. Ledgers re-chain
+//! upgraded rows into a new file (see `church_of_fear_ledger::migrate`).
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use crate::error::SchemaError;
+use crate::event::{parse_event_id, timestamp_from_datetime, timestamp_from_u64};
+use crate::SCHEMA_VERSION;
+
+/// Upgrade a row in place from version `n` to `n + 1`.
+pub type UpgradeStep = fn(&mut Map<String, Value>) -> Result<(), SchemaError>;
+
+/// `schema_version` of a raw row; 0 when absent.
+pub fn record_version(record: &Value) -> u32 {
+    record.get("schema_version").and_then(Value::as_u64).map_or(0, |v| v as u32)
+}
+
+/// Registered steps, keyed by the version they upgrade from.
+#[derive(Debug, Clone)]
+pub struct Upgraders {
+    steps: BTreeMap<u32, UpgradeStep>,
+}
+
+impl Default for Upgraders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Upgraders {
+    /// Every step up to `SCHEMA_VERSION`.
+    pub fn new() -> Self {
+        Self { steps: BTreeMap::from([(0, v0_to_v1 as UpgradeStep)]) }
+    }
+
+    /// Add or replace the step from version `from`.
+    pub fn with_step(mut self, from: u32, step: UpgradeStep) -> Self {
+        self.steps.insert(from, step);
+        self
+    }
+
+    /// Highest version every registered step chains up to.
+    pub fn latest(&self) -> u32 {
+        let mut version = 0;
+        while self.steps.contains_key(&version) {
+            version += 1;
+        }
+        version.max(SCHEMA_VERSION)
+    }
+
+    /// `record` upgraded to version `to`. Rows already at `to` come back
+    /// unchanged; rows from a newer version cannot be downgraded.
+    pub fn upgrade(&self, record: Value, to: u32) -> Result<Value, SchemaError> {
+        let from = record_version(&record);
+        if from > to {
+            return Err(SchemaError::UnsupportedVersion(from));
+        }
+        let Value::Object(mut map) = record else {
+            return Err(SchemaError::InvalidUpgrade("row is not a JSON object".to_string()));
+        };
+        for version in from..to {
+            let step = self.steps.get(&version).ok_or(SchemaError::UnsupportedVersion(version))?;
+            step(&mut map)?;
+            map.insert("schema_version".to_string(), json!(version + 1));
+        }
+        Ok(Value::Object(map))
+    }
+}
+
+/// v0 rows come from the per-crate ledgers: `timestamp` may be a u64 or an
+/// RFC 3339 string, and empty lists or flags may be missing.
+fn v0_to_v1(row: &mut Map<String, Value>) -> Result<(), SchemaError> {
+    match row.get("event_id") {
+        Some(Value::String(id)) => {
+            parse_event_id(id)?;
+        }
+        _ => return Err(SchemaError::InvalidUpgrade("row has no event_id".to_string())),
+    }
+    match row.get("timestamp").cloned() {
+        Some(Value::String(text)) => {
+            let ts = DateTime::parse_from_rfc3339(&text)
+                .map_err(|_| SchemaError::TimestampOutOfRange(text.clone()))?
+                .with_timezone(&Utc);
+            let (secs, nanos) = timestamp_from_datetime(&ts);
+            row.insert("timestamp".to_string(), json!(secs));
+            if nanos != 0 {
+                row.insert("timestamp_nanos".to_string(), json!(nanos));
+            }
+        }
+        Some(Value::Number(n)) if n.as_i64().is_some() => {}
+        Some(Value::Number(n)) => {
+            let secs = n.as_u64().ok_or_else(|| SchemaError::TimestampOutOfRange(n.to_string()))?;
+            row.insert("timestamp".to_string(), json!(timestamp_from_u64(secs)?));
+        }
+        _ => return Err(SchemaError::InvalidUpgrade("row has no timestamp".to_string())),
+    }
+    for key in ["target_ids", "tags", "ethics_flags"] {
+        row.entry(key).or_insert_with(|| json!([]));
+    }
+    row.entry("context_json").or_insert_with(|| json!({}));
+    row.entry("life_harm_flag").or_insert(json!(false));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::DeedEvent;
+
+    #[test]
+    fn v0_rows_upgrade_to_the_current_schema() {
+        let row = json!({
+            "event_id": "6f1c1a7e-3d1b-4c53-9f57-0b7e2c1d8a90",
+            "timestamp": "2025-01-01T00:00:00.5Z",
+            "prev_hash": "0".repeat(64),
+            "self_hash": "ab".repeat(32),
+            "actor_id": "alice",
+            "deed_type": "tree_planting",
+        });
+        let upgraders = Upgraders::new();
+        assert_eq!(record_version(&row), 0);
+        let upgraded = upgraders.upgrade(row, SCHEMA_VERSION).unwrap();
+        assert_eq!(record_version(&upgraded), 1);
+        let event: DeedEvent = serde_json::from_value(upgraded.clone()).unwrap();
+        assert_eq!((event.timestamp, event.timestamp_nanos), (1_735_689_600, 500_000_000));
+        assert!(event.tags.is_empty() && !event.life_harm_flag);
+        assert_eq!(upgraders.upgrade(upgraded.clone(), 1).unwrap(), upgraded);
+
+        assert!(matches!(upgraders.upgrade(upgraded.clone(), 2), Err(SchemaError::UnsupportedVersion(1))));
+        fn v1_to_v2(row: &mut Map<String, Value>) -> Result<(), SchemaError> {
+            row.remove("life_harm_flag");
+            Ok(())
+        }
+        let upgraders = upgraders.with_step(1, v1_to_v2);
+        assert_eq!(upgraders.latest(), 2);
+        let v2 = upgraders.upgrade(upgraded, 2).unwrap();
+        assert_eq!(record_version(&v2), 2);
+        assert!(v2.get("life_harm_flag").is_none());
+        assert!(matches!(upgraders.upgrade(v2, 1), Err(SchemaError::UnsupportedVersion(2))));
+    }
+}
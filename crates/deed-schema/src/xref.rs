@@ -0,0 +1,116 @@
+//! References to deeds on other ledgers, and anchors of their heads.
+//!
+//! A reference is written into `target_ids` as
+//!
+//! ```text
+//! ledger://<ledger_id>/<event_id>@<head>
+//! ```
+//!
+//! where `head` is the `self_hash` of the other ledger's newest deed when
+//! the reference was made, so the referenced deed is pinned to one chain.
+//! Plain target ids are left alone.
+//!
+//! An anchor deed (`ledger_anchor`) records another ledger's head as seen
+//! by this one: its `self_hash` and Merkle tree head. With both ledgers at
+//! hand, a reference checks out if its deed is in the other ledger's chain
+//! up to `head`, and that chain is a prefix of one anchored here.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::error::SchemaError;
+use crate::event::parse_event_id;
+use crate::merkle::TreeHead;
+
+pub const LEDGER_REF_SCHEME: &str = "ledger://";
+pub const ANCHOR_DEED_TYPE: &str = "ledger_anchor";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LedgerRef {
+    pub ledger_id: String,
+    pub event_id: Uuid,
+    pub head: String,                       // hex self_hash of the other ledger's head
+}
+
+impl fmt::Display for LedgerRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}/{}@{}", LEDGER_REF_SCHEME, self.ledger_id, self.event_id, self.head)
+    }
+}
+
+impl FromStr for LedgerRef {
+    type Err = SchemaError;
+
+    fn from_str(s: &str) -> Result<Self, SchemaError> {
+        let invalid = || SchemaError::InvalidReference(s.to_string());
+        let rest = s.strip_prefix(LEDGER_REF_SCHEME).ok_or_else(invalid)?;
+        let (path, head) = rest.rsplit_once('@').ok_or_else(invalid)?;
+        let (ledger_id, event_id) = path.rsplit_once('/').ok_or_else(invalid)?;
+        if !is_ledger_id(ledger_id) || head.len() != 64 || hex::decode(head).is_err() {
+            return Err(invalid());
+        }
+        Ok(Self { ledger_id: ledger_id.to_string(), event_id: parse_event_id(event_id)?, head: head.to_string() })
+    }
+}
+
+/// Ledger ids are non-empty and made of `[A-Za-z0-9._-]`.
+pub fn is_ledger_id(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'))
+}
+
+/// The cross-ledger references among `target_ids`. A target that uses the
+/// `ledger://` scheme but does not parse is an error.
+pub fn ledger_refs(target_ids: &[String]) -> Result<Vec<LedgerRef>, SchemaError> {
+    target_ids.iter().filter(|t| t.starts_with(LEDGER_REF_SCHEME)).map(|t| t.parse()).collect()
+}
+
+/// Context of a `ledger_anchor` deed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub ledger_id: String,
+    pub head: String,                       // self_hash at position tree.tree_size - 1
+    pub tree: TreeHead,
+}
+
+impl Anchor {
+    /// The anchor a deed carries; `None` if it is not an anchor deed.
+    pub fn from_deed(deed_type: &str, context: &serde_json::Value) -> Option<Result<Self, SchemaError>> {
+        if deed_type != ANCHOR_DEED_TYPE {
+            return None;
+        }
+        Some(serde_json::from_value::<Self>(context.clone()).map_err(SchemaError::from).and_then(|a| {
+            if !is_ledger_id(&a.ledger_id) || a.tree.tree_size == 0 {
+                Err(SchemaError::InvalidReference(format!("bad anchor of {}", a.ledger_id)))
+            } else {
+                Ok(a)
+            }
+        }))
+    }
+
+    pub fn to_context(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("anchor serializes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_round_trip_through_target_ids() {
+        let r = LedgerRef { ledger_id: "phoenix-east".into(), event_id: Uuid::new_v4(), head: "ab".repeat(32) };
+        let text = r.to_string();
+        assert_eq!(text.parse::<LedgerRef>().unwrap(), r);
+        assert_eq!(ledger_refs(&["npo-7".into(), text.clone()]).unwrap(), vec![r]);
+
+        for bad in [
+            "ledger://phoenix-east/not-a-uuid@00",
+            &text.replace("phoenix-east", "phoenix east"),
+            &text[..text.len() - 2],
+        ] {
+            assert!(ledger_refs(&[bad.to_string()]).is_err(), "{}", bad);
+        }
+    }
+}
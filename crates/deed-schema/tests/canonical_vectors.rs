@@ -0,0 +1,58 @@
+use deed_schema::canonical::{format_f64, to_canonical_string};
+use deed_schema::hash::{detect_scheme, recompute, verify_chain_lines, HashScheme};
+use serde_json::Value;
+
+const JCS: &str = include_str!("vectors/jcs.json");
+const DEED_HASHES: &str = include_str!("vectors/deed_hashes.json");
+
+fn scheme_named(name: &str) -> HashScheme {
+    serde_json::from_value(Value::String(name.to_string())).unwrap()
+}
+
+#[test]
+fn rfc8785_number_vectors() {
+    let vectors: Value = serde_json::from_str(JCS).unwrap();
+    for v in vectors["numbers"].as_array().unwrap() {
+        let bits = u64::from_str_radix(v["bits"].as_str().unwrap(), 16).unwrap();
+        assert_eq!(format_f64(f64::from_bits(bits)), v["canonical"].as_str().unwrap(), "bits {}", v["bits"]);
+    }
+}
+
+#[test]
+fn rfc8785_document_vectors() {
+    let vectors: Value = serde_json::from_str(JCS).unwrap();
+    for v in vectors["documents"].as_array().unwrap() {
+        let input: Value = serde_json::from_str(v["input"].as_str().unwrap()).unwrap();
+        assert_eq!(to_canonical_string(&input), v["canonical"].as_str().unwrap());
+    }
+}
+
+#[test]
+fn every_legacy_scheme_is_recognised() {
+    let vectors: Value = serde_json::from_str(DEED_HASHES).unwrap();
+    for record in vectors["records"].as_array().unwrap() {
+        let line = record["line"].as_str().unwrap();
+        let scheme = scheme_named(record["scheme"].as_str().unwrap());
+        let expected = record["self_hash"].as_str().unwrap();
+
+        assert_eq!(recompute(line, scheme).unwrap(), expected, "{}", record["source"]);
+        let check = detect_scheme(line).unwrap().expect("scheme not detected");
+        assert_eq!(check.scheme, scheme, "{}", record["source"]);
+        assert_eq!(check.computed, expected);
+    }
+}
+
+#[test]
+fn mixed_scheme_chain_verifies() {
+    let vectors: Value = serde_json::from_str(DEED_HASHES).unwrap();
+    let records = vectors["records"].as_array().unwrap();
+    let lines: Vec<&str> = records.iter().map(|r| r["line"].as_str().unwrap()).collect();
+    let schemes = verify_chain_lines(lines.iter().copied(), vectors["genesis"].as_str().unwrap()).unwrap();
+    let expected: Vec<HashScheme> = records.iter().map(|r| scheme_named(r["scheme"].as_str().unwrap())).collect();
+    assert_eq!(schemes, expected);
+
+    // Dropping a line breaks the link at the row that followed it.
+    let mut broken = lines.clone();
+    broken.remove(1);
+    assert_eq!(verify_chain_lines(broken.iter().copied(), vectors["genesis"].as_str().unwrap()), Err(1));
+}
@@ -35,3 +35,38 @@ impl DeedEvent {
         hex::encode(hasher.finalize())
     }
 }
+
+/// Lossless conversion into the canonical schema. The Tree-of-Life
+/// projections have no core field, so they travel in `extensions`.
+impl From<DeedEvent> for deed_schema::DeedEvent {
+    fn from(e: DeedEvent) -> Self {
+        let (timestamp, timestamp_nanos) = deed_schema::timestamp_from_datetime(&e.timestamp);
+        let mut extensions = serde_json::Map::new();
+        extensions.insert("fear_level".into(), e.fear_level.into());
+        extensions.insert("pain_level".into(), e.pain_level.into());
+        extensions.insert("decay".into(), e.decay.into());
+        extensions.insert("lifeforce".into(), e.lifeforce.into());
+        extensions.insert("calm_stable".into(), e.calm_stable.into());
+        extensions.insert("overloaded".into(), e.overloaded.into());
+        extensions.insert("recovery".into(), e.recovery.into());
+        extensions.insert("unfair_drain".into(), e.unfair_drain.into());
+        deed_schema::DeedEvent {
+            schema_version: deed_schema::UNVERSIONED,
+            event_id: e.event_id,
+            timestamp,
+            timestamp_nanos,
+            prev_hash: e.prev_hash,
+            self_hash: e.self_hash,
+            actor_id: e.actor_id,
+            target_ids: e.target_ids,
+            deed_type: e.deed_type,
+            tags: e.tags,
+            context_json: e.context_json,
+            ethics_flags: e.ethics_flags,
+            life_harm_flag: e.life_harm_flag,
+            extensions,
+            signature: None,
+            disclosures: deed_schema::Disclosures::new(),
+        }
+    }
+}
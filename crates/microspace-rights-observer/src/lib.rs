@@ -92,6 +92,32 @@ impl DeedEvent {
     }
 }
 
+/// Lossless conversion into the canonical deed-schema row.
+impl TryFrom<DeedEvent> for deed_schema::DeedEvent {
+    type Error = deed_schema::SchemaError;
+
+    fn try_from(e: DeedEvent) -> Result<Self, Self::Error> {
+        Ok(deed_schema::DeedEvent {
+            schema_version: deed_schema::UNVERSIONED,
+            event_id: deed_schema::parse_event_id(&e.event_id)?,
+            timestamp: e.timestamp,
+            timestamp_nanos: 0,
+            prev_hash: e.prev_hash,
+            self_hash: e.self_hash,
+            actor_id: e.actor_id,
+            target_ids: e.target_ids,
+            deed_type: e.deed_type,
+            tags: e.tags,
+            context_json: e.context_json,
+            ethics_flags: e.ethics_flags,
+            life_harm_flag: e.life_harm_flag,
+            extensions: serde_json::Map::new(),
+            signature: None,
+            disclosures: deed_schema::Disclosures::new(),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PredicateFlags {
     pub calm_stable: bool,
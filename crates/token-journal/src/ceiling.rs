@@ -0,0 +1,307 @@
+//! Debt ceilings: what an account may request, folded from its deeds.
+//!
+//! Every ledger folds the same way: consequences draw the ceiling down,
+//! verified good deeds grow it, and clean restorative deeds repay the
+//! drawdown. A request through a named `Gate` (a grant, a mint, a compute
+//! quota) is refused with `CeilingError::OverAllowance` when it is over
+//! what the account's current ceiling allows. Which deeds count as verified
+//! is up to the ledger: a valid signature, or evidence it holds.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Tag (or deed type) of a deed that repays earlier drawdown.
+pub const RESTORATIVE_TAG: &str = "restorative";
+/// Tag (or deed type) of a deed recording that an account overdrew a resource.
+pub const RESOURCE_OVERDRAW_TAG: &str = "resource_overdraw";
+/// Tags that make a clean deed a good deed.
+pub const GOOD_DEED_TAGS: [&str; 3] = ["ecological_sustainability", "homelessness_relief", "math_science_education"];
+
+/// Gates of the default policy.
+pub mod gates {
+    pub const GRANT: &str = "grant";
+    pub const MINT: &str = "mint";
+    pub const COMPUTE_QUOTA: &str = "compute_quota";
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CeilingError {
+    #[error("no gate named {0}")]
+    UnknownGate(String),
+    #[error("{gate} request of {requested} is over the allowance of {allowance}")]
+    OverAllowance { gate: String, requested: f64, allowance: f64 },
+    #[error("invalid ceiling policy: {field} is out of range")]
+    InvalidPolicy { field: String },
+}
+
+/// The parts of a deed a debt ceiling is folded from.
+pub trait CeilingDeed {
+    fn event_id(&self) -> String;
+    fn deed_type(&self) -> &str;
+    fn tags(&self) -> &[String];
+    fn ethics_flags(&self) -> &[String];
+    fn life_harm_flag(&self) -> bool;
+
+    /// No life harm and no ethics flags.
+    fn is_clean(&self) -> bool {
+        !self.life_harm_flag() && self.ethics_flags().is_empty()
+    }
+
+    /// Clean and tagged with one of `GOOD_DEED_TAGS`.
+    fn is_good_deed(&self) -> bool {
+        self.is_clean() && self.tags().iter().any(|t| GOOD_DEED_TAGS.contains(&t.as_str()))
+    }
+
+    /// Typed or tagged `mark`.
+    fn is_marked(&self, mark: &str) -> bool {
+        self.deed_type() == mark || self.tags().iter().any(|t| t == mark)
+    }
+}
+
+/// What an account may request through a gate: `per_ceiling` per unit of
+/// debt ceiling, never more than `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Gate {
+    pub per_ceiling: f64,
+    pub max: f64,
+}
+
+/// How an account's debt ceiling moves with its deeds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CeilingPolicy {
+    pub base: f64,                      // ceiling of an account with no history
+    pub cap: f64,                       // growth stops here
+    pub floor: f64,                     // drawdown stops lowering the ceiling here
+    pub per_good_deed: f64,             // per verified good deed
+    pub harm_penalty: f64,
+    pub overdraw_penalty: f64,
+    pub recovery_per_restorative: f64,  // drawdown repaid per clean restorative deed
+    #[serde(default)]
+    pub gates: BTreeMap<String, Gate>,
+}
+
+impl Default for CeilingPolicy {
+    fn default() -> Self {
+        Self {
+            base: 1.0,
+            cap: 2.0,
+            floor: 0.0,
+            per_good_deed: 0.1,
+            harm_penalty: 0.2,
+            overdraw_penalty: 0.1,
+            recovery_per_restorative: 0.05,
+            gates: BTreeMap::from([
+                (gates::GRANT.to_string(), Gate { per_ceiling: 100.0, max: 500.0 }),
+                (gates::MINT.to_string(), Gate { per_ceiling: 20.0, max: 60.0 }),
+                (gates::COMPUTE_QUOTA.to_string(), Gate { per_ceiling: 10.0, max: 40.0 }),
+            ]),
+        }
+    }
+}
+
+impl CeilingPolicy {
+    pub fn is_valid(&self) -> bool {
+        self.check().is_ok()
+    }
+
+    /// Rates and gates are finite and not negative, and
+    /// `floor <= base <= cap`; otherwise names the first field that is not.
+    pub fn check(&self) -> Result<(), CeilingError> {
+        let invalid = |field: String| Err(CeilingError::InvalidPolicy { field });
+        let rates = [
+            ("per_good_deed", self.per_good_deed),
+            ("harm_penalty", self.harm_penalty),
+            ("overdraw_penalty", self.overdraw_penalty),
+            ("recovery_per_restorative", self.recovery_per_restorative),
+        ];
+        let rates = rates.into_iter().map(|(field, n)| (field.to_string(), n));
+        let gates = self.gates.iter().flat_map(|(name, g)| {
+            [(format!("gates.{}.per_ceiling", name), g.per_ceiling), (format!("gates.{}.max", name), g.max)]
+        });
+        if let Some((field, _)) = rates.chain(gates).find(|(_, n)| !(n.is_finite() && *n >= 0.0)) {
+            return invalid(field);
+        }
+        if !self.base.is_finite() {
+            return invalid("base".to_string());
+        }
+        if !(self.floor.is_finite() && self.floor <= self.base) {
+            return invalid("floor".to_string());
+        }
+        if !(self.cap.is_finite() && self.base <= self.cap) {
+            return invalid("cap".to_string());
+        }
+        Ok(())
+    }
+
+    /// Most an account with `ceiling` may request through `gate`.
+    pub fn allowance(&self, gate: &str, ceiling: f64) -> Result<f64, CeilingError> {
+        let rule = self.gates.get(gate).ok_or_else(|| CeilingError::UnknownGate(gate.to_string()))?;
+        Ok((ceiling.max(0.0) * rule.per_ceiling).min(rule.max))
+    }
+
+    pub fn check_request(&self, gate: &str, ceiling: f64, requested: f64) -> Result<(), CeilingError> {
+        let allowance = self.allowance(gate, ceiling)?;
+        if requested > allowance {
+            return Err(CeilingError::OverAllowance { gate: gate.to_string(), requested, allowance });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cause {
+    GoodDeed,
+    Harm,
+    ResourceOverdraw,
+    Restorative,
+}
+
+/// One change to an account's ceiling and the deed that caused it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CeilingStep {
+    pub position: u64,
+    pub event_id: String,
+    pub cause: Cause,
+    pub change: f64,                    // to the ceiling; 0 when held at the cap or floor
+    pub ceiling: f64,                   // after the step
+}
+
+/// An account's debt ceiling with the history that explains it. Steps are
+/// recorded only when folding with `explain`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct DebtCeiling {
+    pub ceiling: f64,
+    pub growth: f64,                    // earned by verified good deeds, up to cap - base
+    pub drawdown: f64,                  // owed for consequences, less recovery
+    pub steps: Vec<CeilingStep>,
+}
+
+impl DebtCeiling {
+    pub fn new(policy: &CeilingPolicy) -> Self {
+        Self { ceiling: policy.base, ..Self::default() }
+    }
+
+    /// Fold one deed in, recording every step: consequences first, then
+    /// growth if the deed is `verified`, then recovery.
+    pub fn apply<D: CeilingDeed>(&mut self, policy: &CeilingPolicy, position: u64, deed: &D, verified: bool) {
+        self.fold(policy, position, deed, verified, true);
+    }
+
+    /// `apply`, recording steps only if `explain` is set.
+    pub fn fold<D: CeilingDeed>(&mut self, policy: &CeilingPolicy, position: u64, deed: &D, verified: bool, explain: bool) {
+        if deed.life_harm_flag() {
+            self.drawdown += policy.harm_penalty;
+            self.record(policy, position, deed, Cause::Harm, explain);
+        }
+        if deed.is_marked(RESOURCE_OVERDRAW_TAG) {
+            self.drawdown += policy.overdraw_penalty;
+            self.record(policy, position, deed, Cause::ResourceOverdraw, explain);
+        }
+        if verified && deed.is_good_deed() {
+            self.growth = (self.growth + policy.per_good_deed).min(policy.cap - policy.base);
+            self.record(policy, position, deed, Cause::GoodDeed, explain);
+        }
+        if deed.is_clean() && deed.is_marked(RESTORATIVE_TAG) && self.drawdown > 0.0 {
+            self.drawdown = (self.drawdown - policy.recovery_per_restorative).max(0.0);
+            self.record(policy, position, deed, Cause::Restorative, explain);
+        }
+    }
+
+    /// `policy.check_request` at this ceiling.
+    pub fn check_request(&self, policy: &CeilingPolicy, gate: &str, requested: f64) -> Result<(), CeilingError> {
+        policy.check_request(gate, self.ceiling, requested)
+    }
+
+    fn record<D: CeilingDeed>(&mut self, policy: &CeilingPolicy, position: u64, deed: &D, cause: Cause, explain: bool) {
+        let ceiling = (policy.base + self.growth - self.drawdown).clamp(policy.floor, policy.cap);
+        let change = ceiling - self.ceiling;
+        self.ceiling = ceiling;
+        if explain {
+            self.steps.push(CeilingStep { position, event_id: deed.event_id(), cause, change, ceiling });
+        }
+    }
+}
+
+/// The debt ceilings of many accounts under one policy, for a service that
+/// sees deeds as they arrive instead of reading them from a ledger.
+#[derive(Debug, Clone, Default)]
+pub struct CeilingBook {
+    policy: CeilingPolicy,
+    ceilings: BTreeMap<String, DebtCeiling>,
+    folded: u64,
+}
+
+impl CeilingBook {
+    /// Fails with `CeilingError::InvalidPolicy` if `policy` is not usable.
+    pub fn new(policy: CeilingPolicy) -> Result<Self, CeilingError> {
+        policy.check()?;
+        Ok(Self { policy, ceilings: BTreeMap::new(), folded: 0 })
+    }
+
+    pub fn policy(&self) -> &CeilingPolicy {
+        &self.policy
+    }
+
+    /// `account`'s ceiling, without steps; the policy's base if it has no deeds.
+    pub fn ceiling(&self, account: &str) -> DebtCeiling {
+        self.ceilings.get(account).cloned().unwrap_or_else(|| DebtCeiling::new(&self.policy))
+    }
+
+    pub fn check_request(&self, account: &str, gate: &str, requested: f64) -> Result<(), CeilingError> {
+        self.ceiling(account).check_request(&self.policy, gate, requested)
+    }
+
+    /// Fold `account`'s next deed in.
+    pub fn fold<D: CeilingDeed>(&mut self, account: &str, deed: &D, verified: bool) {
+        let ceiling = self.ceilings.entry(account.to_string()).or_insert_with(|| DebtCeiling::new(&self.policy));
+        ceiling.fold(&self.policy, self.folded, deed, verified, false);
+        self.folded += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Deed(&'static str, Vec<String>, bool);
+
+    impl CeilingDeed for Deed {
+        fn event_id(&self) -> String {
+            self.0.to_string()
+        }
+        fn deed_type(&self) -> &str {
+            self.0
+        }
+        fn tags(&self) -> &[String] {
+            &self.1
+        }
+        fn ethics_flags(&self) -> &[String] {
+            &[]
+        }
+        fn life_harm_flag(&self) -> bool {
+            self.2
+        }
+    }
+
+    #[test]
+    fn only_verified_good_deeds_grow_the_ceiling() {
+        let mut book = CeilingBook::new(CeilingPolicy::default()).unwrap();
+        let good = Deed("cleanup", vec!["ecological_sustainability".to_string()], false);
+        book.fold("a", &good, false);
+        assert_eq!(book.ceiling("a").ceiling, 1.0);
+        book.fold("a", &good, true);
+        assert!((book.ceiling("a").ceiling - 1.1).abs() < 1e-9);
+
+        // Consequences count whether or not the deed is verified.
+        book.fold("b", &Deed("river_dumping", vec![], true), false);
+        assert!((book.ceiling("b").ceiling - 0.8).abs() < 1e-9);
+        assert_eq!(
+            book.check_request("b", gates::MINT, 20.0),
+            Err(CeilingError::OverAllowance { gate: gates::MINT.to_string(), requested: 20.0, allowance: 16.0 })
+        );
+        assert!(book.check_request("b", gates::MINT, 16.0).is_ok());
+        assert_eq!(book.check_request("b", "rocket", 1.0), Err(CeilingError::UnknownGate("rocket".to_string())));
+    }
+}
@@ -0,0 +1,116 @@
+//! Journal entries and their legs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::error::JournalError;
+use crate::{Asset, SUPPLY_ACCOUNT};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// Out of `SUPPLY_ACCOUNT` to holders.
+    Mint,
+    /// From holders back into `SUPPLY_ACCOUNT`.
+    Burn,
+    /// Between holders; supply is untouched.
+    Grant,
+    /// A burn of POWER forced by the power rule; `memo` says why.
+    Rebalance,
+}
+
+/// One side of an entry: a signed change to `account`'s balance of `asset`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Leg {
+    pub account: String,
+    pub asset: Asset,
+    pub amount: i128,                       // > 0 credits the account, < 0 debits it
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub height: u64,                        // position in the journal, set by `Journal::post`
+    pub kind: EntryKind,
+    pub deed_id: Uuid,                      // event_id of the deed that justified the entry
+    pub legs: Vec<Leg>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub memo: String,
+}
+
+impl JournalEntry {
+    pub fn new(kind: EntryKind, deed_id: Uuid, legs: Vec<Leg>) -> Self {
+        Self { height: 0, kind, deed_id, legs, memo: String::new() }
+    }
+
+    /// Issue `amount` of `asset` to `to`.
+    pub fn mint(asset: Asset, to: &str, amount: u64, deed_id: Uuid) -> Self {
+        Self::new(EntryKind::Mint, deed_id, vec![leg(SUPPLY_ACCOUNT, asset, -i128::from(amount)), leg(to, asset, amount.into())])
+    }
+
+    /// Issue `amount` of `asset` to `to`, who passes `share` of it on to
+    /// `grantee` in the same entry, so neither half can post without the other.
+    pub fn mint_with_grant(asset: Asset, to: &str, amount: u64, grantee: &str, share: u64, deed_id: Uuid) -> Self {
+        let mut entry = Self::mint(asset, to, amount, deed_id);
+        if share > 0 {
+            entry.legs.extend([leg(to, asset, -i128::from(share)), leg(grantee, asset, share.into())]);
+        }
+        entry
+    }
+
+    /// Retire `amount` of `asset` held by `from`.
+    pub fn burn(asset: Asset, from: &str, amount: u64, deed_id: Uuid) -> Self {
+        Self::new(EntryKind::Burn, deed_id, vec![leg(from, asset, -i128::from(amount)), leg(SUPPLY_ACCOUNT, asset, amount.into())])
+    }
+
+    /// Burn `amount` of `holder`'s POWER for the power rule.
+    pub fn rebalance(holder: &str, amount: u64, deed_id: Uuid, reason: String) -> Self {
+        Self::new(EntryKind::Rebalance, deed_id, vec![leg(holder, Asset::Pwr, -i128::from(amount)), leg(SUPPLY_ACCOUNT, Asset::Pwr, amount.into())])
+            .with_memo(reason)
+    }
+
+    /// Move `amount` of `asset` from `from` to `to`.
+    pub fn grant(asset: Asset, from: &str, to: &str, amount: u64, deed_id: Uuid) -> Self {
+        Self::new(EntryKind::Grant, deed_id, vec![leg(from, asset, -i128::from(amount)), leg(to, asset, amount.into())])
+    }
+
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = memo.into();
+        self
+    }
+
+    /// Legs sum to zero per asset, and `SUPPLY_ACCOUNT` only moves the way
+    /// `kind` says.
+    pub fn check(&self) -> Result<(), JournalError> {
+        if self.deed_id.is_nil() {
+            return Err(JournalError::Malformed("entry is not linked to a deed".to_string()));
+        }
+        if self.legs.is_empty() {
+            return Err(JournalError::Malformed("entry has no legs".to_string()));
+        }
+        let mut sums: BTreeMap<Asset, i128> = BTreeMap::new();
+        for leg in &self.legs {
+            if leg.account.is_empty() || leg.amount == 0 {
+                return Err(JournalError::Malformed(format!("empty leg on {:?}", leg.account)));
+            }
+            let supply_ok = match self.kind {
+                EntryKind::Mint => leg.amount < 0,
+                EntryKind::Burn | EntryKind::Rebalance => leg.amount > 0,
+                EntryKind::Grant => false,
+            };
+            if leg.account == SUPPLY_ACCOUNT && !supply_ok {
+                return Err(JournalError::Malformed(format!("{:?} entry cannot move supply that way", self.kind)));
+            }
+            let sum = sums.entry(leg.asset).or_default();
+            *sum = sum.checked_add(leg.amount).ok_or(JournalError::Overflow { account: leg.account.clone(), asset: leg.asset })?;
+        }
+        match sums.into_iter().find(|(_, sum)| *sum != 0) {
+            Some((asset, sum)) => Err(JournalError::Unbalanced { asset, sum }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn leg(account: &str, asset: Asset, amount: i128) -> Leg {
+    Leg { account: account.to_string(), asset, amount }
+}
@@ -0,0 +1,33 @@
+use thiserror::Error;
+
+use crate::Asset;
+
+#[derive(Debug, Error)]
+pub enum JournalError {
+    #[error("entry does not balance: {asset} legs sum to {sum}")]
+    Unbalanced { asset: Asset, sum: i128 },
+    #[error("malformed entry: {0}")]
+    Malformed(String),
+    #[error("{account} holds {balance} {asset}, {needed} needed")]
+    InsufficientFunds { account: String, asset: Asset, balance: i128, needed: i128 },
+    #[error("{account} would hold {power} POWER, above its cap of {cap}")]
+    PowerAboveCap { account: String, power: u64, cap: u64 },
+    #[error("invalid power rule: {0}")]
+    InvalidRule(String),
+    #[error("invalid reward schedule: {0}")]
+    InvalidSchedule(String),
+    #[error("balance of {account} in {asset} overflows")]
+    Overflow { account: String, asset: Asset },
+    #[error("height {height} is beyond the journal's {len} entries")]
+    HeightOutOfRange { height: u64, len: u64 },
+    #[error("corrupt entry at line {line}: {reason}")]
+    Corrupt { line: usize, reason: String },
+    #[error("journal does not verify: {0}")]
+    Inconsistent(String),
+    #[error("invalid deed id: {0}")]
+    Deed(#[from] deed_schema::SchemaError),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
@@ -0,0 +1,381 @@
+//! The journal: ordered entries plus the balances they add up to.
+//!
+//! A file-backed journal is JSONL, one entry per line, appended and synced
+//! before `post` returns. On open every entry is replayed through the same
+//! checks as `post`; bytes after the last newline are an append that never
+//! returned, moved to `<path>.torn` and cut off. An append that fails is cut
+//! off at once.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::Read;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::entry::{EntryKind, JournalEntry};
+use crate::error::JournalError;
+use crate::power::{OnBreach, PowerRule};
+use crate::{Asset, SUPPLY_ACCOUNT};
+
+#[derive(Debug, Default)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+    balances: BTreeMap<(String, Asset), i128>,
+    by_deed: HashMap<Uuid, Vec<u64>>,
+    power_rule: Option<PowerRule>,
+    file: Option<File>,
+}
+
+impl Journal {
+    /// An empty journal kept in memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open or create the journal file at `path`.
+    pub fn open(path: &Path) -> Result<Self, JournalError> {
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        deed_schema::recover_torn_tail(path, &file, &bytes)?;
+        let complete = deed_schema::complete_len(&bytes);
+
+        let mut journal = Self::new();
+        let lines = bytes[..complete].split(|&b| b == b'\n').enumerate();
+        for (idx, line) in lines.filter(|(_, l)| !l.trim_ascii().is_empty()) {
+            let corrupt = |reason: String| JournalError::Corrupt { line: idx + 1, reason };
+            let entry: JournalEntry = serde_json::from_slice(line).map_err(|e| corrupt(e.to_string()))?;
+            if entry.height != journal.len() {
+                return Err(corrupt(format!("height {} out of order", entry.height)));
+            }
+            journal.apply(entry).map_err(|e| corrupt(e.to_string()))?;
+        }
+        journal.file = Some(file);
+        Ok(journal)
+    }
+
+    /// Enforce `rule` on every later posting. Balances already in the
+    /// journal are left alone until a posting touches them.
+    pub fn with_power_rule(mut self, rule: PowerRule) -> Result<Self, JournalError> {
+        rule.check()?;
+        self.power_rule = Some(rule);
+        Ok(self)
+    }
+
+    pub fn power_rule(&self) -> Option<&PowerRule> {
+        self.power_rule.as_ref()
+    }
+
+    /// Check `entry`, persist it and apply it. Returns its height.
+    ///
+    /// Under a power rule, a posting that leaves a holder with POWER above
+    /// its cap is refused, or followed by a `Rebalance` entry for that
+    /// holder which is written in the same append.
+    pub fn post(&mut self, entry: JournalEntry) -> Result<u64, JournalError> {
+        let batch = self.preview(entry)?;
+        if let Some(file) = &mut self.file {
+            let mut lines = Vec::new();
+            for entry in &batch {
+                serde_json::to_writer(&mut lines, entry)?;
+                lines.push(b'\n');
+            }
+            deed_schema::append_synced(file, &lines)?;
+        }
+        let height = batch[0].height;
+        for entry in batch {
+            self.apply(entry)?;
+        }
+        Ok(height)
+    }
+
+    /// The entries `post` would write for `entry`, with heights set and
+    /// every check applied, including the power rule's. Nothing is written,
+    /// so a caller can check a posting before recording anything else.
+    pub fn preview(&self, mut entry: JournalEntry) -> Result<Vec<JournalEntry>, JournalError> {
+        entry.height = self.len();
+        let updated = self.updated_balances(&entry)?;
+        let mut batch = vec![entry];
+        if let Some(rule) = self.power_rule {
+            let holders: BTreeSet<&String> = updated
+                .keys()
+                .filter(|(account, asset)| account != SUPPLY_ACCOUNT && matches!(asset, Asset::Church | Asset::Pwr))
+                .map(|(account, _)| account)
+                .collect();
+            for account in holders {
+                let held = |asset| {
+                    let balance = updated.get(&(account.clone(), asset)).copied().unwrap_or_else(|| self.balance(account, asset));
+                    u64::try_from(balance).unwrap_or(0)
+                };
+                let (power, church) = (held(Asset::Pwr), held(Asset::Church));
+                let Some(excess) = rule.excess(power, church) else { continue };
+                if rule.on_breach == OnBreach::Reject {
+                    return Err(JournalError::PowerAboveCap { account: account.clone(), power, cap: rule.cap(church) });
+                }
+                let mut rebalance = JournalEntry::rebalance(account, excess, batch[0].deed_id, rule.reason(power, church));
+                rebalance.height = self.len() + batch.len() as u64;
+                batch.push(rebalance);
+            }
+        }
+        Ok(batch)
+    }
+
+    pub fn mint(&mut self, asset: Asset, to: &str, amount: u64, deed_id: Uuid) -> Result<u64, JournalError> {
+        self.post(JournalEntry::mint(asset, to, amount, deed_id))
+    }
+
+    pub fn burn(&mut self, asset: Asset, from: &str, amount: u64, deed_id: Uuid) -> Result<u64, JournalError> {
+        self.post(JournalEntry::burn(asset, from, amount, deed_id))
+    }
+
+    pub fn grant(&mut self, asset: Asset, from: &str, to: &str, amount: u64, deed_id: Uuid) -> Result<u64, JournalError> {
+        self.post(JournalEntry::grant(asset, from, to, amount, deed_id))
+    }
+
+    fn apply(&mut self, entry: JournalEntry) -> Result<(), JournalError> {
+        for (key, balance) in self.updated_balances(&entry)? {
+            self.balances.insert(key, balance);
+        }
+        self.by_deed.entry(entry.deed_id).or_default().push(entry.height);
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// The balances `entry` would leave behind, after every check.
+    fn updated_balances(&self, entry: &JournalEntry) -> Result<BTreeMap<(String, Asset), i128>, JournalError> {
+        entry.check()?;
+        let mut updated: BTreeMap<(String, Asset), i128> = BTreeMap::new();
+        for leg in &entry.legs {
+            let key = (leg.account.clone(), leg.asset);
+            let current = updated.get(&key).copied().unwrap_or_else(|| self.balances.get(&key).copied().unwrap_or(0));
+            let next = current
+                .checked_add(leg.amount)
+                .ok_or_else(|| JournalError::Overflow { account: leg.account.clone(), asset: leg.asset })?;
+            updated.insert(key, next);
+        }
+        for ((account, asset), balance) in &updated {
+            if account == SUPPLY_ACCOUNT {
+                continue;
+            }
+            if *balance > i128::from(u64::MAX) {
+                return Err(JournalError::Overflow { account: account.clone(), asset: *asset });
+            }
+            if *balance < 0 {
+                let before = self.balances.get(&(account.clone(), *asset)).copied().unwrap_or(0);
+                return Err(JournalError::InsufficientFunds {
+                    account: account.clone(),
+                    asset: *asset,
+                    balance: before,
+                    needed: before - balance,
+                });
+            }
+        }
+        Ok(updated)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.entries.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// Every burn the power rule forced, in journal order.
+    pub fn rebalances(&self) -> impl Iterator<Item = &JournalEntry> {
+        self.entries.iter().filter(|e| e.kind == EntryKind::Rebalance)
+    }
+
+    /// Every entry justified by the deed `deed_id`, in journal order.
+    pub fn entries_for_deed(&self, deed_id: &Uuid) -> Vec<&JournalEntry> {
+        self.by_deed.get(deed_id).into_iter().flatten().map(|h| &self.entries[*h as usize]).collect()
+    }
+
+    /// Current balance of `account` in `asset`. Negative only for
+    /// `SUPPLY_ACCOUNT`.
+    pub fn balance(&self, account: &str, asset: Asset) -> i128 {
+        self.balances.get(&(account.to_string(), asset)).copied().unwrap_or(0)
+    }
+
+    /// Current balance of a holder. Holder balances never leave `0..=u64::MAX`.
+    pub fn held(&self, account: &str, asset: Asset) -> u64 {
+        u64::try_from(self.balance(account, asset)).unwrap_or(0)
+    }
+
+    /// Balance of `account` in `asset` after the first `height` entries.
+    pub fn balance_at(&self, account: &str, asset: Asset, height: u64) -> Result<i128, JournalError> {
+        Ok(self
+            .entries_upto(height)?
+            .iter()
+            .flat_map(|e| &e.legs)
+            .filter(|leg| leg.asset == asset && leg.account == account)
+            .map(|leg| leg.amount)
+            .sum())
+    }
+
+    /// Circulating supply of `asset`.
+    pub fn supply(&self, asset: Asset) -> i128 {
+        -self.balance(SUPPLY_ACCOUNT, asset)
+    }
+
+    /// Circulating supply of `asset` after the first `height` entries.
+    pub fn supply_at(&self, asset: Asset, height: u64) -> Result<i128, JournalError> {
+        Ok(-self.balance_at(SUPPLY_ACCOUNT, asset, height)?)
+    }
+
+    /// Total minted and burned of `asset` over the first `height` entries.
+    pub fn issuance_at(&self, asset: Asset, height: u64) -> Result<(i128, i128), JournalError> {
+        let (mut minted, mut burned) = (0, 0);
+        for entry in self.entries_upto(height)? {
+            let moved: i128 = entry.legs.iter().filter(|l| l.asset == asset && l.account == SUPPLY_ACCOUNT).map(|l| l.amount).sum();
+            match entry.kind {
+                EntryKind::Mint => minted -= moved,
+                EntryKind::Burn | EntryKind::Rebalance => burned += moved,
+                EntryKind::Grant => {}
+            }
+        }
+        Ok((minted, burned))
+    }
+
+    fn entries_upto(&self, height: u64) -> Result<&[JournalEntry], JournalError> {
+        if height > self.len() {
+            return Err(JournalError::HeightOutOfRange { height, len: self.len() });
+        }
+        Ok(&self.entries[..height as usize])
+    }
+
+    /// Replay every entry from zero and check that the balances come out the
+    /// same, holders are never overdrawn, and for every asset the holders'
+    /// balances add up to its supply.
+    pub fn verify(&self) -> Result<(), JournalError> {
+        let mut replay = Journal::new();
+        for entry in &self.entries {
+            if entry.height != replay.len() {
+                return Err(JournalError::Inconsistent(format!("entry {} out of order", entry.height)));
+            }
+            replay.apply(entry.clone())?;
+        }
+        if replay.balances != self.balances {
+            return Err(JournalError::Inconsistent("cached balances differ from the entries".to_string()));
+        }
+        for asset in Asset::ALL {
+            let held: i128 = self
+                .balances
+                .iter()
+                .filter(|((account, a), _)| *a == asset && account != SUPPLY_ACCOUNT)
+                .map(|(_, balance)| balance)
+                .sum();
+            if held != self.supply(asset) {
+                return Err(JournalError::Inconsistent(format!("{} held {} != supply {}", asset, held, self.supply(asset))));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::Leg;
+    use std::io::Write;
+
+    #[test]
+    fn postings_balance_and_supply_is_checkable_at_any_height() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.jsonl");
+        let (planting, sponsorship, cleanup) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let mut journal = Journal::open(&path).unwrap();
+        journal.mint(Asset::Church, "alice", 5, planting).unwrap();
+        journal.mint(Asset::Pwr, "alice", 2, planting).unwrap();
+        journal.grant(Asset::Church, "alice", "npo-7", 3, sponsorship).unwrap();
+        assert!(matches!(
+            journal.burn(Asset::Church, "alice", 3, cleanup),
+            Err(JournalError::InsufficientFunds { balance: 2, needed: 3, .. })
+        ));
+        journal.burn(Asset::Church, "npo-7", 1, cleanup).unwrap();
+        assert!(matches!(journal.mint(Asset::Church, "npo-7", u64::MAX, planting), Err(JournalError::Overflow { .. })));
+
+        assert_eq!((journal.balance("alice", Asset::Church), journal.held("npo-7", Asset::Church)), (2, 2));
+        assert_eq!(journal.supply(Asset::Church), 4);
+        assert_eq!(journal.supply_at(Asset::Church, 1).unwrap(), 5);
+        assert_eq!(journal.supply_at(Asset::Church, 0).unwrap(), 0);
+        assert_eq!(journal.issuance_at(Asset::Church, journal.len()).unwrap(), (5, 1));
+        assert_eq!(journal.balance_at("alice", Asset::Church, 3).unwrap(), 2);
+        assert!(journal.supply_at(Asset::Church, 9).is_err());
+        assert_eq!(journal.entries_for_deed(&planting).len(), 2);
+        journal.verify().unwrap();
+
+        let lopsided = JournalEntry::new(
+            EntryKind::Grant,
+            sponsorship,
+            vec![Leg { account: "alice".into(), asset: Asset::Church, amount: -1 }, Leg {
+                account: "bob".into(),
+                asset: Asset::Church,
+                amount: 2,
+            }],
+        );
+        assert!(matches!(journal.post(lopsided), Err(JournalError::Unbalanced { sum: 1, .. })));
+        assert!(matches!(journal.mint(Asset::Tech, "alice", 1, Uuid::nil()), Err(JournalError::Malformed(_))));
+        let sneaky = JournalEntry::grant(Asset::Church, SUPPLY_ACCOUNT, "bob", 10, sponsorship);
+        assert!(matches!(journal.post(sneaky), Err(JournalError::Malformed(_))));
+        drop(journal);
+
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"height\":4,").unwrap();
+        let mut reopened = Journal::open(&path).unwrap();
+        assert_eq!(reopened.len(), 4);
+        assert_eq!(std::fs::read(deed_schema::torn_path(&path)).unwrap(), b"{\"height\":4,\n");
+        assert_eq!(reopened.supply(Asset::Church), 4);
+        reopened.mint(Asset::Nano, "bob", 1, cleanup).unwrap();
+        assert_eq!(Journal::open(&path).unwrap().len(), 5);
+        reopened.verify().unwrap();
+    }
+
+    #[test]
+    fn power_rule_rejects_or_rebalances_postings() {
+        let (deed, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut strict = Journal::new().with_power_rule(PowerRule::new(2.0, OnBreach::Reject)).unwrap();
+        strict.mint(Asset::Church, "alice", 3, deed).unwrap();
+        strict.mint(Asset::Pwr, "alice", 6, deed).unwrap();
+        assert!(matches!(
+            strict.mint(Asset::Pwr, "alice", 1, deed),
+            Err(JournalError::PowerAboveCap { power: 7, cap: 6, .. })
+        ));
+        assert!(matches!(strict.grant(Asset::Church, "alice", "bob", 1, other), Err(JournalError::PowerAboveCap { .. })));
+        assert_eq!(strict.len(), 2);
+        assert!(Journal::new().with_power_rule(PowerRule::new(-1.0, OnBreach::Reject)).is_err());
+
+        // A mint whose grant would breach the rule is refused as a whole.
+        let split = JournalEntry::mint_with_grant(Asset::Church, "alice", 1, "bob", 2, other);
+        assert!(matches!(strict.preview(split.clone()), Err(JournalError::PowerAboveCap { .. })));
+        assert!(strict.post(split).is_err());
+        assert_eq!((strict.len(), strict.held("bob", Asset::Church)), (2, 0));
+        let split = JournalEntry::mint_with_grant(Asset::Church, "alice", 2, "bob", 1, other);
+        assert_eq!(strict.preview(split.clone()).unwrap().len(), 1);
+        assert_eq!(strict.len(), 2);
+        strict.post(split).unwrap();
+        assert_eq!((strict.held("alice", Asset::Church), strict.held("bob", Asset::Church)), (4, 1));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.jsonl");
+        let rule = PowerRule::new(2.0, OnBreach::Rebalance);
+        let mut lenient = Journal::open(&path).unwrap().with_power_rule(rule).unwrap();
+        lenient.mint(Asset::Church, "alice", 3, deed).unwrap();
+        lenient.mint(Asset::Pwr, "alice", 6, deed).unwrap();
+        assert_eq!(lenient.grant(Asset::Church, "alice", "bob", 1, other).unwrap(), 2);
+        assert_eq!(lenient.held("alice", Asset::Pwr), 4);
+        assert_eq!(lenient.supply(Asset::Pwr), 4);
+
+        let burns: Vec<_> = lenient.rebalances().collect();
+        assert_eq!(burns.len(), 1);
+        assert_eq!((burns[0].height, burns[0].deed_id), (3, other));
+        assert!(burns[0].memo.starts_with("POWER 6 > 2 x CHURCH 2"));
+        assert_eq!(lenient.entries_for_deed(&other).len(), 2);
+        assert_eq!(lenient.issuance_at(Asset::Pwr, lenient.len()).unwrap(), (6, 2));
+        lenient.verify().unwrap();
+        assert_eq!(Journal::open(&path).unwrap().rebalances().count(), 1);
+    }
+}
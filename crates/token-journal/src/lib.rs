@@ -0,0 +1,74 @@
+//! Double-entry token journal for CHURCH, PWR, TECH and NANO.
+//!
+//! Token balances used to live in several places: `Account` fields that
+//! saturated silently, a kernel-local CHURCH counter, and mint functions
+//! whose result nobody stored. Here every movement of tokens is one
+//! `JournalEntry` of legs that sum to zero per asset, including the legs
+//! on `SUPPLY_ACCOUNT`, which issues every asset. So:
+//!
+//! - a mint moves tokens out of supply, a burn moves them back, and a grant
+//!   moves them between two holders;
+//! - `SUPPLY_ACCOUNT`'s balance is minus the circulating supply, so supply
+//!   can be read at any journal height and checked against the holders;
+//! - no holder balance can go below zero; an overdraft is an error instead
+//!   of a silent clamp;
+//! - every entry names the `event_id` of the deed that justified it;
+//! - with a `PowerRule`, no posting leaves a holder with more POWER than
+//!   `power_church_k` times its CHURCH;
+//! - reward amounts come from one `RewardSchedule` of named rules;
+//! - grant and mint requests are checked against the requesting account's
+//!   `DebtCeiling` before anything is posted.
+
+pub mod ceiling;
+pub mod entry;
+pub mod error;
+pub mod journal;
+pub mod power;
+pub mod schedule;
+
+pub use ceiling::{CeilingBook, CeilingDeed, CeilingError, CeilingPolicy, DebtCeiling};
+pub use entry::{EntryKind, JournalEntry, Leg};
+pub use error::JournalError;
+pub use journal::Journal;
+pub use power::{OnBreach, PowerRule};
+pub use schedule::{RewardBreakdown, RewardInput, RewardItem, RewardSchedule};
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// The account every asset is minted from and burned into.
+pub const SUPPLY_ACCOUNT: &str = "@supply";
+
+/// The id an entry links to, from a deed's string `event_id`.
+pub fn deed_id(event_id: &str) -> Result<Uuid, JournalError> {
+    Ok(deed_schema::parse_event_id(event_id)?)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Asset {
+    Church,
+    Pwr,
+    Tech,
+    Nano,
+}
+
+impl Asset {
+    pub const ALL: [Asset; 4] = [Asset::Church, Asset::Pwr, Asset::Tech, Asset::Nano];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Asset::Church => "CHURCH",
+            Asset::Pwr => "PWR",
+            Asset::Tech => "TECH",
+            Asset::Nano => "NANO",
+        }
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
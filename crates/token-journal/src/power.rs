@@ -0,0 +1,86 @@
+//! The steward rule: a holder's POWER may not exceed `power_church_k` times
+//! its CHURCH.
+//!
+//! This is `god_like_core::is_power_steward_safe` enforced on every posting
+//! instead of checked afterwards. A deployment picks what happens to a
+//! posting that would break it: the posting is refused, or it goes through
+//! and the excess POWER is burned in a `Rebalance` entry that says why.
+
+use god_like_core::Envelope;
+use serde::{Deserialize, Serialize};
+
+use crate::error::JournalError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnBreach {
+    /// Refuse the posting with `JournalError::PowerAboveCap`.
+    Reject,
+    /// Post it, then burn the holder's POWER down to the cap.
+    Rebalance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PowerRule {
+    pub power_church_k: f64,
+    pub on_breach: OnBreach,
+}
+
+impl PowerRule {
+    pub fn new(power_church_k: f64, on_breach: OnBreach) -> Self {
+        Self { power_church_k, on_breach }
+    }
+
+    /// The envelope's `power_church_k`, so the journal and
+    /// `is_power_steward_safe` enforce the same k.
+    pub fn from_envelope(envelope: &Envelope, on_breach: OnBreach) -> Self {
+        Self::new(envelope.power_church_k, on_breach)
+    }
+
+    pub fn check(&self) -> Result<(), JournalError> {
+        if !self.power_church_k.is_finite() || self.power_church_k < 0.0 {
+            return Err(JournalError::InvalidRule(format!("power_church_k {} is not a finite k >= 0", self.power_church_k)));
+        }
+        Ok(())
+    }
+
+    /// Most POWER a holder of `church` CHURCH may have.
+    pub fn cap(&self, church: u64) -> u64 {
+        // `as` saturates; a cap above u64::MAX allows any balance.
+        (self.power_church_k * church as f64).floor() as u64
+    }
+
+    /// POWER above the cap, if any.
+    pub fn excess(&self, power: u64, church: u64) -> Option<u64> {
+        power.checked_sub(self.cap(church)).filter(|excess| *excess > 0)
+    }
+
+    pub(crate) fn reason(&self, power: u64, church: u64) -> String {
+        format!("POWER {} > {} x CHURCH {}: burned down to {}", power, self.power_church_k, church, self.cap(church))
+    }
+}
+
+impl Default for PowerRule {
+    /// k from `Envelope::default`, and refuse breaches.
+    fn default() -> Self {
+        Self::from_envelope(&Envelope::default(), OnBreach::Reject)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cap_follows_k_and_church() {
+        let rule = PowerRule::new(1.5, OnBreach::Reject);
+        assert_eq!(rule.cap(0), 0);
+        assert_eq!(rule.cap(3), 4);
+        assert_eq!(rule.excess(4, 3), None);
+        assert_eq!(rule.excess(7, 3), Some(3));
+        assert_eq!(rule.excess(1, 0), Some(1));
+        assert!(PowerRule::new(f64::NAN, OnBreach::Rebalance).check().is_err());
+        assert!(PowerRule::new(-1.0, OnBreach::Rebalance).check().is_err());
+        assert_eq!(PowerRule::default().power_church_k, Envelope::default().power_church_k);
+    }
+}
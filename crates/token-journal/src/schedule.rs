@@ -0,0 +1,422 @@
+//! Declarative reward schedule.
+//!
+//! Reward amounts used to be written into each crate's mint path. A
+//! `RewardSchedule` holds them as named rules instead, loaded from a policy
+//! file (`.aln` in its JSON-compatible form, or `.json`). Evaluating it for
+//! a deed gives a `RewardBreakdown` with one item per matching rule, so every
+//! minted amount can be traced to the rule and schedule version that set it.
+//!
+//! A rule's value is
+//!
+//! ```text
+//! (base + per_bioload_reduction * max(0, -bioload_delta) + per_minted * minted)
+//!     * moral_position            (if scale_by_moral_position)
+//!     * (1 + per_tag * tags)
+//! ```
+//!
+//! rounded down to whole tokens. Advisory rules are reported but never
+//! minted. `RewardSchedule::builtin` is `schedules/default.aln`, which holds
+//! the amounts the crates used before.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+use crate::error::JournalError;
+use crate::journal::Journal;
+use crate::{Asset, JournalEntry};
+
+/// Rules of the builtin schedule that callers look up by name.
+pub mod rules {
+    pub const ECOLOGICAL_SUSTAINABILITY_REWARD: &str = "ecological_sustainability_reward";
+    pub const BIOLOAD_REDUCTION_BONUS: &str = "bioload_reduction_bonus";
+    pub const GOOD_DEED_RECOMMENDATION: &str = "good_deed_recommendation";
+    pub const ECO_GRANT_RECOMMENDATION: &str = "eco_grant_recommendation";
+    pub const ECO_GRANT_SHARE: &str = "eco_grant_share";
+    pub const SAFE_DEED_TECH: &str = "safe_deed_tech";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardSchedule {
+    pub version: u32,
+    pub rules: Vec<RewardRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardRule {
+    pub name: String,
+    pub asset: Asset,
+    #[serde(default)]
+    pub advisory: bool,                     // reported, never minted
+    #[serde(default)]
+    pub when: Conditions,
+    pub amount: Amount,
+}
+
+/// What a deed must look like for a rule to apply. A bound on a metric the
+/// caller did not supply never matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conditions {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deed_types: Vec<String>,            // any of these; empty matches all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags_any: Vec<String>,              // at least one of these; empty matches all
+    #[serde(default = "default_clean")]
+    pub clean: bool,                        // no life harm and no ethics flags
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bioload_delta: Option<Bounds>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roh: Option<Bounds>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decay: Option<Bounds>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moral_position: Option<Bounds>,
+}
+
+impl Default for Conditions {
+    fn default() -> Self {
+        Self {
+            deed_types: Vec::new(),
+            tags_any: Vec::new(),
+            clean: true,
+            bioload_delta: None,
+            roh: None,
+            decay: None,
+            moral_position: None,
+        }
+    }
+}
+
+fn default_clean() -> bool {
+    true
+}
+
+fn is_zero(n: &f64) -> bool {
+    *n == 0.0
+}
+
+/// `min`/`max` are inclusive, `above`/`below` exclusive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bounds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub above: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below: Option<f64>,
+}
+
+impl Bounds {
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min)
+            && self.max.is_none_or(|max| value <= max)
+            && self.above.is_none_or(|above| value > above)
+            && self.below.is_none_or(|below| value < below)
+    }
+
+    fn matches(bounds: &Option<Bounds>, value: Option<f64>) -> bool {
+        match bounds {
+            Some(bounds) => value.is_some_and(|v| bounds.contains(v)),
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Amount {
+    #[serde(default)]
+    pub base: f64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub base_by_deed_type: BTreeMap<String, f64>, // overrides `base` for these types
+    #[serde(default)]
+    pub per_bioload_reduction: f64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub per_minted: f64,                    // share of the CHURCH minted for the same deed
+    #[serde(default)]
+    pub scale_by_moral_position: bool,
+    #[serde(default)]
+    pub per_tag: f64,
+}
+
+/// The facts about a deed that rules match on.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RewardInput {
+    pub deed_type: String,
+    pub tags: Vec<String>,
+    pub clean: bool,
+    pub bioload_delta: Option<f64>,
+    pub roh: Option<f64>,
+    pub decay: Option<f64>,
+    pub moral_position: Option<f64>,
+    pub minted: Option<u64>,                // CHURCH minted for the deed, for `per_minted` rules
+}
+
+impl RewardInput {
+    pub fn new(deed_type: &str, tags: &[String], clean: bool) -> Self {
+        Self { deed_type: deed_type.to_string(), tags: tags.to_vec(), clean, ..Self::default() }
+    }
+
+    pub fn from_deed(deed: &deed_schema::DeedEvent) -> Self {
+        Self::new(&deed.deed_type, &deed.tags, !deed.life_harm_flag && deed.ethics_flags.is_empty())
+    }
+
+    pub fn with_bioload_delta(mut self, delta: f64) -> Self {
+        self.bioload_delta = Some(delta);
+        self
+    }
+
+    pub fn with_roh(mut self, roh: f64) -> Self {
+        self.roh = Some(roh);
+        self
+    }
+
+    pub fn with_decay(mut self, decay: f64) -> Self {
+        self.decay = Some(decay);
+        self
+    }
+
+    pub fn with_moral_position(mut self, score: f64) -> Self {
+        self.moral_position = Some(score);
+        self
+    }
+
+    pub fn with_minted(mut self, minted: u64) -> Self {
+        self.minted = Some(minted);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardItem {
+    pub rule: String,
+    pub asset: Asset,
+    pub value: f64,                         // before rounding
+    pub amount: u64,                        // whole tokens
+    pub advisory: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RewardBreakdown {
+    pub schedule_version: u32,
+    pub items: Vec<RewardItem>,
+}
+
+impl RewardBreakdown {
+    pub fn item(&self, rule: &str) -> Option<&RewardItem> {
+        self.items.iter().find(|item| item.rule == rule)
+    }
+
+    /// Whole tokens from `rule`, 0 if it did not match.
+    pub fn amount_of(&self, rule: &str) -> u64 {
+        self.item(rule).map_or(0, |item| item.amount)
+    }
+
+    /// Unrounded value from `rule`, 0 if it did not match.
+    pub fn value_of(&self, rule: &str) -> f64 {
+        self.item(rule).map_or(0.0, |item| item.value)
+    }
+
+    /// The items paid in `asset`.
+    pub fn only(&self, asset: Asset) -> RewardBreakdown {
+        let items = self.items.iter().filter(|item| item.asset == asset).cloned().collect();
+        RewardBreakdown { schedule_version: self.schedule_version, items }
+    }
+
+    /// Tokens of `asset` to mint: the non-advisory items.
+    pub fn minted(&self, asset: Asset) -> u64 {
+        self.items
+            .iter()
+            .filter(|item| item.asset == asset && !item.advisory)
+            .fold(0, |total, item| total.saturating_add(item.amount))
+    }
+
+    /// One mint entry paying every non-advisory item to `account`, a pair
+    /// of legs per item, with the rule names as memo. `None` if nothing is
+    /// minted.
+    pub fn mint_entry(&self, account: &str, deed_id: Uuid) -> Option<JournalEntry> {
+        let paid: Vec<&RewardItem> = self.items.iter().filter(|item| !item.advisory && item.amount > 0).collect();
+        let mut entry = JournalEntry::mint(paid.first()?.asset, account, paid[0].amount, deed_id);
+        for item in &paid[1..] {
+            entry.legs.extend(JournalEntry::mint(item.asset, account, item.amount, deed_id).legs);
+        }
+        let rules: Vec<&str> = paid.iter().map(|item| item.rule.as_str()).collect();
+        Some(entry.with_memo(format!("{} (schedule v{})", rules.join(", "), self.schedule_version)))
+    }
+}
+
+impl RewardSchedule {
+    pub fn from_json(text: &str) -> Result<Self, JournalError> {
+        let schedule: Self = serde_json::from_str(text)?;
+        schedule.check()?;
+        Ok(schedule)
+    }
+
+    /// Load a schedule from an `.aln` (JSON-compatible) or `.json` file.
+    pub fn load(path: &Path) -> Result<Self, JournalError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// The schedule in `schedules/default.aln`.
+    pub fn builtin() -> &'static RewardSchedule {
+        static BUILTIN: OnceLock<RewardSchedule> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            Self::from_json(include_str!("../schedules/default.aln")).expect("builtin reward schedule is valid")
+        })
+    }
+
+    /// Rule names are unique and every number is finite, with no negative
+    /// amounts.
+    pub fn check(&self) -> Result<(), JournalError> {
+        let mut names = BTreeSet::new();
+        for rule in &self.rules {
+            let invalid = |reason: &str| JournalError::InvalidSchedule(format!("rule {:?} {}", rule.name, reason));
+            if rule.name.is_empty() || !names.insert(rule.name.as_str()) {
+                return Err(invalid("is unnamed or named twice"));
+            }
+            let amount = &rule.amount;
+            let rates = [amount.base, amount.per_bioload_reduction, amount.per_minted, amount.per_tag];
+            if rates.iter().chain(amount.base_by_deed_type.values()).any(|n| !n.is_finite() || *n < 0.0) {
+                return Err(invalid("has a negative or non-finite amount"));
+            }
+            let when = &rule.when;
+            let bounds = [when.bioload_delta, when.roh, when.decay, when.moral_position];
+            if bounds.iter().flatten().flat_map(|b| [b.min, b.max, b.above, b.below]).flatten().any(|n| !n.is_finite()) {
+                return Err(invalid("has a non-finite bound"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn evaluate(&self, input: &RewardInput) -> RewardBreakdown {
+        let items = self
+            .rules
+            .iter()
+            .filter(|rule| rule.matches(input))
+            .filter_map(|rule| {
+                let value = rule.value(input);
+                (value > 0.0).then(|| RewardItem {
+                    rule: rule.name.clone(),
+                    asset: rule.asset,
+                    value,
+                    amount: value as u64,
+                    advisory: rule.advisory,
+                })
+            })
+            .collect();
+        RewardBreakdown { schedule_version: self.version, items }
+    }
+}
+
+impl RewardRule {
+    fn matches(&self, input: &RewardInput) -> bool {
+        let when = &self.when;
+        (!when.clean || input.clean)
+            && (when.deed_types.is_empty() || when.deed_types.contains(&input.deed_type))
+            && (when.tags_any.is_empty() || input.tags.iter().any(|t| when.tags_any.contains(t)))
+            && Bounds::matches(&when.bioload_delta, input.bioload_delta)
+            && Bounds::matches(&when.roh, input.roh)
+            && Bounds::matches(&when.decay, input.decay)
+            && Bounds::matches(&when.moral_position, input.moral_position)
+            && (!self.amount.scale_by_moral_position || input.moral_position.is_some())
+    }
+
+    fn value(&self, input: &RewardInput) -> f64 {
+        let amount = &self.amount;
+        let base = amount.base_by_deed_type.get(&input.deed_type).copied().unwrap_or(amount.base);
+        let reduction = input.bioload_delta.map_or(0.0, |delta| (-delta).max(0.0));
+        let minted = input.minted.map_or(0.0, |minted| minted as f64);
+        let mut value = base + amount.per_bioload_reduction * reduction + amount.per_minted * minted;
+        if amount.scale_by_moral_position {
+            value *= input.moral_position.unwrap_or(0.0);
+        }
+        value * (1.0 + amount.per_tag * input.tags.len() as f64)
+    }
+}
+
+impl Journal {
+    /// Mint every non-advisory item of `breakdown` to `account` as one
+    /// entry (`RewardBreakdown::mint_entry`), so a refused item refuses them
+    /// all. Returns its height, `None` if nothing was minted.
+    pub fn post_rewards(&mut self, account: &str, breakdown: &RewardBreakdown, deed_id: Uuid) -> Result<Option<u64>, JournalError> {
+        breakdown.mint_entry(account, deed_id).map(|entry| self.post(entry)).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::power::{OnBreach, PowerRule};
+
+    #[test]
+    fn builtin_schedule_keeps_the_old_amounts() {
+        let schedule = RewardSchedule::builtin();
+        let tags = vec!["tree_planting".to_string()];
+
+        let planting = RewardInput::new("ecological_sustainability", &tags, true).with_bioload_delta(-0.12).with_roh(0.2);
+        let rewards = schedule.evaluate(&planting);
+        assert_eq!(rewards.amount_of(rules::ECOLOGICAL_SUSTAINABILITY_REWARD), 12);
+        assert_eq!(rewards.amount_of(rules::BIOLOAD_REDUCTION_BONUS), 6);
+        assert!(rewards.item(rules::BIOLOAD_REDUCTION_BONUS).unwrap().advisory);
+        assert_eq!(rewards.amount_of(rules::GOOD_DEED_RECOMMENDATION), 1);
+        assert_eq!(rewards.amount_of(rules::SAFE_DEED_TECH), 10);
+        assert_eq!(rewards.minted(Asset::Church), 12);
+        assert_eq!(rewards.only(Asset::Tech).items.len(), 1);
+
+        let flagged = RewardInput::new("ecological_sustainability", &tags, false).with_bioload_delta(-0.12).with_moral_position(0.7);
+        let rewards = schedule.evaluate(&flagged);
+        assert_eq!(rewards.amount_of(rules::ECOLOGICAL_SUSTAINABILITY_REWARD), 0);
+        assert_eq!(rewards.amount_of(rules::BIOLOAD_REDUCTION_BONUS), 6);
+        assert_eq!(rewards.value_of(rules::ECO_GRANT_RECOMMENDATION), 25.0 * 0.7 * (1.0 + 1.0 * 0.05));
+        assert_eq!(schedule.evaluate(&RewardInput::new("other", &[], true)).items, vec![]);
+
+        let minted = RewardInput::default().with_minted(100);
+        assert_eq!(schedule.evaluate(&minted).amount_of(rules::ECO_GRANT_SHARE), 10);
+        assert!(schedule.evaluate(&minted).item(rules::ECO_GRANT_SHARE).unwrap().advisory);
+        assert_eq!(schedule.evaluate(&RewardInput::default().with_minted(9)).amount_of(rules::ECO_GRANT_SHARE), 0);
+    }
+
+    #[test]
+    fn schedules_load_from_policy_files_and_post_itemised_mints() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rewards.aln");
+        std::fs::write(
+            &path,
+            r#"{ "version": 3, "rules": [
+                { "name": "calm_repair", "asset": "CHURCH",
+                  "when": { "tags_any": ["repair"], "decay": { "below": 0.5 }, "moral_position": { "min": 0.6 } },
+                  "amount": { "base": 4, "per_tag": 0.5 } },
+                { "name": "repair_power", "asset": "PWR", "when": { "deed_types": ["repair"] }, "amount": { "base": 2 } }
+            ] }"#,
+        )
+        .unwrap();
+        let schedule = RewardSchedule::load(&path).unwrap();
+        let tags = vec!["repair".to_string(), "river".to_string()];
+        let input = RewardInput::new("repair", &tags, true).with_decay(0.2).with_moral_position(0.8);
+        let rewards = schedule.evaluate(&input);
+        assert_eq!((rewards.minted(Asset::Church), rewards.minted(Asset::Pwr)), (8, 2));
+        assert!(schedule.evaluate(&RewardInput::new("repair", &tags, true).with_decay(0.2)).item("calm_repair").is_none());
+
+        let deed = Uuid::new_v4();
+        let mut journal = Journal::new();
+        assert_eq!(journal.post_rewards("alice", &rewards, deed).unwrap(), Some(0));
+        assert_eq!(journal.entries()[0].memo, "calm_repair, repair_power (schedule v3)");
+        assert_eq!(journal.held("alice", Asset::Pwr), 2);
+        assert_eq!(journal.post_rewards("alice", &rewards.only(Asset::Nano), deed).unwrap(), None);
+
+        // A rule the power rule refuses takes the others down with it.
+        let mut strict = Journal::new().with_power_rule(PowerRule::new(0.1, OnBreach::Reject)).unwrap();
+        assert!(matches!(strict.post_rewards("alice", &rewards, deed), Err(JournalError::PowerAboveCap { .. })));
+        assert_eq!((strict.len(), strict.held("alice", Asset::Church)), (0, 0));
+
+        let twice = r#"{ "version": 1, "rules": [
+            { "name": "a", "asset": "NANO", "amount": { "base": 1 } },
+            { "name": "a", "asset": "NANO", "amount": { "base": 1 } } ] }"#;
+        assert!(matches!(RewardSchedule::from_json(twice), Err(JournalError::InvalidSchedule(_))));
+        let negative = r#"{ "version": 1, "rules": [ { "name": "a", "asset": "NANO", "amount": { "base": -1 } } ] }"#;
+        assert!(matches!(RewardSchedule::from_json(negative), Err(JournalError::InvalidSchedule(_))));
+    }
+}
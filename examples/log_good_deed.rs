@@ -1,4 +1,5 @@
 use church_ledger::append_deed_event;
+use deed_schema::{RandomIds, SystemClock};
 fn main() {
     let _ = append_deed_event(
         "data/church-ledger.jsonl",
@@ -9,6 +10,8 @@ fn main() {
         serde_json::json!({"hours": 8, "meals_served": 45, "location": "San Tan Valley"}),
         vec![],
         false,
+        &SystemClock,
+        &RandomIds,
     );
     // This single deed mints ~28 CHURCH tokens + eco_grant recommendation for real NPO funding routing
 }
@@ -0,0 +1 @@
+pub mod reversal_protection_kernel;
@@ -6,23 +6,25 @@
 // - serde: for JSON serialization/deserialization.
 // - chrono: for timestamping.
 // - ed25519-dalek: for multi-sig quorum signatures.
-// - rand: for secure randomness (if needed for nonces).
 // - thiserror: for structured errors.
 // - log: for tamper-evident logging.
+// - token-journal: double-entry journal for minted CHURCH, linked to audit records.
 
 // No additional pip installs; all within Rust ecosystem.
 
 use ring::digest::{Context, SHA256};
 use serde::{Deserialize, Serialize};
 use chrono::Utc;
-use ed25519_dalek::{Keypair, Signature, Signer, Verifier};
-use rand::rngs::OsRng;
+use ed25519_dalek::{Signature, Verifier};
 use thiserror::Error;
 use log::{info, warn};
 use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::{self, BufRead, BufReader, Write};
 use std::path::Path;
+use token_journal::schedule::rules::ECO_GRANT_SHARE;
+use token_journal::{Asset, Journal, JournalEntry, JournalError, PowerRule, RewardInput, RewardSchedule};
+use uuid::Uuid;
 
 // Knowledge objects (KOs): Define rare items for CHURCH token earning.
 // KO1: NonReversalProof - A verifiable proof that a simulation re-analysis preserved rights without downgrade.
@@ -88,25 +90,42 @@ pub enum ReversalError {
     RollbackForbidden,
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
+    #[error("Serialization error: {0}")]
+    Json(#[from] serde_json::Error),
     #[error("Crypto error: {0}")]
     Crypto(String),
+    #[error("Token journal error: {0}")]
+    Journal(#[from] JournalError),
 }
 
 pub struct ReversalProtectionKernel {
     pub audit_log_path: String,
     pub public_keys: Vec<ed25519_dalek::PublicKey>, // Authorized personnel public keys
     pub quorum_threshold: usize,                    // e.g., 2 out of 3
-    pub church_token_balance: u64,                  // Accumulated CHURCH tokens
+    pub journal: Journal,                           // CHURCH minted for good deeds, per account
+    pub schedule: RewardSchedule,                   // sets the eco_grants share of each mint
 }
 
+// Account that receives the eco_grants share of every CHURCH mint.
+pub const ECO_GRANTS_ACCOUNT: &str = "eco_grants";
+
 impl ReversalProtectionKernel {
-    pub fn new(audit_log_path: &str, public_keys: Vec<ed25519_dalek::PublicKey>, quorum_threshold: usize) -> Self {
-        Self {
+    // The token journal at `journal_path` is replayed on open and appended to on every post,
+    // with `power_rule` enforced on each posting.
+    pub fn new(
+        audit_log_path: &str,
+        journal_path: &Path,
+        power_rule: PowerRule,
+        public_keys: Vec<ed25519_dalek::PublicKey>,
+        quorum_threshold: usize,
+    ) -> Result<Self, ReversalError> {
+        Ok(Self {
             audit_log_path: audit_log_path.to_string(),
             public_keys,
             quorum_threshold,
-            church_token_balance: 0,
-        }
+            journal: Journal::open(journal_path)?.with_power_rule(power_rule)?,
+            schedule: RewardSchedule::builtin().clone(),
+        })
     }
 
     // Function: verify_quorum_sig
@@ -143,13 +162,22 @@ impl ReversalProtectionKernel {
     }
 
     // Function: mint_church_tokens
-    // Mints CHURCH tokens for good deeds (e.g., safe re-simulation without reversal).
-    pub fn mint_church_tokens(&mut self, deed_value: u64) {
-        self.church_token_balance += deed_value;
-        info!("Minted {} CHURCH tokens for good deed. New balance: {}", deed_value, self.church_token_balance);
-        // Promote ecological help: Donate fraction to homelessness_relief or ecological_sustainability NPOs.
-        let eco_grant = deed_value / 10; // 10% to eco_grants
-        info!("Allocated {} to eco_grants for sustainability projects", eco_grant);
+    // Mints CHURCH tokens for good deeds (e.g., safe re-simulation without reversal),
+    // posted to the journal against the audit record `deed_id` of the deed.
+    pub fn mint_church_tokens(&mut self, account: &str, deed_value: u64, deed_id: Uuid) -> Result<(), ReversalError> {
+        let entry = self.church_mint_entry(account, deed_value, deed_id);
+        self.journal.post(entry)?;
+        info!("Minted {} CHURCH tokens for good deed. New balance: {}", deed_value, self.journal.held(account, Asset::Church));
+        info!("{} now holds {} for sustainability projects", ECO_GRANTS_ACCOUNT, self.journal.held(ECO_GRANTS_ACCOUNT, Asset::Church));
+        Ok(())
+    }
+
+    // Helper: church_mint_entry
+    // The mint and its eco grant as one entry, so the journal takes both or neither.
+    fn church_mint_entry(&self, account: &str, deed_value: u64, deed_id: Uuid) -> JournalEntry {
+        // Promote ecological help: Donate the schedule's `eco_grant_share` to homelessness_relief or ecological_sustainability NPOs.
+        let eco_grant = self.schedule.evaluate(&RewardInput::default().with_minted(deed_value)).amount_of(ECO_GRANT_SHARE);
+        JournalEntry::mint_with_grant(Asset::Church, account, deed_value, ECO_GRANTS_ACCOUNT, eco_grant, deed_id)
     }
 
     // Function: resimulate_safe
@@ -171,7 +199,13 @@ impl ReversalProtectionKernel {
                 proof_hash: self.compute_hash(simulation_data.as_bytes()),
                 preserved_rights: true,
             };
-            self.mint_church_tokens(100); // Reward good deed of safe re-simulation.
+            // Reward good deed of safe re-simulation, linked to its audit record.
+            // The mint is checked first so a refused posting leaves no audit record behind.
+            let record = self.create_audit_record(state, "simulation_reanalysis");
+            let deed_id = token_journal::deed_id(&record.event_id)?;
+            self.journal.preview(self.church_mint_entry(&state.user_id, 100, deed_id))?;
+            self.log_tamper_evident(&record)?;
+            self.mint_church_tokens(&state.user_id, 100, deed_id)?;
             info!("Safe re-simulation completed: evolution_index={}", state.evolution_index);
             Ok(proof)
         } else {
@@ -270,44 +304,96 @@ pub struct NonReversalProof {
     pub preserved_rights: bool,
 }
 
-// Usage example (for real-world integration, e.g., Android NDK FFI).
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let public_keys: Vec<ed25519_dalek::PublicKey> = vec![]; // Populate with real keys
-    let mut kernel = ReversalProtectionKernel::new("audit.log", public_keys, 2);
-
-    let mut state = SovereigntyState {
-        user_id: "XboxTeeJay".to_string(),
-        capability_tier: "CapGeneralUse".to_string(),
-        roh_value: 0.25,
-        power: 100,
-        tech: 100,
-        nano: 100,
-        evolution_index: 1,
-        no_rollback: true,
-    };
-
-    let envelope = BiophysicalEnvelope {
-        min_safe: 0.0,
-        max_safe: 0.3,
-        min_warn: 0.1,
-        max_warn: 0.25,
-        current_value: 0.26, // Slightly over warn for testing
-    };
-
-    // Test safe re-simulation.
-    let proof = kernel.resimulate_safe(&mut state, &envelope, "simulation_data")?;
-    println!("Non-reversal proof: {:?}", proof);
-
-    // Test downgrade request (should fail unless emergency and quorum).
-    let request = DowngradeRequest {
-        new_tier: "CapLabBench".to_string(),
-        emergency: true,
-        simulation_data: "data".to_string(),
-    };
-    let signatures: Vec<(ed25519_dalek::PublicKey, Signature)> = vec![]; // Mock signatures
-    if let Err(e) = kernel.ensure_rights_held(&mut state, &envelope, &request, &signatures) {
-        println!("Downgrade denied: {}", e);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use token_journal::OnBreach;
+
+    #[test]
+    fn mint_posts_eco_grant_to_the_journal_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal_path = dir.path().join("tokens.jsonl");
+        let audit_log = dir.path().join("audit.log");
+        let rule = PowerRule::new(2.0, OnBreach::Rebalance);
+        let mut kernel = ReversalProtectionKernel::new(audit_log.to_str().unwrap(), &journal_path, rule, vec![], 2).unwrap();
+        assert_eq!(kernel.journal.power_rule(), Some(&rule));
+
+        let deed_id = Uuid::new_v4();
+        kernel.mint_church_tokens("alice", 100, deed_id).unwrap();
+        kernel.mint_church_tokens("alice", 9, Uuid::new_v4()).unwrap(); // eco grant rounds to 0
+        assert_eq!(kernel.journal.held("alice", Asset::Church), 99);
+        assert_eq!(kernel.journal.held(ECO_GRANTS_ACCOUNT, Asset::Church), 10);
+
+        let reopened = Journal::open(&journal_path).unwrap();
+        assert_eq!(reopened.len(), 2);
+        assert_eq!(reopened.held("alice", Asset::Church), 99);
+        assert_eq!(reopened.held(ECO_GRANTS_ACCOUNT, Asset::Church), 10);
+        assert_eq!(reopened.entries_for_deed(&deed_id).len(), 1); // the mint and its eco grant, in one entry
+
+        // A deployment's own schedule sets the share.
+        kernel.schedule = RewardSchedule::from_json(
+            r#"{ "version": 2, "rules": [ { "name": "eco_grant_share", "asset": "CHURCH", "advisory": true,
+                 "when": { "clean": false }, "amount": { "per_minted": 0.25 } } ] }"#,
+        )
+        .unwrap();
+        kernel.mint_church_tokens("bob", 100, Uuid::new_v4()).unwrap();
+        assert_eq!(kernel.journal.held("bob", Asset::Church), 75);
+        assert_eq!(kernel.journal.held(ECO_GRANTS_ACCOUNT, Asset::Church), 35);
     }
 
-    Ok(())
+    #[test]
+    fn refused_mint_posts_neither_the_mint_nor_its_grant() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal_path = dir.path().join("tokens.jsonl");
+        let audit_log = dir.path().join("audit.log");
+        // POWER from before the rule: 95 PWR against no CHURCH.
+        Journal::open(&journal_path).unwrap().mint(Asset::Pwr, "alice", 95, Uuid::new_v4()).unwrap();
+        let rule = PowerRule::new(1.0, OnBreach::Reject);
+        let mut kernel = ReversalProtectionKernel::new(audit_log.to_str().unwrap(), &journal_path, rule, vec![], 2).unwrap();
+
+        // 100 CHURCH would cover 95 PWR, but not the 90 left after the eco grant.
+        let err = kernel.mint_church_tokens("alice", 100, Uuid::new_v4()).unwrap_err();
+        assert!(matches!(err, ReversalError::Journal(JournalError::PowerAboveCap { power: 95, cap: 90, .. })));
+        assert_eq!(kernel.journal.len(), 1);
+
+        let mut state = SovereigntyState {
+            user_id: "alice".to_string(),
+            capability_tier: "CapGeneralUse".to_string(),
+            roh_value: 0.2,
+            power: 100,
+            tech: 100,
+            nano: 100,
+            evolution_index: 1,
+            no_rollback: true,
+        };
+        let envelope = BiophysicalEnvelope { min_safe: 0.0, max_safe: 0.3, min_warn: 0.1, max_warn: 0.25, current_value: 0.2 };
+        assert!(kernel.resimulate_safe(&mut state, &envelope, "simulation_data").is_err());
+        assert!(!audit_log.exists());
+        assert_eq!(Journal::open(&journal_path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn safe_resimulation_mints_against_its_audit_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_log = dir.path().join("audit.log");
+        let mut kernel = ReversalProtectionKernel::new(audit_log.to_str().unwrap(), &dir.path().join("tokens.jsonl"), PowerRule::default(), vec![], 2).unwrap();
+        let mut state = SovereigntyState {
+            user_id: "alice".to_string(),
+            capability_tier: "CapGeneralUse".to_string(),
+            roh_value: 0.2,
+            power: 100,
+            tech: 100,
+            nano: 100,
+            evolution_index: 1,
+            no_rollback: true,
+        };
+        let envelope = BiophysicalEnvelope { min_safe: 0.0, max_safe: 0.3, min_warn: 0.1, max_warn: 0.25, current_value: 0.2 };
+
+        kernel.resimulate_safe(&mut state, &envelope, "simulation_data").unwrap();
+        let record: EvolutionAuditRecord = serde_json::from_str(std::fs::read_to_string(&audit_log).unwrap().trim_end()).unwrap();
+        let deed_id = token_journal::deed_id(&record.event_id).unwrap();
+        assert_eq!(kernel.journal.entries_for_deed(&deed_id).len(), 1);
+        assert_eq!(kernel.journal.held("alice", Asset::Church), 90);
+        assert_eq!(kernel.journal.held(ECO_GRANTS_ACCOUNT, Asset::Church), 10);
+    }
 }
@@ -1,42 +1,185 @@
-use crate::ledger::Ledger;
-use crate::utils::time::time_discount_factor;
-use chrono::Utc;
+use crate::ledger::ceiling::{fold_ceiling, replay_ceiling};
+use crate::ledger::{CeilingPolicy, DebtCeiling, DecayAccumulator, DeedEvent, DeedVerifier, Ledger};
+use crate::utils::time::DecayPolicy;
+use deed_schema::CorrectableDeed;
+use std::collections::HashMap;
 
-#[derive(Debug)]
+/// Appends between automatic account checkpoints.
+const CHECKPOINT_EVERY: usize = 1024;
+
+/// How the ledger scores accounts, and the account state it keeps: live,
+/// folded on append, and the newest checkpoint.
+pub(crate) struct Accounts {
+    pub(crate) decay: DecayPolicy, // how good deeds lose weight with age, per deed type
+    pub(crate) ceiling: CeilingPolicy, // how debt ceilings grow, draw down and recover
+    pub(crate) verifier: DeedVerifier, // which good deeds may grow a debt ceiling
+    pub(crate) live: AccountCheckpoint, // every account, folded on append
+    pub(crate) checkpoint: Option<AccountCheckpoint>, // newest per-account snapshot
+}
+
+impl Default for Accounts {
+    fn default() -> Self {
+        Self {
+            decay: DecayPolicy::default(),
+            ceiling: CeilingPolicy::default(),
+            verifier: Box::new(|_| false),
+            live: AccountCheckpoint::default(),
+            checkpoint: None,
+        }
+    }
+}
+
+impl Accounts {
+    /// Whether a chain of `len` deeds is `CHECKPOINT_EVERY` past the checkpoint.
+    pub(crate) fn checkpoint_due(&self, len: usize) -> bool {
+        len - self.checkpoint.as_ref().map_or(0, |cp| cp.event_count) >= CHECKPOINT_EVERY
+    }
+}
+
+/// Per-account aggregates over the first `event_count` deeds. Each account's
+/// accumulator is discounted to the time of its last deed's fold, at most
+/// `taken_at`, and can be read at any later time without a replay.
+#[derive(Debug, Clone, Default)]
+pub struct AccountCheckpoint {
+    pub event_count: usize,
+    pub chain_head: String, // last_hash at event_count
+    pub taken_at: u64,
+    pub good_deeds: HashMap<String, DecayAccumulator>,
+    pub harm_flags: HashMap<String, u32>,
+    pub ceilings: HashMap<String, DebtCeiling>, // without steps
+}
+
+impl AccountCheckpoint {
+    /// Roll this checkpoint forward over the deeds after `self.event_count`.
+    /// Only the accounts with new deeds are touched.
+    pub(crate) fn advance(mut self, ledger: &Ledger, now: u64) -> Self {
+        for pos in self.event_count..ledger.events.len() {
+            let actor_id = &ledger.events[pos].actor_id;
+            let good = self.good_deeds.entry(actor_id.clone()).or_insert_with(|| DecayAccumulator::new(now));
+            good.advance_to(now);
+            let harm = self.harm_flags.entry(actor_id.clone()).or_default();
+            let ceiling = self.ceilings.entry(actor_id.clone()).or_insert_with(|| DebtCeiling::new(&ledger.accounts.ceiling));
+            fold_effective(ledger, pos, good, harm, ceiling);
+        }
+        self.event_count = ledger.events.len();
+        self.chain_head = ledger.last_hash.clone();
+        self.taken_at = self.taken_at.max(now);
+        self
+    }
+}
+
+/// Count `event` into `good` with weight `mass` (-1 takes it back) if it is
+/// a good deed, under its deed type's decay kernel.
+fn add_good(policy: &DecayPolicy, event: &DeedEvent, good: &mut DecayAccumulator, mass: f64) {
+    if event.is_good_deed() {
+        good.add(policy.kernel_for(&event.deed_type), event.timestamp, mass);
+    }
+}
+
+/// Fold the deed at `pos` into an account as the effective view sees it:
+/// an applied correction replaces its target's contribution with the
+/// corrected one, and correction deeds are not deeds of their own. The
+/// ceiling clamps at every step, so a correction refolds the actor's
+/// ceiling instead of swapping one contribution.
+fn fold_effective(ledger: &Ledger, pos: usize, good: &mut DecayAccumulator, harm: &mut u32, ceiling: &mut DebtCeiling) {
+    let event = &ledger.events[pos];
+    if let Some((before, after)) = ledger.correction_at(pos) {
+        add_good(&ledger.accounts.decay, &before, good, -1.0);
+        add_good(&ledger.accounts.decay, &after, good, 1.0);
+        *harm = *harm + u32::from(after.life_harm_flag) - u32::from(before.life_harm_flag);
+        *ceiling = replay_ceiling(ledger, &event.actor_id, pos + 1, false);
+    } else if event.correction().is_none() {
+        add_good(&ledger.accounts.decay, event, good, 1.0);
+        *harm += u32::from(event.life_harm_flag);
+        fold_ceiling(ledger, ceiling, pos, event, false);
+    }
+}
+
+/// The point in the chain an account is computed at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsOf {
+    /// Every deed, discounted to the ledger clock's current time.
+    Now,
+    /// Deeds stamped at or before this time, discounted to it. They are a
+    /// prefix of the chain, since `Ledger::append` keeps timestamps in order.
+    Time(u64),
+    /// The first `n` deeds, discounted to the latest timestamp among them.
+    Height(usize),
+}
+
+impl AsOf {
+    /// Ledger height and evaluation time this point resolves to.
+    pub(crate) fn resolve(self, ledger: &Ledger) -> (usize, u64) {
+        let events = &ledger.events;
+        match self {
+            AsOf::Now => (events.len(), ledger.clock.unix_seconds() as u64),
+            AsOf::Time(at) => (events.partition_point(|e| e.timestamp <= at), at),
+            AsOf::Height(n) => {
+                let n = n.min(events.len());
+                (n, events[..n].iter().map(|e| e.timestamp).max().unwrap_or(0))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct ChurchAccountState {
     pub cumulative_good_deeds: f64, // Time-discounted sum
     pub cumulative_harm_flags: u32,
     pub eco_score: f64, // Convex combo: 0.7 * good_deeds_norm + 0.3 * (1 - harm_norm)
-    pub debt_ceiling: f64, // Reduced by harm
+    pub debt_ceiling: f64, // Grows with verified good deeds, drawn down by consequences; see `DebtCeiling`
     pub church_balance: f64, // Minted tokens
 }
 
 impl ChurchAccountState {
+    /// Reads the ledger's live accumulators, which are kept current on every
+    /// append. Corrected deeds count in their corrected form.
     pub fn compute_from_ledger(ledger: &Ledger, actor_id: &str) -> Option<Self> {
-        let events = ledger.events_for_actor(actor_id);
-        if events.is_empty() {
+        Self::compute_as_of(ledger, actor_id, AsOf::Now)
+    }
+
+    /// The account as it stood at `as_of`: only deeds (and corrections)
+    /// before that point count. Resumes from the live accumulators or the
+    /// checkpoint, whichever is newest at or before the point, and replays
+    /// the actor's deeds after it.
+    pub fn compute_as_of(ledger: &Ledger, actor_id: &str, as_of: AsOf) -> Option<Self> {
+        let (height, now) = as_of.resolve(ledger);
+        let positions = ledger.positions_for_actor_since(actor_id, 0);
+        let positions = &positions[..positions.partition_point(|&i| i < height)];
+        if positions.is_empty() {
             return None;
         }
 
-        let now = Utc::now().timestamp() as u64;
-        let mut good_deeds = 0.0;
+        let mut good = DecayAccumulator::new(now);
         let mut harm_flags = 0;
-
-        for event in events {
-            let age = now - event.timestamp;
-            let discount = time_discount_factor(age);
-            if event.is_good_deed() {
-                good_deeds += 1.0 * discount;
+        let mut ceiling = DebtCeiling::new(&ledger.accounts.ceiling);
+        let mut replay_from = 0;
+        let resume = [Some(&ledger.accounts.live), ledger.checkpoint()]
+            .into_iter()
+            .flatten()
+            .filter(|cp| cp.event_count <= height && cp.taken_at <= now)
+            .max_by_key(|cp| cp.event_count);
+        if let Some(cp) = resume {
+            if let Some(acc) = cp.good_deeds.get(actor_id) {
+                good = acc.clone();
+                good.advance_to(now);
             }
-            if event.life_harm_flag {
-                harm_flags += 1;
+            harm_flags = cp.harm_flags.get(actor_id).copied().unwrap_or(0);
+            if let Some(resumed) = cp.ceilings.get(actor_id) {
+                ceiling = resumed.clone();
             }
+            replay_from = cp.event_count;
         }
 
+        for &pos in &positions[positions.partition_point(|&i| i < replay_from)..] {
+            fold_effective(ledger, pos, &mut good, &mut harm_flags, &mut ceiling);
+        }
+        let good_deeds = good.value();
+
         let good_deeds_norm = good_deeds.min(1.0);
         let harm_norm = (harm_flags as f64 / 10.0).min(1.0); // Cap at 10 harms
         let eco_score = 0.7 * good_deeds_norm + 0.3 * (1.0 - harm_norm);
-        let debt_ceiling = 1.0 - harm_norm;
+        let debt_ceiling = ceiling.ceiling;
         let church_balance = good_deeds * 0.1; // Symbolic mint per good deed
 
         Some(Self {
@@ -48,6 +191,18 @@ impl ChurchAccountState {
         })
     }
 
+    /// How the account changed between two points; `None` on a side where
+    /// the actor had no deeds yet.
+    pub fn diff(ledger: &Ledger, actor_id: &str, from: AsOf, to: AsOf) -> AccountDiff {
+        let before = Self::compute_as_of(ledger, actor_id, from);
+        let after = Self::compute_as_of(ledger, actor_id, to);
+        let (from_height, _) = from.resolve(ledger);
+        let (to_height, _) = to.resolve(ledger);
+        let positions = ledger.positions_for_actor_since(actor_id, from_height);
+        let new_deeds = positions.partition_point(|&i| i < to_height);
+        AccountDiff { before, after, new_deeds }
+    }
+
     pub fn can_mint_church(&self) -> bool {
         self.cumulative_harm_flags == 0 && self.eco_score > 0.5
     }
@@ -58,7 +213,40 @@ impl ChurchAccountState {
 
     // Rare-item: Simulates NEUROMORPH-GOD quorum for forgiveness
     pub fn forgiveness_quorum(roles: &[String], required_quorum: usize) -> bool {
-        let required = vec!["Host", "OrganicCPUOwner", "Regulator", "SovereignKernel"];
+        let required = ["Host", "OrganicCPUOwner", "Regulator", "SovereignKernel"];
         roles.iter().filter(|r| required.contains(&r.as_str())).count() >= required_quorum
     }
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountDiff {
+    pub before: Option<ChurchAccountState>,
+    pub after: Option<ChurchAccountState>,
+    pub new_deeds: usize, // deeds by the actor between the two points
+}
+
+impl AccountDiff {
+    fn delta(&self, field: impl Fn(&ChurchAccountState) -> f64) -> f64 {
+        self.after.as_ref().map_or(0.0, &field) - self.before.as_ref().map_or(0.0, &field)
+    }
+
+    pub fn eco_score(&self) -> f64 {
+        self.delta(|s| s.eco_score)
+    }
+
+    pub fn debt_ceiling(&self) -> f64 {
+        self.delta(|s| s.debt_ceiling)
+    }
+
+    pub fn good_deeds(&self) -> f64 {
+        self.delta(|s| s.cumulative_good_deeds)
+    }
+
+    pub fn harm_flags(&self) -> i64 {
+        self.delta(|s| f64::from(s.cumulative_harm_flags)) as i64
+    }
+
+    pub fn church_balance(&self) -> f64 {
+        self.delta(|s| s.church_balance)
+    }
+}
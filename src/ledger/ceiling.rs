@@ -0,0 +1,55 @@
+use crate::ledger::{DeedEvent, Ledger};
+use deed_schema::CorrectableDeed;
+
+pub use token_journal::ceiling::{
+    gates, Cause, CeilingBook, CeilingDeed, CeilingError, CeilingPolicy, CeilingStep, DebtCeiling, Gate, GOOD_DEED_TAGS,
+    RESOURCE_OVERDRAW_TAG, RESTORATIVE_TAG,
+};
+
+/// Whether a deed is verified, by a signature or evidence the caller checks.
+/// Only verified good deeds grow a debt ceiling.
+pub type DeedVerifier = Box<dyn Fn(&DeedEvent) -> bool + Send + Sync>;
+
+impl CeilingDeed for DeedEvent {
+    fn event_id(&self) -> String {
+        self.event_id.clone()
+    }
+
+    fn deed_type(&self) -> &str {
+        &self.deed_type
+    }
+
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    fn ethics_flags(&self) -> &[String] {
+        &self.ethics_flags
+    }
+
+    fn life_harm_flag(&self) -> bool {
+        self.life_harm_flag
+    }
+}
+
+/// Replay `actor_id`'s deeds below position `height`, in their corrected
+/// form as of `height`, recording every step if `explain` is set.
+/// Correction deeds are not deeds of their own.
+pub(crate) fn replay_ceiling(ledger: &Ledger, actor_id: &str, height: usize, explain: bool) -> DebtCeiling {
+    let mut ceiling = DebtCeiling::new(&ledger.accounts.ceiling);
+    for &pos in ledger.positions_for_actor_since(actor_id, 0).iter().take_while(|&&pos| pos < height) {
+        if ledger.events[pos].correction().is_some() {
+            continue;
+        }
+        if let Some(event) = ledger.effective_event(pos, height) {
+            fold_ceiling(ledger, &mut ceiling, pos, &event, explain);
+        }
+    }
+    ceiling
+}
+
+/// Fold `event` at `pos` into `ceiling` under the ledger's policy and verifier.
+pub(crate) fn fold_ceiling(ledger: &Ledger, ceiling: &mut DebtCeiling, pos: usize, event: &DeedEvent, explain: bool) {
+    let accounts = &ledger.accounts;
+    ceiling.fold(&accounts.ceiling, pos as u64, event, (accounts.verifier)(event), explain);
+}
@@ -0,0 +1,125 @@
+use crate::utils::time::DecayKernel;
+use std::collections::BTreeMap;
+
+/// Decayed sum of one account's good deeds, kept current as deeds arrive.
+///
+/// Exponential kernels need one running sum each, since re-discounting the
+/// sum is the same as discounting every deed. Linear and step kernels keep
+/// the deeds still inside their span and drop them as they age out, so
+/// neither needs a replay of the account's history.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecayAccumulator {
+    at: u64,
+    tracks: Vec<(DecayKernel, Track)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Track {
+    Sum(f64),                 // exponential: discounted to `at`
+    Live(BTreeMap<u64, f64>), // windowed: timestamp -> weight mass
+}
+
+impl DecayAccumulator {
+    pub fn new(at: u64) -> Self {
+        Self { at, tracks: Vec::new() }
+    }
+
+    /// Time the sum is discounted to.
+    pub fn at(&self) -> u64 {
+        self.at
+    }
+
+    /// Add `mass` (1 for a deed, -1 to take one back) stamped `timestamp`.
+    /// A deed stamped after `at` counts as stamped at `at`.
+    pub fn add(&mut self, kernel: DecayKernel, timestamp: u64, mass: f64) {
+        let timestamp = timestamp.min(self.at);
+        let at = self.at;
+        match self.track(kernel) {
+            Track::Sum(sum) => *sum += mass * kernel.weight(at - timestamp),
+            Track::Live(live) => {
+                if kernel.span().is_some_and(|span| at - timestamp < span) {
+                    *live.entry(timestamp).or_default() += mass;
+                }
+            }
+        }
+    }
+
+    /// Move the sum forward to `now`. Earlier times are ignored.
+    pub fn advance_to(&mut self, now: u64) {
+        if now <= self.at {
+            return;
+        }
+        let elapsed = now - self.at;
+        for (kernel, track) in &mut self.tracks {
+            match track {
+                Track::Sum(sum) => *sum *= kernel.weight(elapsed),
+                Track::Live(live) => {
+                    // Keep deeds younger than the span: stamped after now - span.
+                    if let Some(cutoff) = kernel.span().and_then(|span| now.checked_sub(span)) {
+                        *live = live.split_off(&(cutoff + 1));
+                    }
+                }
+            }
+        }
+        self.tracks.retain(|(_, track)| !matches!(track, Track::Live(live) if live.is_empty()));
+        self.at = now;
+    }
+
+    /// The sum at `at`.
+    pub fn value(&self) -> f64 {
+        self.discounted_to(self.at)
+    }
+
+    /// The sum at `now`, which must not be before `at`.
+    pub fn discounted_to(&self, now: u64) -> f64 {
+        let elapsed = now.saturating_sub(self.at);
+        self.tracks
+            .iter()
+            .map(|(kernel, track)| match track {
+                Track::Sum(sum) => sum * kernel.weight(elapsed),
+                Track::Live(live) => live.iter().map(|(&t, mass)| mass * kernel.weight(now.saturating_sub(t))).sum(),
+            })
+            .sum()
+    }
+
+    fn track(&mut self, kernel: DecayKernel) -> &mut Track {
+        let idx = match self.tracks.iter().position(|(k, _)| *k == kernel) {
+            Some(idx) => idx,
+            None => {
+                let track = if kernel.span().is_some() { Track::Live(BTreeMap::new()) } else { Track::Sum(0.0) };
+                self.tracks.push((kernel, track));
+                self.tracks.len() - 1
+            }
+        };
+        &mut self.tracks[idx].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulator_matches_direct_sum() {
+        let kernels = [
+            DecayKernel::Exponential { half_life: 3600.0 },
+            DecayKernel::Linear { lifetime: 7200 },
+            DecayKernel::Step { window: 5000 },
+        ];
+        let deeds = [(0, 0), (1000, 1), (1500, 2), (4000, 0), (4000, 1), (9000, 2)];
+        let mut acc = DecayAccumulator::new(0);
+        for &(t, k) in &deeds {
+            acc.advance_to(t);
+            acc.add(kernels[k], t, 1.0);
+        }
+        acc.add(kernels[1], 4000, -1.0);
+
+        for now in [9000, 10_000, 12_000, 20_000] {
+            let direct: f64 = deeds.iter().map(|&(t, k)| kernels[k].weight(now - t)).sum::<f64>() - kernels[1].weight(now - 4000);
+            assert!((acc.discounted_to(now) - direct).abs() < 1e-12);
+            acc.advance_to(now);
+            assert!((acc.value() - direct).abs() < 1e-12);
+        }
+        assert_eq!(acc.tracks.len(), 1);
+    }
+}
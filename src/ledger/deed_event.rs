@@ -24,8 +24,67 @@ impl DeedEvent {
         compute_sha256_hash(serialized.as_bytes())
     }
 
+    /// Clean and tagged with one of `GOOD_DEED_TAGS`.
     pub fn is_good_deed(&self) -> bool {
-        !self.life_harm_flag && self.ethics_flags.is_empty() &&
-        self.tags.iter().any(|t| matches!(t.as_str(), "ecological_sustainability" | "homelessness_relief" | "math_science_education"))
+        token_journal::CeilingDeed::is_good_deed(self)
+    }
+}
+
+/// Lossless conversion into the canonical schema (u64 timestamp must fit i64).
+impl TryFrom<DeedEvent> for deed_schema::DeedEvent {
+    type Error = deed_schema::SchemaError;
+
+    fn try_from(e: DeedEvent) -> Result<Self, Self::Error> {
+        Ok(deed_schema::DeedEvent {
+            schema_version: deed_schema::UNVERSIONED,
+            event_id: deed_schema::parse_event_id(&e.event_id)?,
+            timestamp: deed_schema::timestamp_from_u64(e.timestamp)?,
+            timestamp_nanos: 0,
+            prev_hash: e.prev_hash,
+            self_hash: e.self_hash,
+            actor_id: e.actor_id,
+            target_ids: e.target_ids,
+            deed_type: e.deed_type,
+            tags: e.tags,
+            context_json: e.context_json,
+            ethics_flags: e.ethics_flags,
+            life_harm_flag: e.life_harm_flag,
+            extensions: serde_json::Map::new(),
+            signature: None,
+            disclosures: deed_schema::Disclosures::new(),
+        })
+    }
+}
+
+impl deed_schema::CorrectableDeed for DeedEvent {
+    fn id(&self) -> String {
+        self.event_id.clone()
+    }
+
+    fn actor(&self) -> &str {
+        &self.actor_id
+    }
+
+    fn kind(&self) -> &str {
+        &self.deed_type
+    }
+
+    fn context(&self) -> &Value {
+        &self.context_json
+    }
+
+    fn ethics_flags(&self) -> &[String] {
+        &self.ethics_flags
+    }
+
+    fn fields_mut(&mut self) -> deed_schema::DeedFieldsMut<'_> {
+        deed_schema::DeedFieldsMut {
+            deed_type: &mut self.deed_type,
+            target_ids: Some(&mut self.target_ids),
+            tags: Some(&mut self.tags),
+            context_json: &mut self.context_json,
+            ethics_flags: &mut self.ethics_flags,
+            life_harm_flag: &mut self.life_harm_flag,
+        }
     }
 }
@@ -1,14 +1,39 @@
 mod deed_event;
 mod account;
+mod decay;
+mod ceiling;
 
 pub use deed_event::DeedEvent;
-pub use account::ChurchAccountState;
+pub use account::{AccountCheckpoint, AccountDiff, AsOf, ChurchAccountState};
+use account::Accounts;
+pub use decay::DecayAccumulator;
+pub use ceiling::{
+    gates, Cause, CeilingBook, CeilingDeed, CeilingError, CeilingPolicy, CeilingStep, DebtCeiling, DeedVerifier, Gate,
+    GOOD_DEED_TAGS, RESOURCE_OVERDRAW_TAG, RESTORATIVE_TAG,
+};
 
-use std::collections::HashMap;
+use crate::utils::time::{DecayPolicy, InvalidDecay};
 
+use deed_schema::correction::check_correction;
+use deed_schema::{Clock, CorrectableDeed, SystemClock};
+use std::collections::{BTreeMap, HashMap};
+
+/// The chain and its lookups. Account scoring and state are one `Accounts`
+/// component, folded after every append.
 pub struct Ledger {
     events: Vec<DeedEvent>,
     last_hash: String,
+    by_actor: HashMap<String, Vec<usize>>, // actor_id -> positions, maintained on append
+    by_id: HashMap<String, usize>, // event_id -> first position
+    corrections: BTreeMap<usize, usize>, // applied correction position -> corrected position
+    clock: Box<dyn Clock>, // "now" for checkpoints and current account state
+    accounts: Accounts, // scoring policies, live accounts and the newest checkpoint
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Ledger {
@@ -16,15 +41,142 @@ impl Ledger {
         Ledger {
             events: Vec::new(),
             last_hash: String::new(),
+            by_actor: HashMap::new(),
+            by_id: HashMap::new(),
+            corrections: BTreeMap::new(),
+            clock: Box::new(SystemClock),
+            accounts: Accounts::default(),
         }
     }
 
+    /// Use `clock` wherever the ledger needs the current time, so replays
+    /// and tests score accounts reproducibly.
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Decay good deeds under `policy`. Accounts already folded are folded
+    /// again under it, and the checkpoint is dropped. Fails, naming the
+    /// kernel parameter, if `policy` has an unusable kernel.
+    pub fn with_decay(mut self, policy: DecayPolicy) -> Result<Self, InvalidDecay> {
+        policy.check()?;
+        self.accounts.decay = policy;
+        self.refold_accounts();
+        Ok(self)
+    }
+
+    pub fn decay(&self) -> &DecayPolicy {
+        &self.accounts.decay
+    }
+
+    /// Move debt ceilings under `policy`. Accounts already folded are folded
+    /// again under it, and the checkpoint is dropped. Fails with
+    /// `CeilingError::InvalidPolicy`, naming the field, if `policy` is not
+    /// usable.
+    pub fn with_ceiling_policy(mut self, policy: CeilingPolicy) -> Result<Self, CeilingError> {
+        policy.check()?;
+        self.accounts.ceiling = policy;
+        self.refold_accounts();
+        Ok(self)
+    }
+
+    pub fn ceiling_policy(&self) -> &CeilingPolicy {
+        &self.accounts.ceiling
+    }
+
+    /// Grow debt ceilings only for good deeds `verifier` accepts, e.g. those
+    /// with a valid signature or evidence the caller holds. Without one no
+    /// good deed is verified. Accounts already folded are folded again, and
+    /// the checkpoint is dropped.
+    pub fn with_verifier(mut self, verifier: DeedVerifier) -> Self {
+        self.accounts.verifier = verifier;
+        self.refold_accounts();
+        self
+    }
+
+    /// Fold every account again from genesis, under the current policies,
+    /// and drop the checkpoint.
+    fn refold_accounts(&mut self) {
+        self.accounts.checkpoint = None;
+        self.accounts.live = AccountCheckpoint::default().advance(self, self.clock.unix_seconds() as u64);
+    }
+
+    /// `actor_id`'s debt ceiling at `as_of`, with every step that led to it.
+    /// Replays the actor's history; `ChurchAccountState` reads the ceiling
+    /// kept current on append instead.
+    pub fn debt_ceiling(&self, actor_id: &str, as_of: AsOf) -> DebtCeiling {
+        ceiling::replay_ceiling(self, actor_id, as_of.resolve(self).0, true)
+    }
+
     pub fn append(&mut self, event: DeedEvent) {
         if event.prev_hash != self.last_hash {
             panic!("Invalid prev_hash");
         }
+        // `AsOf::Time` cuts the chain at a height, which needs ordered timestamps.
+        if self.events.last().is_some_and(|last| event.timestamp < last.timestamp) {
+            panic!("Timestamp before the previous deed's");
+        }
+        let pos = self.events.len();
+        self.by_actor.entry(event.actor_id.clone()).or_default().push(pos);
+        self.by_id.entry(event.event_id.clone()).or_insert(pos);
         self.events.push(event.clone());
         self.last_hash = event.self_hash;
+        self.apply_correction(pos);
+        let live = std::mem::take(&mut self.accounts.live);
+        self.accounts.live = live.advance(self, self.clock.unix_seconds() as u64);
+        if self.accounts.checkpoint_due(self.events.len()) {
+            self.take_checkpoint(self.clock.unix_seconds() as u64);
+        }
+    }
+
+    /// Fold every deed since the previous checkpoint into a new one taken at
+    /// `now`. Account queries then only replay deeds after it.
+    pub fn take_checkpoint(&mut self, now: u64) -> &AccountCheckpoint {
+        let checkpoint = self.accounts.checkpoint.take().unwrap_or_default().advance(self, now);
+        self.accounts.checkpoint.insert(checkpoint)
+    }
+
+    /// Record the deed at `pos` as applied if it is a valid correction. An
+    /// invalid one stays in the chain without effect.
+    fn apply_correction(&mut self, pos: usize) {
+        let event = &self.events[pos];
+        let Some(Ok(correction)) = event.correction() else {
+            return;
+        };
+        let Some(target) = self.by_id.get(&correction.corrects).copied().filter(|&t| t < pos) else {
+            return;
+        };
+        if check_correction(event, &correction, self.effective_event(target, pos).as_ref()).is_ok() {
+            self.corrections.insert(pos, target);
+        }
+    }
+
+    /// The deed at `pos` with every correction before position `upto`
+    /// applied; `events` keeps the original.
+    pub fn effective_event(&self, pos: usize, upto: usize) -> Option<DeedEvent> {
+        let mut event = self.events.get(pos)?.clone();
+        for (&at, _) in self.corrections.range(..upto).filter(|(_, &target)| target == pos) {
+            if let Some(Ok(correction)) = self.events[at].correction() {
+                event.supersede(&correction.set);
+            }
+        }
+        Some(event)
+    }
+
+    /// For an applied correction at `pos`: its target just before and just
+    /// after it.
+    pub(crate) fn correction_at(&self, pos: usize) -> Option<(DeedEvent, DeedEvent)> {
+        let target = *self.corrections.get(&pos)?;
+        Some((self.effective_event(target, pos)?, self.effective_event(target, pos + 1)?))
+    }
+
+    pub fn checkpoint(&self) -> Option<&AccountCheckpoint> {
+        self.accounts.checkpoint.as_ref()
     }
 
     pub fn last_hash(&self) -> &str {
@@ -32,6 +184,22 @@ impl Ledger {
     }
 
     pub fn events_for_actor(&self, actor_id: &str) -> Vec<&DeedEvent> {
-        self.events.iter().filter(|e| e.actor_id == actor_id).collect()
+        self.by_actor
+            .get(actor_id)
+            .map(|positions| positions.iter().map(|&i| &self.events[i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Deeds by `actor_id` at positions `from` and later.
+    pub fn events_for_actor_since(&self, actor_id: &str, from: usize) -> Vec<&DeedEvent> {
+        self.positions_for_actor_since(actor_id, from).iter().map(|&i| &self.events[i]).collect()
+    }
+
+    pub(crate) fn positions_for_actor_since(&self, actor_id: &str, from: usize) -> &[usize] {
+        let Some(positions) = self.by_actor.get(actor_id) else {
+            return &[];
+        };
+        let start = positions.partition_point(|&i| i < from);
+        &positions[start..]
     }
 }
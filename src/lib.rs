@@ -0,0 +1,3 @@
+pub mod kernel;
+pub mod ledger;
+pub mod utils;
@@ -1,5 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::LN_2;
+use thiserror::Error;
+
+const DAY: f64 = 86400.0;
+
+/// Weight of a deed `age_seconds` old under the default kernel.
 pub fn time_discount_factor(age_seconds: u64) -> f64 {
-    // Exponential decay: e^(-age / tau), tau = 1 day
-    let tau = 86400.0;
-    (-(age_seconds as f64) / tau).exp()
+    DecayKernel::default().weight(age_seconds)
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("invalid decay policy: {field} must be positive")]
+pub struct InvalidDecay {
+    pub field: String, // e.g. "default.half_life" or "by_deed_type.cleanup.window"
+}
+
+/// How a good deed's weight falls with age.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DecayKernel {
+    /// Halves every `half_life` seconds.
+    Exponential { half_life: f64 },
+    /// Falls from 1 to 0 over `lifetime` seconds.
+    Linear { lifetime: u64 },
+    /// 1 for `window` seconds, then 0.
+    Step { window: u64 },
+}
+
+impl Default for DecayKernel {
+    /// e^(-age / 1 day), the original discount.
+    fn default() -> Self {
+        DecayKernel::Exponential { half_life: DAY * LN_2 }
+    }
+}
+
+impl DecayKernel {
+    pub fn weight(&self, age_seconds: u64) -> f64 {
+        let age = age_seconds as f64;
+        match *self {
+            DecayKernel::Exponential { half_life } => (-age * LN_2 / half_life).exp(),
+            DecayKernel::Linear { lifetime } => (1.0 - age / lifetime as f64).max(0.0),
+            DecayKernel::Step { window } => f64::from(u8::from(age_seconds < window)),
+        }
+    }
+
+    /// Age from which the weight is 0; `None` for exponential decay.
+    pub fn span(&self) -> Option<u64> {
+        match *self {
+            DecayKernel::Exponential { .. } => None,
+            DecayKernel::Linear { lifetime } => Some(lifetime),
+            DecayKernel::Step { window } => Some(window),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.invalid_field().is_none()
+    }
+
+    /// The parameter that is out of range, if any.
+    fn invalid_field(&self) -> Option<&'static str> {
+        match *self {
+            DecayKernel::Exponential { half_life } if !(half_life.is_finite() && half_life > 0.0) => Some("half_life"),
+            DecayKernel::Linear { lifetime: 0 } => Some("lifetime"),
+            DecayKernel::Step { window: 0 } => Some("window"),
+            _ => None,
+        }
+    }
+}
+
+/// Decay kernel per deed type, with a default for the rest.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DecayPolicy {
+    #[serde(default)]
+    pub default: DecayKernel,
+    #[serde(default)]
+    pub by_deed_type: HashMap<String, DecayKernel>,
+}
+
+impl DecayPolicy {
+    pub fn new(default: DecayKernel) -> Self {
+        Self { default, by_deed_type: HashMap::new() }
+    }
+
+    pub fn with_kernel(mut self, deed_type: &str, kernel: DecayKernel) -> Self {
+        self.by_deed_type.insert(deed_type.to_string(), kernel);
+        self
+    }
+
+    pub fn kernel_for(&self, deed_type: &str) -> DecayKernel {
+        self.by_deed_type.get(deed_type).copied().unwrap_or(self.default)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.check().is_ok()
+    }
+
+    /// Every kernel is usable; otherwise names the first parameter that is
+    /// not, the default kernel first and then by deed type in name order.
+    pub fn check(&self) -> Result<(), InvalidDecay> {
+        let mut kernels: Vec<_> = self.by_deed_type.iter().map(|(t, k)| (format!("by_deed_type.{}", t), k)).collect();
+        kernels.sort_by(|a, b| a.0.cmp(&b.0));
+        kernels.insert(0, ("default".to_string(), &self.default));
+        match kernels.into_iter().find_map(|(path, k)| k.invalid_field().map(|f| format!("{}.{}", path, f))) {
+            Some(field) => Err(InvalidDecay { field }),
+            None => Ok(()),
+        }
+    }
 }
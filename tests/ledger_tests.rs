@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use super::super::ledger::{DeedEvent, Ledger, ChurchAccountState};
+    use church_of_fear_ledger::ledger::{AsOf, Cause, CeilingError, CeilingPolicy, DeedEvent, DeedVerifier, Ledger, ChurchAccountState};
+    use church_of_fear_ledger::utils::time::{DecayKernel, DecayPolicy};
     use serde_json::json;
     use uuid::Uuid;
 
@@ -26,8 +27,8 @@ mod tests {
     }
 
     #[test]
-    fn test_account_compute() {
-        let mut ledger = Ledger::new();
+    fn test_account_compute_counts_a_fresh_good_deed() {
+        let mut ledger = Ledger::new().with_clock(Box::new(deed_schema::FixedClock::new(0)));
         let mut deed_good = DeedEvent {
             event_id: Uuid::new_v4().to_string(),
             timestamp: 0,
@@ -36,7 +37,7 @@ mod tests {
             actor_id: "test".to_string(),
             target_ids: vec![],
             deed_type: "ecological_sustainability".to_string(),
-            tags: vec!["tree_planting".to_string()],
+            tags: vec!["ecological_sustainability".to_string()],
             context_json: json!({}),
             ethics_flags: vec![],
             life_harm_flag: false,
@@ -46,6 +47,332 @@ mod tests {
 
         let state = ChurchAccountState::compute_from_ledger(&ledger, "test").unwrap();
         assert!(state.can_mint_church());
-        assert_eq!(state.compute_mint_amount(), 7.0); // Assuming eco_score=0.7
+        assert_eq!(state.compute_mint_amount(), 10.0); // eco_score = 0.7 * 1 fresh deed + 0.3 * no harm
+    }
+
+    #[test]
+    fn test_account_compute_resumes_from_checkpoint() {
+        let mut ledger = Ledger::new();
+        let now = chrono::Utc::now().timestamp() as u64;
+        for (i, harm) in [false, true, false].into_iter().enumerate() {
+            let mut deed = DeedEvent {
+                event_id: Uuid::new_v4().to_string(),
+                timestamp: now - 3600,
+                prev_hash: ledger.last_hash().to_string(),
+                self_hash: String::new(),
+                actor_id: "test".to_string(),
+                target_ids: vec![],
+                deed_type: "ecological_sustainability".to_string(),
+                tags: vec!["ecological_sustainability".to_string()],
+                context_json: json!({ "n": i }),
+                ethics_flags: vec![],
+                life_harm_flag: harm,
+            };
+            deed.self_hash = deed.compute_self_hash();
+            ledger.append(deed);
+            if i == 0 {
+                ledger.take_checkpoint(now);
+            }
+        }
+
+        let resumed = ChurchAccountState::compute_from_ledger(&ledger, "test").unwrap();
+        assert_eq!(ledger.checkpoint().unwrap().event_count, 1);
+        assert_eq!(resumed.cumulative_harm_flags, 1);
+        let expected = 2.0 * (-3600.0f64 / 86400.0).exp();
+        assert!((resumed.cumulative_good_deeds - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_account_compute_uses_corrected_deeds() {
+        let mut ledger = Ledger::new();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let deed = |ledger: &mut Ledger, actor: &str, deed_type: &str, tags: Vec<String>, context: serde_json::Value| {
+            let mut deed = DeedEvent {
+                event_id: Uuid::new_v4().to_string(),
+                timestamp: now,
+                prev_hash: ledger.last_hash().to_string(),
+                self_hash: String::new(),
+                actor_id: actor.to_string(),
+                target_ids: vec![],
+                deed_type: deed_type.to_string(),
+                tags,
+                context_json: context,
+                ethics_flags: vec![],
+                life_harm_flag: false,
+            };
+            deed.self_hash = deed.compute_self_hash();
+            ledger.append(deed.clone());
+            deed
+        };
+        let original = deed(&mut ledger, "test", "tree_planting", vec![], json!({}));
+        let fix = json!({
+            "corrects": original.event_id,
+            "set": { "tags": ["ecological_sustainability"] },
+            "reason": "missing tag",
+        });
+        deed(&mut ledger, "mallory", "correction", vec![], fix.clone());
+        assert_eq!(ChurchAccountState::compute_from_ledger(&ledger, "test").unwrap().cumulative_good_deeds, 0.0);
+
+        deed(&mut ledger, "test", "correction", vec![], fix);
+        let state = ChurchAccountState::compute_from_ledger(&ledger, "test").unwrap();
+        assert!((state.cumulative_good_deeds - 1.0).abs() < 1e-3);
+        assert!(ledger.events_for_actor("test")[0].tags.is_empty());
+        assert_eq!(ledger.effective_event(0, usize::MAX).unwrap().tags, vec!["ecological_sustainability".to_string()]);
+
+        ledger.take_checkpoint(now);
+        assert!((ledger.checkpoint().unwrap().good_deeds["test"].value() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_account_as_of_and_diff() {
+        let mut ledger = Ledger::new().with_verifier(Box::new(|_| true));
+        let start = 1_700_000_000;
+        for (i, harm) in [false, false, true].into_iter().enumerate() {
+            let mut deed = DeedEvent {
+                event_id: Uuid::new_v4().to_string(),
+                timestamp: start + i as u64 * 86400,
+                prev_hash: ledger.last_hash().to_string(),
+                self_hash: String::new(),
+                actor_id: "test".to_string(),
+                target_ids: vec![],
+                deed_type: "ecological_sustainability".to_string(),
+                tags: vec!["ecological_sustainability".to_string()],
+                context_json: json!({ "n": i }),
+                ethics_flags: vec![],
+                life_harm_flag: harm,
+            };
+            deed.self_hash = deed.compute_self_hash();
+            ledger.append(deed);
+        }
+
+        assert!(ChurchAccountState::compute_as_of(&ledger, "test", AsOf::Time(start - 1)).is_none());
+        let day_one = ChurchAccountState::compute_as_of(&ledger, "test", AsOf::Time(start + 3600)).unwrap();
+        assert_eq!(day_one.cumulative_harm_flags, 0);
+        assert!((day_one.cumulative_good_deeds - (-3600.0f64 / 86400.0).exp()).abs() < 1e-9);
+        let at_height = ChurchAccountState::compute_as_of(&ledger, "test", AsOf::Height(2));
+        assert_eq!(at_height, ChurchAccountState::compute_as_of(&ledger, "test", AsOf::Time(start + 86400)));
+
+        ledger.take_checkpoint(start + 3 * 86400);
+        let diff = ChurchAccountState::diff(&ledger, "test", AsOf::Height(1), AsOf::Height(3));
+        assert_eq!(diff.new_deeds, 2);
+        assert_eq!(diff.harm_flags(), 1);
+        assert!((diff.debt_ceiling() + 0.1).abs() < 1e-9);
+        assert_eq!(diff.after, ChurchAccountState::compute_as_of(&ledger, "test", AsOf::Height(3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Timestamp before the previous deed's")]
+    fn test_append_refuses_timestamps_that_go_backwards() {
+        let mut ledger = Ledger::new();
+        let start = 1_700_000_000;
+        for timestamp in [start, start, start - 1] {
+            let mut deed = DeedEvent {
+                event_id: Uuid::new_v4().to_string(),
+                timestamp,
+                prev_hash: ledger.last_hash().to_string(),
+                self_hash: String::new(),
+                actor_id: "test".to_string(),
+                target_ids: vec![],
+                deed_type: "ecological_sustainability".to_string(),
+                tags: vec![],
+                context_json: json!({}),
+                ethics_flags: vec![],
+                life_harm_flag: false,
+            };
+            deed.self_hash = deed.compute_self_hash();
+            ledger.append(deed);
+            let diff = ChurchAccountState::diff(&ledger, "test", AsOf::Time(start - 1), AsOf::Time(start));
+            assert_eq!(diff.new_deeds, ledger.events_for_actor("test").len());
+        }
+    }
+
+    #[test]
+    fn test_account_compute_with_fixed_clock() {
+        let clock = deed_schema::FixedClock::new(1_700_086_400);
+        let mut ledger = Ledger::new().with_clock(Box::new(clock));
+        let mut deed = DeedEvent {
+            event_id: Uuid::new_v4().to_string(),
+            timestamp: 1_700_000_000,
+            prev_hash: String::new(),
+            self_hash: String::new(),
+            actor_id: "test".to_string(),
+            target_ids: vec![],
+            deed_type: "ecological_sustainability".to_string(),
+            tags: vec!["ecological_sustainability".to_string()],
+            context_json: json!({}),
+            ethics_flags: vec![],
+            life_harm_flag: false,
+        };
+        deed.self_hash = deed.compute_self_hash();
+        ledger.append(deed);
+
+        let state = ChurchAccountState::compute_from_ledger(&ledger, "test").unwrap();
+        assert_eq!(state.cumulative_good_deeds, (-1.0f64).exp());
+        assert_eq!(state, ChurchAccountState::compute_from_ledger(&ledger, "test").unwrap());
+    }
+
+    #[test]
+    fn test_account_decay_kernels_per_deed_type() {
+        let start = 1_700_000_000;
+        let day = 86400;
+        let policy = DecayPolicy::new(DecayKernel::Linear { lifetime: 10 * day })
+            .with_kernel("homelessness_relief", DecayKernel::Step { window: 2 * day });
+        let clock = deed_schema::FixedClock::new((start + day) as i64);
+        let mut ledger = Ledger::new().with_clock(Box::new(clock)).with_decay(policy).unwrap();
+        for deed_type in ["ecological_sustainability", "homelessness_relief"] {
+            let mut deed = DeedEvent {
+                event_id: Uuid::new_v4().to_string(),
+                timestamp: start,
+                prev_hash: ledger.last_hash().to_string(),
+                self_hash: String::new(),
+                actor_id: "test".to_string(),
+                target_ids: vec![],
+                deed_type: deed_type.to_string(),
+                tags: vec![deed_type.to_string()],
+                context_json: json!({}),
+                ethics_flags: vec![],
+                life_harm_flag: false,
+            };
+            deed.self_hash = deed.compute_self_hash();
+            ledger.append(deed);
+        }
+
+        let now = ChurchAccountState::compute_from_ledger(&ledger, "test").unwrap();
+        assert!((now.cumulative_good_deeds - 1.9).abs() < 1e-9);
+        let later = ChurchAccountState::compute_as_of(&ledger, "test", AsOf::Time(start + 3 * day)).unwrap();
+        assert!((later.cumulative_good_deeds - 0.7).abs() < 1e-9);
+
+        let broken = DecayPolicy::default().with_kernel("cleanup", DecayKernel::Step { window: 0 });
+        let err = Ledger::new().with_decay(broken).err().unwrap();
+        assert_eq!(err.field, "by_deed_type.cleanup.window");
+    }
+
+    #[test]
+    fn test_debt_ceiling_lifecycle() {
+        let policy = CeilingPolicy { cap: 1.15, ..CeilingPolicy::default() };
+        let mut ledger = Ledger::new().with_ceiling_policy(policy).unwrap().with_verifier(Box::new(|_| true));
+        let steps = [
+            ("ecological_sustainability", vec!["ecological_sustainability"], false),
+            ("homelessness_relief", vec!["homelessness_relief"], false),
+            ("river_dumping", vec![], true),
+            ("power_draw", vec!["resource_overdraw"], false),
+            ("cleanup", vec!["restorative"], false),
+        ];
+        for (deed_type, tags, harm) in steps {
+            let mut deed = DeedEvent {
+                event_id: Uuid::new_v4().to_string(),
+                timestamp: 1_700_000_000,
+                prev_hash: ledger.last_hash().to_string(),
+                self_hash: String::new(),
+                actor_id: "test".to_string(),
+                target_ids: vec![],
+                deed_type: deed_type.to_string(),
+                tags: tags.into_iter().map(String::from).collect(),
+                context_json: json!({}),
+                ethics_flags: vec![],
+                life_harm_flag: harm,
+            };
+            deed.self_hash = deed.compute_self_hash();
+            ledger.append(deed);
+        }
+
+        let ceiling = ledger.debt_ceiling("test", AsOf::Height(5));
+        let causes: Vec<_> = ceiling.steps.iter().map(|s| (s.position, s.cause)).collect();
+        assert_eq!(causes, vec![(0, Cause::GoodDeed), (1, Cause::GoodDeed), (2, Cause::Harm), (3, Cause::ResourceOverdraw), (4, Cause::Restorative)]);
+        assert!((ceiling.steps[1].ceiling - 1.15).abs() < 1e-9); // held at the cap
+        assert!((ceiling.ceiling - 0.9).abs() < 1e-9);
+        assert!((ceiling.drawdown - 0.25).abs() < 1e-9);
+        let state = ChurchAccountState::compute_from_ledger(&ledger, "test").unwrap();
+        assert_eq!(state.debt_ceiling, ceiling.ceiling);
+
+        let policy = ledger.ceiling_policy();
+        assert!((policy.allowance("grant", ceiling.ceiling).unwrap() - 90.0).abs() < 1e-9);
+        assert!(policy.check_request("grant", ceiling.ceiling, 80.0).is_ok());
+        assert!(matches!(policy.check_request("grant", ceiling.ceiling, 100.0), Err(CeilingError::OverAllowance { .. })));
+        assert_eq!(policy.check_request("rocket", 1.0, 1.0), Err(CeilingError::UnknownGate("rocket".to_string())));
+        assert!((ledger.debt_ceiling("test", AsOf::Height(2)).ceiling - 1.15).abs() < 1e-9);
+
+        let invalid = |policy| Ledger::new().with_ceiling_policy(policy).err().unwrap();
+        let field = |field: &str| CeilingError::InvalidPolicy { field: field.to_string() };
+        assert_eq!(invalid(CeilingPolicy { cap: 0.5, ..CeilingPolicy::default() }), field("cap"));
+        assert_eq!(invalid(CeilingPolicy { harm_penalty: -0.2, ..CeilingPolicy::default() }), field("harm_penalty"));
+        let mut gated = CeilingPolicy::default();
+        gated.gates.get_mut("grant").unwrap().max = f64::NAN;
+        assert_eq!(invalid(gated), field("gates.grant.max"));
+    }
+
+    #[test]
+    fn test_debt_ceiling_is_kept_on_append() {
+        let policy = CeilingPolicy { cap: 1.15, ..CeilingPolicy::default() };
+        let mut ledger = Ledger::new().with_ceiling_policy(policy).unwrap().with_verifier(Box::new(|_| true));
+        let append = |ledger: &mut Ledger, deed_type: &str, tags: Vec<&str>, context: serde_json::Value| {
+            let mut deed = DeedEvent {
+                event_id: Uuid::new_v4().to_string(),
+                timestamp: 1_700_000_000,
+                prev_hash: ledger.last_hash().to_string(),
+                self_hash: String::new(),
+                actor_id: "test".to_string(),
+                target_ids: vec![],
+                deed_type: deed_type.to_string(),
+                tags: tags.into_iter().map(String::from).collect(),
+                context_json: context,
+                ethics_flags: vec![],
+                life_harm_flag: false,
+            };
+            deed.self_hash = deed.compute_self_hash();
+            ledger.append(deed.clone());
+            deed
+        };
+        append(&mut ledger, "ecological_sustainability", vec!["ecological_sustainability"], json!({}));
+        let overdraw = append(&mut ledger, "power_draw", vec!["resource_overdraw"], json!({}));
+        ledger.take_checkpoint(1_700_000_000);
+        append(&mut ledger, "cleanup", vec!["restorative"], json!({}));
+        let fix = json!({ "corrects": overdraw.event_id, "set": { "tags": [] }, "reason": "metered wrong" });
+        append(&mut ledger, "correction", vec![], fix);
+
+        // The restorative deed no longer repays anything once the overdraw is
+        // corrected away, so the ceiling is refolded, not patched.
+        for height in 1..=4 {
+            let kept = ChurchAccountState::compute_as_of(&ledger, "test", AsOf::Height(height)).unwrap().debt_ceiling;
+            assert!((kept - ledger.debt_ceiling("test", AsOf::Height(height)).ceiling).abs() < 1e-9, "height {}", height);
+        }
+        assert!((ledger.debt_ceiling("test", AsOf::Height(3)).ceiling - 1.05).abs() < 1e-9);
+        assert!((ChurchAccountState::compute_from_ledger(&ledger, "test").unwrap().debt_ceiling - 1.1).abs() < 1e-9);
+        assert!(ledger.checkpoint().unwrap().ceilings["test"].steps.is_empty());
+    }
+
+    #[test]
+    fn test_debt_ceiling_grows_only_for_verified_deeds() {
+        let deed = |ledger: &Ledger, evidence: &str| {
+            let mut deed = DeedEvent {
+                event_id: Uuid::new_v4().to_string(),
+                timestamp: 1_700_000_000,
+                prev_hash: ledger.last_hash().to_string(),
+                self_hash: String::new(),
+                actor_id: "test".to_string(),
+                target_ids: vec![],
+                deed_type: "cleanup".to_string(),
+                tags: vec!["ecological_sustainability".to_string()],
+                context_json: json!({ "evidence": evidence }),
+                ethics_flags: vec![],
+                life_harm_flag: false,
+            };
+            deed.self_hash = deed.compute_self_hash();
+            deed
+        };
+        let verifier: DeedVerifier = Box::new(|deed: &DeedEvent| deed.context_json["evidence"] == "ipfs://receipt");
+        let mut ledger = Ledger::new().with_verifier(verifier);
+        ledger.append(deed(&ledger, "ipfs://receipt"));
+        ledger.append(deed(&ledger, "trust me"));
+
+        let ceiling = ledger.debt_ceiling("test", AsOf::Now);
+        assert_eq!(ceiling.steps.iter().map(|s| (s.position, s.cause)).collect::<Vec<_>>(), vec![(0, Cause::GoodDeed)]);
+        assert!((ceiling.ceiling - 1.1).abs() < 1e-9);
+        assert_eq!(ChurchAccountState::compute_from_ledger(&ledger, "test").unwrap().debt_ceiling, ceiling.ceiling);
+
+        // Without a verifier no good deed counts.
+        let mut unverified = Ledger::new();
+        unverified.append(deed(&unverified, "ipfs://receipt"));
+        assert_eq!(unverified.debt_ceiling("test", AsOf::Now).ceiling, 1.0);
     }
 }